@@ -14,6 +14,8 @@ macro_rules! svec {
     ($($x:expr),*) => (vec![$($x.to_string()),*]);
 }
 
+// Only one is ever created, boxing InstallFlags would gain nothing.
+#[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum DenoSubcommand {
   Bundle {
@@ -47,13 +49,7 @@ pub enum DenoSubcommand {
     json: bool,
     file: Option<String>,
   },
-  Install {
-    module_url: String,
-    args: Vec<String>,
-    name: Option<String>,
-    root: Option<PathBuf>,
-    force: bool,
-  },
+  Install(InstallFlags),
   Lint {
     files: Vec<String>,
     ignore: Vec<String>,
@@ -72,6 +68,12 @@ pub enum DenoSubcommand {
     filter: Option<String>,
   },
   Types,
+  Uninstall {
+    name: String,
+    root: Option<PathBuf>,
+    bin_subdir: Option<String>,
+    json: bool,
+  },
   Upgrade {
     dry_run: bool,
     force: bool,
@@ -81,12 +83,145 @@ pub enum DenoSubcommand {
   },
 }
 
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct InstallFlags {
+  pub module_url: String,
+  pub args: Vec<String>,
+  pub name: Option<String>,
+  pub root: Option<PathBuf>,
+  pub force: bool,
+  /// Succeed without doing anything if the script is already installed.
+  pub if_not_installed: bool,
+  /// Overwrite an existing installation without --force, as long as it is of
+  /// the same module.
+  pub update: bool,
+  pub keep_existing_config: bool,
+  /// Point the executable at the given config instead of a copy of it.
+  pub no_copy_config: bool,
+  /// Put the copies of the config, import map and lock file under
+  /// `$DENO_DIR/installs` instead of next to the executable.
+  pub copies_in_deno_dir: bool,
+  /// File name of the config copy, instead of `<name>.tsconfig.json`.
+  pub config_dest_name: Option<String>,
+  /// Substitute placeholders like `{{INSTALL_DIR}}` in the config copy.
+  pub config_template: bool,
+  /// Truncate inferred names to this many characters, reject longer explicit
+  /// names.
+  pub max_name_length: Option<usize>,
+  /// Append the first script argument, a subcommand, to the inferred name.
+  pub name_from_subcommand: bool,
+  pub use_module_hints: bool,
+  /// Name of an installed script to print the metadata of, instead of
+  /// installing.
+  pub info: Option<String>,
+  /// Output the installation, `info`, or errors, as JSON.
+  pub json: bool,
+  /// With -q/--quiet, don't print anything but errors.
+  pub quiet: bool,
+  /// Diagnose the installation directory instead of installing.
+  pub doctor: bool,
+  /// Remove the files left behind by scripts that are no longer installed
+  /// instead of installing.
+  pub prune: bool,
+  /// List the installed scripts instead of installing.
+  pub list: bool,
+  /// Pin the shell PATH hints are given for, instead of installing.
+  pub set_default_shell: Option<String>,
+  /// Shell to give the PATH hint for, instead of the pinned one or $SHELL.
+  pub shell: Option<String>,
+  /// Check whether a name can be installed under, instead of installing.
+  pub check_name: Option<String>,
+  /// Name of an installed script to install again with the options it was
+  /// installed with.
+  pub reinstall: Option<String>,
+  /// With `reinstall`, only write the executables again from the manifest.
+  pub metadata_only: bool,
+  /// Kinds of executable to generate (sh, cmd, ps1), the platform's own if
+  /// empty.
+  pub targets: Vec<String>,
+  /// Additional names to install the script under.
+  pub aliases: Vec<String>,
+  /// Environment variables the executables set before running deno.
+  pub env: Vec<(String, String)>,
+  /// Directory the executables change to before running deno.
+  pub run_dir: Option<PathBuf>,
+  /// Flags of `deno run` passed before the module URL, unlike `args`.
+  pub deno_args: Vec<String>,
+  /// Names to install the script under, each executable passing its own name
+  /// as the first argument.
+  pub names: Vec<String>,
+  /// File to read `module_url` from, keeping it out of the shell history.
+  pub module_file: Option<PathBuf>,
+  /// Install the module and each of `args` as separate scripts.
+  pub many: bool,
+  /// Install into `.deno` in the current directory instead of the root.
+  pub local: bool,
+  /// Install into a new temporary directory instead of the root, to try the
+  /// script out.
+  pub ephemeral: bool,
+  /// Directory of the root the executables are written to, `bin` if not
+  /// given, or `.` for the root itself.
+  pub bin_subdir: Option<String>,
+  /// Run the installed script with `--quiet`.
+  pub quiet_runtime: bool,
+  /// Leave out the hint to add the installation directory to PATH.
+  pub quiet_path_hint: bool,
+  /// Add the installation directory to PATH in the shell profile when it
+  /// isn't in PATH yet.
+  pub modify_path: bool,
+  /// The deno the executables run, instead of the one found in PATH.
+  pub deno_path: Option<PathBuf>,
+  /// Check that a remote module can be fetched before installing it.
+  pub verify: bool,
+  /// Record the checksum of the remote module in the metadata.
+  pub pin: bool,
+  /// With `pin`, the checksum of the remote module as it was fetched and
+  /// cached, set by the caller that fetched it rather than from the command
+  /// line.
+  pub module_checksum: Option<String>,
+  /// Mark the installation of a local module as a development install.
+  pub dev: bool,
+  /// Permissions of the executables on unix, `0o755` if not given.
+  pub mode: Option<u32>,
+  /// Where to move the files replaced by a force install, instead of keeping
+  /// them next to the new ones with a `.bak` extension.
+  pub backup_dir: Option<PathBuf>,
+  /// On Windows, a precompiled stub copied as `<name>.exe` instead of
+  /// writing a batch file.
+  pub trampoline: Option<PathBuf>,
+  /// Shell and subcommand of the script printing its completions for that
+  /// shell, to write a completion file for.
+  pub completions: Option<(String, String)>,
+  /// A comment line added at the top of the executables, eg. for audits.
+  pub header: Option<String>,
+  pub dry_run: bool,
+  pub print_args: bool,
+}
+
 impl Default for DenoSubcommand {
   fn default() -> DenoSubcommand {
     DenoSubcommand::Repl
   }
 }
 
+/// The modules that aren't type checked.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NoCheck {
+  /// Every module is type checked.
+  None,
+  /// Remote modules aren't type checked, with --no-check=remote: the type
+  /// errors found in them are left out.
+  Remote,
+  /// No module is type checked, with --no-check.
+  All,
+}
+
+impl Default for NoCheck {
+  fn default() -> NoCheck {
+    NoCheck::None
+  }
+}
+
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct Flags {
   /// Vector of CLI arguments - these are user script arguments, all Deno
@@ -113,7 +248,8 @@ pub struct Flags {
   pub lock_write: bool,
   pub log_level: Option<Level>,
   pub net_allowlist: Vec<String>,
-  pub no_check: bool,
+  pub no_check: NoCheck,
+  pub no_config: bool,
   pub no_prompts: bool,
   pub no_remote: bool,
   pub read_allowlist: Vec<PathBuf>,
@@ -191,6 +327,8 @@ static ENV_VARIABLES_HELP: &str = "ENVIRONMENT VARIABLES:
     DENO_DIR             Set the cache directory
     DENO_INSTALL_ROOT    Set deno install's output directory
                          (defaults to $HOME/.deno/bin)
+    DENO_INSTALL_CONFIG  Set the config file of deno install
+                         when --config is not given
     DENO_CERT            Load certificate authority from PEM encoded file
     NO_COLOR             Set to disable color
     HTTP_PROXY           Proxy address for HTTP requests
@@ -274,6 +412,8 @@ pub fn flags_from_vec_safe(args: Vec<String>) -> clap::Result<Flags> {
     completions_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("test") {
     test_parse(&mut flags, m);
+  } else if let Some(m) = matches.subcommand_matches("uninstall") {
+    uninstall_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("upgrade") {
     upgrade_parse(&mut flags, m);
   } else if let Some(m) = matches.subcommand_matches("doc") {
@@ -334,6 +474,7 @@ If the flag is set, restrict these messages to errors.",
     .subcommand(run_subcommand())
     .subcommand(test_subcommand())
     .subcommand(types_subcommand())
+    .subcommand(uninstall_subcommand())
     .subcommand(upgrade_subcommand())
     .long_about(DENO_HELP)
     .after_help(ENV_VARIABLES_HELP)
@@ -365,9 +506,15 @@ fn fmt_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
 fn install_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   permission_args_parse(flags, matches);
   config_arg_parse(flags, matches);
+  importmap_arg_parse(flags, matches);
+  lock_args_parse(flags, matches);
   ca_file_arg_parse(flags, matches);
   no_check_arg_parse(flags, matches);
   unstable_arg_parse(flags, matches);
+  inspect_arg_parse(flags, matches);
+  v8_flags_arg_parse(flags, matches);
+  seed_arg_parse(flags, matches);
+  flags.cached_only = matches.is_present("cached-only");
 
   let root = if matches.is_present("root") {
     let install_root = matches.value_of("root").unwrap();
@@ -376,24 +523,147 @@ fn install_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     None
   };
 
+  let local = matches.is_present("local");
+  let ephemeral = matches.is_present("ephemeral");
+  let bin_subdir = matches.value_of("bin-subdir").map(String::from);
+  let quiet_runtime = matches.is_present("quiet-runtime");
+  let quiet_path_hint = matches.is_present("quiet-path-hint");
+  let modify_path = matches.is_present("modify-path");
   let force = matches.is_present("force");
+  let if_not_installed = matches.is_present("if-not-installed");
+  let update = matches.is_present("update");
+  let keep_existing_config = matches.is_present("keep-existing-config");
+  let no_copy_config = matches.is_present("no-copy-config");
+  let copies_in_deno_dir = matches.is_present("copies-in-deno-dir");
+  let config_dest_name = matches.value_of("config-name").map(String::from);
+  let config_template = matches.is_present("config-template");
+  let max_name_length = matches
+    .value_of("max-name-length")
+    .map(|val| val.parse::<usize>().unwrap());
+  let name_from_subcommand = matches.is_present("name-from-subcommand");
+  let backup_dir = matches.value_of("backup-dir").map(PathBuf::from);
+  let trampoline = matches.value_of("trampoline").map(PathBuf::from);
+  let header = matches.value_of("header").map(String::from);
+  let completions = matches.values_of("completions").map(|mut values| {
+    let shell = values.next().unwrap().to_string();
+    let subcommand = values.next().unwrap().to_string();
+    (shell, subcommand)
+  });
+  let mode = matches
+    .value_of("mode")
+    .map(|val| u32::from_str_radix(val, 8).unwrap());
+  let dry_run = matches.is_present("dry-run");
+  let verify = matches.is_present("verify");
+  let pin = matches.is_present("pin");
+  let dev = matches.is_present("dev");
+  let deno_path = matches.value_of("deno-path").map(PathBuf::from);
+  let print_args = matches.is_present("print-args");
+  let use_module_hints = matches.is_present("use-module-hints");
   let name = matches.value_of("name").map(|s| s.to_string());
-  let cmd_values = matches.values_of("cmd").unwrap();
+  let info = matches.value_of("info").map(|s| s.to_string());
+  let json = matches.is_present("json");
+  let quiet = flags.log_level == Some(Level::Error);
+  let doctor = matches.is_present("doctor");
+  let prune = matches.is_present("prune");
+  let list = matches.is_present("list");
+  let set_default_shell =
+    matches.value_of("set-default-shell").map(String::from);
+  let check_name = matches.value_of("check-name").map(String::from);
+  let shell = matches.value_of("shell").map(String::from);
+  let reinstall = matches.value_of("reinstall").map(String::from);
+  let metadata_only = matches.is_present("metadata-only");
+  let targets = match matches.values_of("targets") {
+    Some(targets) => targets.map(String::from).collect(),
+    None => vec![],
+  };
+  let env = match matches.values_of("env") {
+    Some(env) => env
+      .map(|var| {
+        let (key, value) = var.split_at(var.find('=').unwrap());
+        (key.to_string(), value[1..].to_string())
+      })
+      .collect(),
+    None => vec![],
+  };
+  let run_dir = matches.value_of("cwd").map(PathBuf::from);
+  let deno_args = match matches.values_of("deno-arg") {
+    Some(deno_args) => deno_args.map(String::from).collect(),
+    None => vec![],
+  };
+  let aliases = match matches.values_of("alias") {
+    Some(aliases) => aliases.map(String::from).collect(),
+    None => vec![],
+  };
+  let many = matches.is_present("many");
+  let module_file = matches.value_of("module-file").map(PathBuf::from);
+  let names = match matches.values_of("names") {
+    Some(names) => names.map(String::from).collect(),
+    None => vec![],
+  };
   let mut cmd = vec![];
-  for value in cmd_values {
-    cmd.push(value.to_string());
+  if let Some(cmd_values) = matches.values_of("cmd") {
+    for value in cmd_values {
+      cmd.push(value.to_string());
+    }
   }
 
-  let module_url = cmd[0].to_string();
-  let args = cmd[1..].to_vec();
+  let module_url = cmd.first().cloned().unwrap_or_default();
+  let args = cmd.into_iter().skip(1).collect();
 
-  flags.subcommand = DenoSubcommand::Install {
+  flags.subcommand = DenoSubcommand::Install(InstallFlags {
     name,
     module_url,
     args,
     root,
     force,
-  };
+    if_not_installed,
+    update,
+    keep_existing_config,
+    no_copy_config,
+    copies_in_deno_dir,
+    config_dest_name,
+    config_template,
+    max_name_length,
+    name_from_subcommand,
+    use_module_hints,
+    mode,
+    verify,
+    pin,
+    module_checksum: None,
+    dev,
+    deno_path,
+    info,
+    json,
+    quiet,
+    doctor,
+    prune,
+    list,
+    set_default_shell,
+    shell,
+    check_name,
+    reinstall,
+    metadata_only,
+    targets,
+    aliases,
+    env,
+    run_dir,
+    deno_args,
+    names,
+    module_file,
+    many,
+    local,
+    ephemeral,
+    bin_subdir,
+    quiet_runtime,
+    quiet_path_hint,
+    modify_path,
+    backup_dir,
+    trampoline,
+    completions,
+    header,
+    dry_run,
+    print_args,
+  });
 }
 
 fn bundle_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
@@ -476,7 +746,7 @@ fn info_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   ca_file_arg_parse(flags, matches);
   unstable_arg_parse(flags, matches);
   let json = matches.is_present("json");
-  flags.no_check = true;
+  flags.no_check = NoCheck::All;
   flags.subcommand = DenoSubcommand::Info {
     file: matches.value_of("file").map(|f| f.to_string()),
     json,
@@ -528,22 +798,7 @@ fn run_test_args_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
     flags.cached_only = true;
   }
 
-  if matches.is_present("seed") {
-    let seed_string = matches.value_of("seed").unwrap();
-    let seed = seed_string.parse::<u64>().unwrap();
-    flags.seed = Some(seed);
-
-    let v8_seed_flag = format!("--random-seed={}", seed);
-
-    match flags.v8_flags {
-      Some(ref mut v8_flags) => {
-        v8_flags.push(v8_seed_flag);
-      }
-      None => {
-        flags.v8_flags = Some(svec![v8_seed_flag]);
-      }
-    }
-  }
+  seed_arg_parse(flags, matches);
 }
 
 fn run_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
@@ -591,6 +846,19 @@ fn test_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   };
 }
 
+fn uninstall_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
+  let root = matches.value_of("root").map(PathBuf::from);
+  let name = matches.value_of("name").unwrap().to_string();
+  let bin_subdir = matches.value_of("bin-subdir").map(String::from);
+  let json = matches.is_present("json");
+  flags.subcommand = DenoSubcommand::Uninstall {
+    name,
+    root,
+    bin_subdir,
+    json,
+  };
+}
+
 fn upgrade_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   ca_file_arg_parse(flags, matches);
 
@@ -712,11 +980,11 @@ fn repl_subcommand<'a, 'b>() -> App<'a, 'b> {
 }
 
 fn install_subcommand<'a, 'b>() -> App<'a, 'b> {
-  permission_args(SubCommand::with_name("install"))
+  permission_args(inspect_args(SubCommand::with_name("install")))
         .setting(AppSettings::TrailingVarArg)
         .arg(
           Arg::with_name("cmd")
-            .required(true)
+            .required_unless_one(&["info", "doctor", "prune", "list", "set-default-shell", "check-name", "reinstall", "module-file"])
             .multiple(true)
             .allow_hyphen_values(true))
         .arg(
@@ -726,22 +994,327 @@ fn install_subcommand<'a, 'b>() -> App<'a, 'b> {
           .help("Executable file name")
           .takes_value(true)
           .required(false))
+        .arg(
+          Arg::with_name("max-name-length")
+            .long("max-name-length")
+            .value_name("NUMBER")
+            .help("Truncate inferred executable names to NUMBER characters")
+            .takes_value(true)
+            .validator(|val: String| match val.parse::<usize>() {
+              Ok(length) if length > 0 => Ok(()),
+              _ => Err("Length should be a positive number".to_string()),
+            }))
+        .arg(
+          Arg::with_name("name-from-subcommand")
+            .long("name-from-subcommand")
+            .help("Append the subcommand passed to the script to the inferred name")
+            .conflicts_with("name")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("mode")
+            .long("mode")
+            .value_name("MODE")
+            .help("Set the permissions of the executable to the octal MODE (unix only)")
+            .takes_value(true)
+            .validator(|val: String| match u32::from_str_radix(&val, 8) {
+              Ok(mode) if mode <= 0o7777 => Ok(()),
+              _ => Err("Mode should be an octal number like 755".to_string()),
+            }))
+        .arg(
+          Arg::with_name("alias")
+            .long("alias")
+            .value_name("NAME")
+            .help("Additional executable name, can be repeated")
+            .takes_value(true)
+            .number_of_values(1)
+            .multiple(true))
+        .arg(
+          Arg::with_name("env")
+            .long("env")
+            .value_name("KEY=VALUE")
+            .help("Environment variable set by the executable, can be repeated")
+            .takes_value(true)
+            .number_of_values(1)
+            .multiple(true)
+            .validator(|val: String| match val.find('=') {
+              Some(index) if index > 0 => Ok(()),
+              _ => Err("must be given as KEY=VALUE".to_string()),
+            }))
+        .arg(
+          Arg::with_name("cwd")
+            .long("cwd")
+            .value_name("DIR")
+            .help("Directory the executable runs the script from")
+            .takes_value(true))
+        .arg(
+          Arg::with_name("deno-arg")
+            .long("deno-arg")
+            .value_name("FLAG")
+            .help("Flag of deno run passed before the module, can be repeated")
+            .takes_value(true)
+            .number_of_values(1)
+            .multiple(true)
+            .allow_hyphen_values(true))
+        .arg(
+          Arg::with_name("names")
+            .long("names")
+            .value_name("NAMES")
+            .help("Executable names, each passing its own name to the script")
+            .takes_value(true)
+            .use_delimiter(true)
+            .conflicts_with_all(&["name", "alias"]))
+        .arg(
+          Arg::with_name("module-file")
+            .long("module-file")
+            .value_name("FILE")
+            .help("Read the module URL from the first non-empty line of FILE")
+            .takes_value(true)
+            .conflicts_with_all(&["cmd", "many", "reinstall"]))
+        .arg(
+          Arg::with_name("many")
+            .long("many")
+            .help("Install each of the given modules, instead of passing the others to the first")
+            .conflicts_with_all(&["name", "alias", "names", "print-args"])
+            .takes_value(false))
         .arg(
           Arg::with_name("root")
             .long("root")
             .help("Installation root")
             .takes_value(true)
             .multiple(false))
+        .arg(
+          Arg::with_name("local")
+            .long("local")
+            .help("Install into .deno/bin in the current directory")
+            .conflicts_with("root")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("ephemeral")
+            .long("ephemeral")
+            .help("Install into a new temporary directory to try the script out")
+            .conflicts_with_all(&["root", "local"])
+            .takes_value(false))
+        .arg(
+          Arg::with_name("bin-subdir")
+            .long("bin-subdir")
+            .help("Directory of the root to install into, instead of bin")
+            .takes_value(true)
+            .value_name("DIR"))
+        .arg(
+          Arg::with_name("quiet-runtime")
+            .long("quiet-runtime")
+            .help("Run the installed script with --quiet")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("quiet-path-hint")
+            .long("quiet-path-hint")
+            .help("Don't print how to add the installation directory to PATH")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("modify-path")
+            .long("modify-path")
+            .help("Add the installation directory to PATH in the shell profile")
+            .takes_value(false))
         .arg(
           Arg::with_name("force")
             .long("force")
             .short("f")
             .help("Forcefully overwrite existing installation")
             .takes_value(false))
+        .arg(
+          Arg::with_name("if-not-installed")
+            .long("if-not-installed")
+            .help("Do nothing if the executable is already installed")
+            .conflicts_with("force")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("update")
+            .long("update")
+            .help("Overwrite an existing installation of the same module")
+            .conflicts_with_all(&["force", "if-not-installed"])
+            .takes_value(false))
+        .arg(
+          Arg::with_name("keep-existing-config")
+            .long("keep-existing-config")
+            .help("When overwriting, keep the existing copy of the config file")
+            .requires("force")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("no-copy-config")
+            .long("no-copy-config")
+            .help("Use the config file in place instead of copying it")
+            .requires("config")
+            .conflicts_with("keep-existing-config")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("copies-in-deno-dir")
+            .long("copies-in-deno-dir")
+            .help("Copy the config, import map and lock file under $DENO_DIR/installs")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("config-name")
+            .long("config-name")
+            .value_name("FILE_NAME")
+            .help("File name of the config copy")
+            .requires("config")
+            .conflicts_with("no-copy-config")
+            .takes_value(true))
+        .arg(
+          Arg::with_name("config-template")
+            .long("config-template")
+            .help("Substitute {{INSTALL_DIR}} and {{NAME}} in the config copy")
+            .requires("config")
+            .conflicts_with("no-copy-config")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("backup-dir")
+            .long("backup-dir")
+            .value_name("DIR")
+            .help("When overwriting, move the replaced files into DIR")
+            .requires("force")
+            .takes_value(true))
+        .arg(
+          Arg::with_name("trampoline")
+            .long("trampoline")
+            .value_name("STUB")
+            .help("On Windows, install a copy of STUB as an .exe instead of a .cmd")
+            .takes_value(true))
+        .arg(
+          Arg::with_name("completions")
+            .long("completions")
+            .value_names(&["SHELL", "SUBCOMMAND"])
+            .help("Write a completion file for SHELL, generated by the script's SUBCOMMAND")
+            .takes_value(true)
+            .number_of_values(2))
+        .arg(
+          Arg::with_name("header")
+            .long("header")
+            .value_name("TEXT")
+            .help("Add TEXT as a comment at the top of the executables")
+            .takes_value(true))
+        .arg(
+          Arg::with_name("dry-run")
+            .long("dry-run")
+            .help("Show what would be installed without writing any file")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("deno-path")
+            .long("deno-path")
+            .value_name("PATH")
+            .help("Run the script with the deno at PATH instead of the one found in PATH")
+            .takes_value(true))
+        .arg(
+          Arg::with_name("verify")
+            .long("verify")
+            .help("Check that a remote module can be fetched before installing it")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("pin")
+            .long("pin")
+            .help("Record the checksum of the remote module")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("dev")
+            .long("dev")
+            .help("Mark the installation of a local module as for development")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("print-args")
+            .long("print-args")
+            .help("Print the arguments the executable would pass to deno, as JSON")
+            .conflicts_with("dry-run")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("targets")
+            .long("targets")
+            .value_name("KINDS")
+            .help("Kinds of executable to generate, defaults to the platform's")
+            .takes_value(true)
+            .use_delimiter(true)
+            .possible_values(&["sh", "cmd", "ps1"]))
+        .arg(
+          Arg::with_name("use-module-hints")
+            .long("use-module-hints")
+            .help("Grant the permissions requested by the module's header comment")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("info")
+            .long("info")
+            .value_name("NAME")
+            .help("Show how an installed script was installed")
+            .takes_value(true)
+            .conflicts_with("cmd"))
+        .arg(
+          Arg::with_name("json")
+            .long("json")
+            .help("Outputs the installation, or the error if installing fails, in JSON format")
+            .takes_value(false))
+        .arg(
+          Arg::with_name("doctor")
+            .long("doctor")
+            .help("Diagnose PATH and installation issues")
+            .conflicts_with_all(&["cmd", "info"])
+            .takes_value(false))
+        .arg(
+          Arg::with_name("prune")
+            .long("prune")
+            .help("Remove the files left behind by scripts no longer installed")
+            .conflicts_with_all(&["cmd", "info", "doctor"])
+            .takes_value(false))
+        .arg(
+          Arg::with_name("list")
+            .long("list")
+            .help("List the installed scripts")
+            .conflicts_with_all(&["cmd", "info", "doctor"])
+            .takes_value(false))
+        .arg(
+          Arg::with_name("set-default-shell")
+            .long("set-default-shell")
+            .value_name("SHELL")
+            .help("Always give PATH hints for SHELL, whatever $SHELL is")
+            .takes_value(true)
+            .possible_values(&["sh", "bash", "zsh", "fish", "csh", "tcsh", "powershell", "cmd"])
+            .conflicts_with_all(&["cmd", "info", "doctor", "list"]))
+        .arg(
+          Arg::with_name("shell")
+            .long("shell")
+            .value_name("SHELL")
+            .help("Give the PATH hint for SHELL, whatever $SHELL is")
+            .takes_value(true)
+            .possible_values(&["sh", "bash", "zsh", "fish", "csh", "tcsh", "powershell", "cmd"])
+            .conflicts_with("set-default-shell"))
+        .arg(
+          Arg::with_name("check-name")
+            .long("check-name")
+            .value_name("NAME")
+            .help("Check that a script can be installed as NAME")
+            .takes_value(true)
+            .conflicts_with_all(&["cmd", "info", "doctor", "list", "set-default-shell"]))
+        .arg(
+          Arg::with_name("reinstall")
+            .long("reinstall")
+            .value_name("NAME")
+            .help("Install NAME again with the options it was installed with")
+            .takes_value(true)
+            .conflicts_with_all(&["cmd", "info", "doctor", "list", "set-default-shell", "check-name"]))
+        .arg(
+          Arg::with_name("metadata-only")
+            .long("metadata-only")
+            .help("With --reinstall, only write the executables again from the manifest")
+            .requires("reinstall"))
         .arg(no_check_arg())
         .arg(ca_file_arg())
+        .arg(seed_arg())
+        .arg(v8_flags_arg())
         .arg(unstable_arg())
         .arg(config_arg())
+        .arg(no_config_arg())
+        .arg(importmap_arg())
+        .arg(lock_arg())
+        .arg(
+          Arg::with_name("cached-only")
+            .long("cached-only")
+            .help("Require that remote dependencies are already cached when the script runs"))
         .about("Install script as an executable")
         .long_about(
 "Installs a script as an executable in the installation root's bin directory.
@@ -751,19 +1324,259 @@ fn install_subcommand<'a, 'b>() -> App<'a, 'b> {
 To change the executable name, use -n/--name:
   deno install --allow-net --allow-read -n serve https://deno.land/std/http/file_server.ts
 
+A scoped name installs the executable in a directory named after the scope:
+  deno install --allow-net --allow-read -n @std/serve https://deno.land/std/http/file_server.ts
+
 The executable name is inferred by default:
   - Attempt to take the file stem of the URL path. The above example would
     become 'file_server'.
   - If the file stem is something generic like 'main', 'mod', 'index' or 'cli',
     and the path has no parent, take the file name of the parent path. Otherwise
-    settle with the generic name.
+    settle with the generic name. A parent like 'src', 'dist', 'lib' or 'bin'
+    is skipped for the one above it.
+  - If the URL path is a directory, take its name. Directory names like
+    'tool@1.2.3' are stripped of their version, when taken from a parent too.
+
+A local directory is installed through the first of mod.ts, main.ts, index.ts
+and cli.ts it contains, and so named after the directory, unless a deno.json or
+deno.jsonc in it has a 'name':
+  deno install --allow-read ./mytool/
+
+Modules hosted on GitHub can be installed with a gh:user/repo[@ref]/path
+shorthand for their raw URL, the ref defaulting to 'main':
+  deno install --allow-net --allow-read gh:denoland/deno@v1.3.1/std/http/file_server.ts
+
+To keep inferred names short, use --max-name-length. An explicit -n/--name
+longer than that is rejected:
+  deno install --max-name-length 8 https://deno.land/std/http/file_server.ts
+
+To install several subcommands of one tool side by side, use
+--name-from-subcommand. The first argument after the module is appended to the
+inferred name, installing 'cliffy-serve' here:
+  deno install --name-from-subcommand https://deno.land/x/cliffy/cli.ts serve
+
+To also make the script available under other names, use --alias:
+  deno install --allow-net --allow-read -n serve --alias srv https://deno.land/std/http/file_server.ts
+
+To run the script with environment variables set, use --env for each of them:
+  deno install --env NO_COLOR=1 --env LOG_LEVEL=debug https://deno.land/std/examples/colors.ts
+
+For a script that has to be run from a particular directory, use --cwd. The
+executable changes to it, relative to the current directory, before running
+the script:
+  deno install --allow-read --cwd ./site https://deno.land/std/http/file_server.ts
+
+Arguments after the module are passed to the script. To pass some of the flags
+of deno run that have no deno install equivalent, like --reload or
+--cached-only, use --deno-arg with the flag:
+  deno install --deno-arg=--reload https://deno.land/std/examples/colors.ts
+
+A batch file can't be started by programs that don't go through a shell. On
+Windows, --trampoline installs a copy of a precompiled stub as <name>.exe
+instead, which runs deno with the arguments in <name>.exe.json next to it:
+  deno install --trampoline deno_stub.exe https://deno.land/std/examples/colors.ts
+
+For a script that prints its own shell completions, --completions writes a
+completion file for bash, zsh, fish or powershell to the completions directory
+next to the bin directory. The file runs the script's given subcommand with the
+shell name, here 'tool completions bash', and sources what it prints:
+  deno install -n tool --completions bash completions https://example.com/tool.ts
+
+To add a comment to the executables, e.g. pointing to the policy they were
+installed under, use --header with a single line of text:
+  deno install --header 'See https://example.com/policy' https://deno.land/std/examples/colors.ts
+
+For a script that behaves differently depending on the name it is run as, use
+--names. Each executable passes its own name as the first argument:
+  deno install --names add,remove,list https://example.com/todo.ts
+
+To install several modules at once with the same options, use --many. Each
+is installed under its inferred name, and a failure doesn't stop the others:
+  deno install --many --allow-net https://deno.land/std/http/file_server.ts https://deno.land/std/examples/chat/server.ts
 
 To change the installation root, use --root:
   deno install --allow-net --allow-read --root /usr/local https://deno.land/std/http/file_server.ts
 
+The executables are written to the bin directory of the root. To use another
+directory of the root, or the root itself with '.', use --bin-subdir:
+  deno install --root ~/.local --bin-subdir shims https://deno.land/std/examples/colors.ts
+
+Give the same --bin-subdir to --list, --info, --reinstall, --prune and the
+other options that look up installed scripts.
+
+Once installed, the permissions granted to the script are listed. The
+-q/--quiet flag of deno install leaves them out along with everything else
+deno install prints but errors, and is also passed to the installed script.
+To only suppress the diagnostic output of the installed script, use
+--quiet-runtime:
+  deno install --quiet-runtime https://deno.land/std/examples/colors.ts
+
+To leave out the hint to add the installation directory to PATH, e.g. when
+provisioning a machine whose PATH is set up separately, use --quiet-path-hint:
+  deno install --quiet-path-hint https://deno.land/std/examples/colors.ts
+
+To have the installation directory added to PATH instead, use --modify-path.
+The command is appended to the profile of the shell, e.g. ~/.bashrc or
+~/.zshrc, unless it is there already. On Windows the setx command to run is
+printed:
+  deno install --modify-path https://deno.land/std/examples/colors.ts
+
+To install the script for the project in the current directory only, into
+./.deno/bin, use --local:
+  deno install --local --allow-net --allow-read https://deno.land/std/http/file_server.ts
+
+To try a script out without installing it for good, use --ephemeral. It is
+installed into a new temporary directory, and the command to run it printed:
+  deno install --ephemeral --allow-net --allow-read https://deno.land/std/http/file_server.ts
+
+On Windows, a .ps1 PowerShell script is generated next to the .cmd batch file.
+To generate executables for other platforms too, e.g. for a bin directory
+shared between them, use --targets with any of sh, cmd and ps1:
+  deno install --targets sh,cmd,ps1 https://deno.land/std/examples/colors.ts
+
+To only install the script when it isn't installed yet, e.g. in a setup script
+that runs repeatedly, use --if-not-installed:
+  deno install --if-not-installed https://deno.land/std/examples/colors.ts
+
+To install it again with other options, without --force overwriting a script
+of another module that has the same name, use --update:
+  deno install --update --allow-env https://deno.land/std/examples/colors.ts
+
+With --force, an installation that would be written exactly as it is
+installed is left as is, keeping the modification times of its files.
+
+The config file given with --config is copied next to the executable, like the
+import map and lock file. To keep these copies with the rest of the Deno state
+instead, in a directory of $DENO_DIR/installs for the installation root, use
+--copies-in-deno-dir. To share one config between several scripts, use
+--no-copy-config:
+  deno install --config tsconfig.json --no-copy-config https://deno.land/std/examples/colors.ts
+
+Without --config, the config file of the DENO_INSTALL_CONFIG environment
+variable is copied, unless --no-config is given.
+
+The copy is named <name>.tsconfig.json. To give it another file name in the
+same directory, use --config-name:
+  deno install --config tsconfig.json --config-name colors.json https://deno.land/std/examples/colors.ts
+
+To fill in where the script is installed in the config copy, use
+--config-template. {{INSTALL_DIR}} is replaced with the directory of the
+executable and {{NAME}} with the name of the script:
+  deno install --config tsconfig.json --config-template https://deno.land/std/examples/colors.ts
+
+When reinstalling with -f/--force, the replaced files are kept with a .bak
+extension. To move them into a timestamped directory instead, use --backup-dir:
+  deno install -f --backup-dir ~/.deno/backup https://deno.land/std/examples/colors.ts
+
+To reinstall over an existing installation without replacing a config copy
+that was edited by hand, add --keep-existing-config to -f/--force:
+  deno install -f --keep-existing-config --config tsconfig.json https://deno.land/std/http/file_server.ts
+
+To grant the permissions a module requests in a leading
+'// deno-install: --allow-net --allow-read' comment, use --use-module-hints.
+Permissions passed on the command line take precedence:
+  deno install --use-module-hints https://deno.land/std/http/file_server.ts
+
+To show how a script was installed, use --info (add --json for JSON output):
+  deno install --info file_server
+
+With --json, the installation is reported as a JSON object with the name,
+path and module URL of the executable, whether PATH needs updating and whether
+an existing executable was overwritten, or the error code and message if it
+failed:
+  deno install --json https://deno.land/std/examples/colors.ts
+
+To preview an installation without writing any file, use --dry-run (add
+--json for JSON output, including the content of the executable):
+  deno install --dry-run --json https://deno.land/std/examples/colors.ts
+
+With -f/--force, the preview also lists the files of an existing installation
+that would be overwritten, and the module it ran if that changes.
+
+To only print the arguments the executable would pass to deno, as a JSON
+array, use --print-args:
+  deno install --print-args --allow-net https://deno.land/std/http/file_server.ts
+
+To list the installed scripts, use --list (add --json for JSON output):
+  deno install --list
+
+When the installation root isn't in PATH, a hint to add it is given for the
+shell in $SHELL. To give it for another shell, use --shell, or to always do
+so, --set-default-shell:
+  deno install --shell fish https://deno.land/std/examples/colors.ts
+  deno install --set-default-shell fish
+
+To look for problems with the installation directory and installed scripts,
+use --doctor:
+  deno install --doctor
+
+To remove the config copies and metadata left behind by scripts that are no
+longer installed, use --prune:
+  deno install --prune
+
+To check that a name is valid and free before installing under it, use
+--check-name. It exits with an error if the name can't be used:
+  deno install --check-name serve
+
+To fetch the latest version of an installed script and install it again with
+the same options, use --reinstall:
+  deno install --reinstall serve
+
+To only write the executables again from the manifest, without fetching the
+module, e.g. after upgrading deno, add --metadata-only:
+  deno install --reinstall serve --metadata-only
+
+To read the module from stdin, pass - as the module. A name must be given:
+  echo https://deno.land/std/examples/colors.ts | deno install -n colors -
+
+To keep a long or secret module URL out of the shell history, read it from
+the first non-empty line of a file with --module-file:
+  deno install --allow-net --module-file ./url.txt
+
+The executable runs the deno found in PATH. To run a deno at a fixed location
+instead, use --deno-path:
+  deno install --deno-path /opt/deno/bin/deno https://deno.land/std/examples/colors.ts
+
+To make sure a remote module can be fetched before installing it, rather than
+when the script is first run, use --verify:
+  deno install --verify https://deno.land/std/examples/colors.ts
+
+To record the SHA-256 checksum of the remote module as it is when installed,
+shown by --info, use --pin. It is recorded again when reinstalling:
+  deno install --pin https://deno.land/std/examples/colors.ts
+
+To mark the installation of a local module you are working on, use --dev. The
+executable runs the file where it is, and --list shows it with (dev):
+  deno install --dev ./mytool/
+
+To seed Math.random() or tune V8 in the installed script, use --seed and
+--v8-flags:
+  deno install --seed 1 --v8-flags=--max-old-space-size=4096 https://deno.land/std/examples/colors.ts
+
+To debug an installed script, use --inspect or --inspect-brk, the script is
+then always run with the inspector:
+  deno install --inspect=127.0.0.1:9229 https://deno.land/std/examples/colors.ts
+
+On unix, executables get mode 755 by default, and the config copy mode 644.
+To restrict who can run them, use --mode with an octal mode that lets the
+owner execute them. A mode that keeps others from reading the executables
+makes the config copy 600:
+  deno install --mode 700 https://deno.land/std/examples/colors.ts
+
+Projects can share installation defaults in a .deno-install.json file, read
+from the current directory. Command line flags take precedence:
+  {
+    \"permissions\": [\"--allow-net\", \"--allow-read\"],
+    \"root\": \"./tools\",
+    \"max_name_length\": 16
+  }
+
 The installation root is determined, in order of precedence:
   - --root option
+  - root of .deno-install.json
   - DENO_INSTALL_ROOT environment variable
+  - $XDG_BIN_HOME (used as the bin directory itself, except on Windows)
+  - $XDG_DATA_HOME/deno (except on Windows and macOS)
   - $HOME/.deno
 
 These must be added to the path manually if required.")
@@ -913,6 +1726,57 @@ Future runs of this module will trigger no downloads or compilation unless
     )
 }
 
+fn uninstall_subcommand<'a, 'b>() -> App<'a, 'b> {
+  SubCommand::with_name("uninstall")
+    .arg(
+      Arg::with_name("name")
+        .required(true)
+        .help("Name of the executable to remove"),
+    )
+    .arg(
+      Arg::with_name("root")
+        .long("root")
+        .help("Installation root")
+        .takes_value(true)
+        .multiple(false),
+    )
+    .arg(
+      Arg::with_name("bin-subdir")
+        .long("bin-subdir")
+        .help("Directory of the root the script was installed into")
+        .takes_value(true)
+        .value_name("DIR"),
+    )
+    .arg(
+      Arg::with_name("json")
+        .long("json")
+        .help("Outputs the removed files in JSON format")
+        .takes_value(false),
+    )
+    .about("Uninstall a script previously installed with deno install")
+    .long_about(
+      "Uninstalls an executable script in the installation root's bin directory.
+  deno uninstall serve
+
+To change the installation root, use --root:
+  deno uninstall --root /usr/local serve
+
+A script installed with --bin-subdir is uninstalled with the same option:
+  deno uninstall --root ~/.local --bin-subdir shims serve
+
+Each removed file is listed along with the disk space freed; use --json to
+get the same report in JSON format:
+  deno uninstall --json serve
+
+The installation root is determined, in order of precedence:
+  - --root option
+  - DENO_INSTALL_ROOT environment variable
+  - $XDG_BIN_HOME (used as the bin directory itself, except on Windows)
+  - $XDG_DATA_HOME/deno (except on Windows and macOS)
+  - $HOME/.deno",
+    )
+}
+
 fn upgrade_subcommand<'a, 'b>() -> App<'a, 'b> {
   SubCommand::with_name("upgrade")
     .about("Upgrade deno executable to given version")
@@ -1139,17 +2003,7 @@ fn run_test_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         .long("cached-only")
         .help("Require that remote dependencies are already cached"),
     )
-    .arg(
-      Arg::with_name("seed")
-        .long("seed")
-        .value_name("NUMBER")
-        .help("Seed Math.random()")
-        .takes_value(true)
-        .validator(|val: String| match val.parse::<u64>() {
-          Ok(_) => Ok(()),
-          Err(_) => Err("Seed should be a number".to_string()),
-        }),
-    )
+    .arg(seed_arg())
 }
 
 fn run_subcommand<'a, 'b>() -> App<'a, 'b> {
@@ -1251,8 +2105,16 @@ fn config_arg<'a, 'b>() -> Arg<'a, 'b> {
     .takes_value(true)
 }
 
+fn no_config_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("no-config")
+    .long("no-config")
+    .conflicts_with("config")
+    .help("Disable automatic loading of the configuration file")
+}
+
 fn config_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
   flags.config_path = matches.value_of("config").map(ToOwned::to_owned);
+  flags.no_config = matches.is_present("no-config");
 }
 
 fn ca_file_arg<'a, 'b>() -> Arg<'a, 'b> {
@@ -1406,15 +2268,57 @@ fn v8_flags_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
   }
 }
 
+fn seed_arg<'a, 'b>() -> Arg<'a, 'b> {
+  Arg::with_name("seed")
+    .long("seed")
+    .value_name("NUMBER")
+    .help("Seed Math.random()")
+    .takes_value(true)
+    .validator(|val: String| match val.parse::<u64>() {
+      Ok(_) => Ok(()),
+      Err(_) => Err("Seed should be a number".to_string()),
+    })
+}
+
+fn seed_arg_parse(flags: &mut Flags, matches: &ArgMatches) {
+  if matches.is_present("seed") {
+    let seed_string = matches.value_of("seed").unwrap();
+    let seed = seed_string.parse::<u64>().unwrap();
+    flags.seed = Some(seed);
+
+    let v8_seed_flag = format!("--random-seed={}", seed);
+
+    match flags.v8_flags {
+      Some(ref mut v8_flags) => {
+        v8_flags.push(v8_seed_flag);
+      }
+      None => {
+        flags.v8_flags = Some(svec![v8_seed_flag]);
+      }
+    }
+  }
+}
+
 fn no_check_arg<'a, 'b>() -> Arg<'a, 'b> {
   Arg::with_name("no-check")
     .long("no-check")
-    .help("Skip type checking modules")
+    .takes_value(true)
+    .min_values(0)
+    .max_values(1)
+    .require_equals(true)
+    .possible_values(&["remote"])
+    .value_name("MODULES")
+    .help(
+      "Skip type checking modules, or only remote ones with --no-check=remote",
+    )
 }
 
 fn no_check_arg_parse(flags: &mut Flags, matches: &clap::ArgMatches) {
   if matches.is_present("no-check") {
-    flags.no_check = true;
+    flags.no_check = match matches.value_of("no-check") {
+      Some("remote") => NoCheck::Remote,
+      _ => NoCheck::All,
+    };
   }
 }
 
@@ -1533,6 +2437,46 @@ mod tests {
     );
   }
 
+  #[test]
+  fn uninstall() {
+    let r = flags_from_vec_safe(svec!["deno", "uninstall", "file_server"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Uninstall {
+          name: "file_server".to_string(),
+          root: None,
+          bin_subdir: None,
+          json: false,
+        },
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "uninstall",
+      "--root",
+      "/user/foo/bar",
+      "--bin-subdir",
+      "shims",
+      "--json",
+      "file_server"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Uninstall {
+          name: "file_server".to_string(),
+          root: Some(PathBuf::from("/user/foo/bar")),
+          bin_subdir: Some("shims".to_string()),
+          json: true,
+        },
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn version() {
     let r = flags_from_vec_safe(svec!["deno", "--version"]);
@@ -1907,7 +2851,7 @@ mod tests {
           json: false,
           file: Some("script.ts".to_string()),
         },
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -1921,7 +2865,7 @@ mod tests {
           file: Some("script.ts".to_string()),
         },
         reload: true,
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -1934,7 +2878,7 @@ mod tests {
           json: true,
           file: Some("script.ts".to_string()),
         },
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -1947,7 +2891,7 @@ mod tests {
           json: false,
           file: None
         },
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -1960,7 +2904,7 @@ mod tests {
           json: true,
           file: None
         },
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -2442,13 +3386,10 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install {
-          name: None,
+        subcommand: DenoSubcommand::Install(InstallFlags {
           module_url: "https://deno.land/std/examples/colors.ts".to_string(),
-          args: vec![],
-          root: None,
-          force: false,
-        },
+          ..InstallFlags::default()
+        }),
         ..Flags::default()
       }
     );
@@ -2466,13 +3407,10 @@ mod tests {
       r.unwrap(),
       Flags {
         unstable: true,
-        subcommand: DenoSubcommand::Install {
-          name: None,
+        subcommand: DenoSubcommand::Install(InstallFlags {
           module_url: "https://deno.land/std/examples/colors.ts".to_string(),
-          args: svec![],
-          root: None,
-          force: false,
-        },
+          ..InstallFlags::default()
+        }),
         ..Flags::default()
       }
     );
@@ -2492,13 +3430,11 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install {
+        subcommand: DenoSubcommand::Install(InstallFlags {
           name: Some("file_server".to_string()),
           module_url: "https://deno.land/std/http/file_server.ts".to_string(),
-          args: vec![],
-          root: None,
-          force: false,
-        },
+          ..InstallFlags::default()
+        }),
         allow_net: true,
         allow_read: true,
         ..Flags::default()
@@ -2519,13 +3455,10 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install {
-          name: None,
+        subcommand: DenoSubcommand::Install(InstallFlags {
           module_url: "https://deno.land/std/examples/colors.ts".to_string(),
-          args: svec![],
-          root: None,
-          force: false,
-        },
+          ..InstallFlags::default()
+        }),
         config_path: Some("tsconfig.json".to_owned()),
         ..Flags::default()
       }
@@ -2533,16 +3466,103 @@ mod tests {
   }
 
   #[test]
-  fn install_with_args_and_dir_and_force() {
+  fn install_with_no_config() {
     let r = flags_from_vec_safe(svec![
       "deno",
       "install",
-      "--root",
-      "/usr/local",
-      "-f",
-      "--allow-net",
-      "--allow-read",
-      "-n",
+      "--no-config",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          ..InstallFlags::default()
+        }),
+        no_config: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--no-config",
+      "--config",
+      "tsconfig.json",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+
+    // Only deno install picks a config file up by itself.
+    let r =
+      flags_from_vec_safe(svec!["deno", "run", "--no-config", "script.ts"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_with_importmap() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--unstable",
+      "--importmap",
+      "import_map.json",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          ..InstallFlags::default()
+        }),
+        import_map_path: Some("import_map.json".to_owned()),
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_with_lock_and_cached_only() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--lock",
+      "lock.json",
+      "--cached-only",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          ..InstallFlags::default()
+        }),
+        lock: Some("lock.json".to_owned()),
+        cached_only: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_with_args_and_dir_and_force() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--root",
+      "/usr/local",
+      "-f",
+      "--allow-net",
+      "--allow-read",
+      "-n",
       "file_server",
       "https://deno.land/std/http/file_server.ts",
       "arg1",
@@ -2551,15 +3571,1247 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install {
-          name: Some("file_server".to_string()),
-          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
-          args: svec!["arg1", "arg2"],
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          name: Some("file_server".to_string()),
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          args: svec!["arg1", "arg2"],
+          root: Some(PathBuf::from("/usr/local")),
+          force: true,
+          ..InstallFlags::default()
+        }),
+        allow_net: true,
+        allow_read: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_with_module_hints() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--use-module-hints",
+      "https://deno.land/std/http/file_server.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          use_module_hints: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_update() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--update",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          update: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--update",
+      "-f",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_if_not_installed() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--if-not-installed",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          if_not_installed: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--if-not-installed",
+      "-f",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_keep_existing_config() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "-f",
+      "--keep-existing-config",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          force: true,
+          keep_existing_config: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--keep-existing-config",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_targets() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--targets",
+      "sh,cmd,ps1",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          targets: svec!["sh", "cmd", "ps1"],
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--targets",
+      "sh,fish",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_name_from_subcommand() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--name-from-subcommand",
+      "https://deno.land/x/cliffy/cli.ts",
+      "serve"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/x/cliffy/cli.ts".to_string(),
+          args: svec!["serve"],
+          name_from_subcommand: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--name-from-subcommand",
+      "-n",
+      "serve",
+      "https://deno.land/x/cliffy/cli.ts",
+      "serve"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_max_name_length() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--max-name-length",
+      "8",
+      "https://deno.land/std/http/file_server.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          max_name_length: Some(8),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    for length in &["0", "-1", "eight"] {
+      let r = flags_from_vec_safe(svec![
+        "deno",
+        "install",
+        "--max-name-length",
+        length,
+        "https://deno.land/std/http/file_server.ts"
+      ]);
+      assert!(r.is_err());
+    }
+  }
+
+  #[test]
+  fn install_aliases() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--alias",
+      "c",
+      "--alias",
+      "col",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          aliases: svec!["c", "col"],
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_env() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--env",
+      "NO_COLOR=1",
+      "--env",
+      "GREETING=a=b c",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          env: vec![
+            ("NO_COLOR".to_string(), "1".to_string()),
+            ("GREETING".to_string(), "a=b c".to_string()),
+          ],
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--env",
+      "=1",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_many() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--many",
+      "--allow-net",
+      "https://deno.land/std/http/file_server.ts",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          args: svec!["https://deno.land/std/examples/colors.ts"],
+          many: true,
+          ..InstallFlags::default()
+        }),
+        allow_net: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_quiet() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "-q",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          quiet: true,
+          ..InstallFlags::default()
+        }),
+        log_level: Some(Level::Error),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_quiet_runtime() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--quiet-runtime",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          quiet_runtime: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_quiet_path_hint() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--quiet-path-hint",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          quiet_path_hint: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_modify_path() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--modify-path",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          modify_path: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_cwd() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--cwd",
+      "/srv/app",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          run_dir: Some(PathBuf::from("/srv/app")),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_dev() {
+    let r = flags_from_vec_safe(svec!["deno", "install", "--dev", "./mytool/"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "./mytool/".to_string(),
+          dev: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_trampoline() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--trampoline",
+      "deno_stub.exe",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          trampoline: Some(PathBuf::from("deno_stub.exe")),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_completions() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--completions",
+      "bash",
+      "completions",
+      "https://example.com/tool.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://example.com/tool.ts".to_string(),
+          completions: Some(("bash".to_string(), "completions".to_string())),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_header() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--header",
+      "See https://example.com/policy",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          header: Some("See https://example.com/policy".to_string()),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_deno_arg() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--deno-arg",
+      "--reload",
+      "--deno-arg=--seed=1",
+      "https://deno.land/std/examples/colors.ts",
+      "--foobar"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          args: svec!["--foobar"],
+          deno_args: svec!["--reload", "--seed=1"],
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_bin_subdir() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--bin-subdir",
+      "shims",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          bin_subdir: Some("shims".to_string()),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_local() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--local",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          local: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--local",
+      "--root",
+      "/usr/local",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_ephemeral() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--ephemeral",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          ephemeral: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    for conflicting in &[svec!["--root", "/usr/local"], svec!["--local"]] {
+      let mut args = svec!["deno", "install", "--ephemeral"];
+      args.extend(conflicting.iter().cloned());
+      args.push("https://deno.land/std/examples/colors.ts".to_string());
+      assert!(flags_from_vec_safe(args).is_err());
+    }
+  }
+
+  #[test]
+  fn install_names() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--names",
+      "add,remove,list",
+      "https://example.com/todo.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://example.com/todo.ts".to_string(),
+          names: svec!["add", "remove", "list"],
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--names",
+      "add,remove",
+      "-n",
+      "todo",
+      "https://example.com/todo.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_list() {
+    let r = flags_from_vec_safe(svec!["deno", "install", "--list", "--json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          list: true,
+          json: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_set_default_shell() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--set-default-shell",
+      "fish"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          set_default_shell: Some("fish".to_string()),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--set-default-shell",
+      "nu"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_shell() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--shell",
+      "fish",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          shell: Some("fish".to_string()),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--shell",
+      "nu",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_check_name() {
+    let r =
+      flags_from_vec_safe(svec!["deno", "install", "--check-name", "serve"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          check_name: Some("serve".to_string()),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_module_stdin() {
+    let r = flags_from_vec_safe(svec!["deno", "install", "-n", "colors", "-"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "-".to_string(),
+          name: Some("colors".to_string()),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_module_file() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--allow-net",
+      "--module-file",
+      "./url.txt",
+      "-n",
+      "serve"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_file: Some(PathBuf::from("./url.txt")),
+          name: Some("serve".to_string()),
+          ..InstallFlags::default()
+        }),
+        allow_net: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--module-file",
+      "./url.txt",
+      "https://deno.land/std/http/file_server.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_inspect() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--inspect",
+      "--inspect-brk=127.0.0.1:9230",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          ..InstallFlags::default()
+        }),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        inspect_brk: Some("127.0.0.1:9230".parse().unwrap()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_seed_v8_flags() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--seed",
+      "250",
+      "--v8-flags=--expose-gc",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          ..InstallFlags::default()
+        }),
+        seed: Some(250),
+        v8_flags: Some(svec!["--expose-gc", "--random-seed=250"]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_deno_path() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--deno-path",
+      "/opt/deno/bin/deno",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          deno_path: Some(PathBuf::from("/opt/deno/bin/deno")),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_pin() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--pin",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          pin: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_verify() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--verify",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          verify: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_mode() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--mode",
+      "700",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          mode: Some(0o700),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--mode",
+      "789",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_reinstall() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--reinstall",
+      "serve",
+      "--root",
+      "/usr/local"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          reinstall: Some("serve".to_string()),
+          root: Some(PathBuf::from("/usr/local")),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--reinstall",
+      "serve",
+      "https://deno.land/std/http/file_server.ts"
+    ]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--reinstall",
+      "serve",
+      "--metadata-only"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          reinstall: Some("serve".to_string()),
+          metadata_only: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--metadata-only",
+      "https://deno.land/std/http/file_server.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_doctor() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--doctor",
+      "--root",
+      "/usr/local"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
           root: Some(PathBuf::from("/usr/local")),
+          doctor: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--doctor",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_prune() {
+    let r = flags_from_vec_safe(svec!["deno", "install", "--prune"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          prune: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--prune",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_config_template() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--config",
+      "tsconfig.json",
+      "--config-template",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          config_template: true,
+          ..InstallFlags::default()
+        }),
+        config_path: Some("tsconfig.json".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--config-template",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_copies_in_deno_dir() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--copies-in-deno-dir",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          copies_in_deno_dir: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_no_copy_config() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--config",
+      "tsconfig.json",
+      "--no-copy-config",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          no_copy_config: true,
+          ..InstallFlags::default()
+        }),
+        config_path: Some("tsconfig.json".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--no-copy-config",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_config_name() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--config",
+      "tsconfig.json",
+      "--config-name",
+      "colors.json",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          config_dest_name: Some("colors.json".to_string()),
+          ..InstallFlags::default()
+        }),
+        config_path: Some("tsconfig.json".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--config-name",
+      "colors.json",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn install_backup_dir() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "-f",
+      "--backup-dir",
+      "/tmp/backup",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
           force: true,
-        },
-        allow_net: true,
-        allow_read: true,
+          backup_dir: Some(PathBuf::from("/tmp/backup")),
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_dry_run() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--dry-run",
+      "--json",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          dry_run: true,
+          json: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_print_args() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--print-args",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          print_args: true,
+          ..InstallFlags::default()
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn install_info() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "install",
+      "--info",
+      "file_server",
+      "--json",
+      "--root",
+      "/usr/local"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          root: Some(PathBuf::from("/usr/local")),
+          info: Some("file_server".to_string()),
+          json: true,
+          ..InstallFlags::default()
+        }),
         ..Flags::default()
       }
     );
@@ -2669,6 +4921,29 @@ mod tests {
   }
   */
 
+  #[test]
+  fn no_check_remote() {
+    let r = flags_from_vec_safe(svec![
+      "deno",
+      "run",
+      "--no-check=remote",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run {
+          script: "script.ts".to_string(),
+        },
+        no_check: NoCheck::Remote,
+        ..Flags::default()
+      }
+    );
+    let r =
+      flags_from_vec_safe(svec!["deno", "run", "--no-check=all", "script.ts"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn no_check() {
     let r =
@@ -2679,7 +4954,7 @@ mod tests {
         subcommand: DenoSubcommand::Run {
           script: "script.ts".to_string(),
         },
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -2695,7 +4970,7 @@ mod tests {
           quiet: false,
           include: Some(svec!["script.ts"]),
         },
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -2707,7 +4982,7 @@ mod tests {
         subcommand: DenoSubcommand::Cache {
           files: svec!["script.ts"],
         },
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -2723,7 +4998,7 @@ mod tests {
           json: false,
           file: Some("script.ts".to_string()),
         },
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -3066,7 +5341,7 @@ mod tests {
           file: Some("https://example.com".to_string()),
         },
         ca_file: Some("example.crt".to_owned()),
-        no_check: true,
+        no_check: NoCheck::All,
         ..Flags::default()
       }
     );
@@ -3086,13 +5361,11 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install {
+        subcommand: DenoSubcommand::Install(InstallFlags {
           name: Some("deno_colors".to_string()),
           module_url: "https://deno.land/std/examples/colors.ts".to_string(),
-          args: vec![],
-          root: None,
-          force: false,
-        },
+          ..InstallFlags::default()
+        }),
         ca_file: Some("example.crt".to_owned()),
         ..Flags::default()
       }
@@ -158,7 +158,16 @@ impl GlobalState {
     let allow_js = should_allow_js(&module_graph_files);
 
     if should_compile {
-      if self.flags.no_check {
+      let no_check = match self.flags.no_check {
+        flags::NoCheck::All => true,
+        // Without local modules there is nothing left to check, otherwise
+        // the compiler leaves out the errors of the remote ones.
+        flags::NoCheck::Remote => module_graph_files
+          .iter()
+          .all(|module_file| !module_file.url.starts_with("file:")),
+        flags::NoCheck::None => false,
+      };
+      if no_check {
         self.ts_compiler.transpile(module_graph).await?;
       } else {
         self
@@ -1,698 +1,11959 @@
 // Copyright 2018-2020 the Deno authors. All rights reserved. MIT license.
+use crate::flags::flags_from_vec_safe;
+use crate::flags::DenoSubcommand;
 use crate::flags::Flags;
+use crate::flags::InstallFlags;
+use crate::flags::NoCheck;
+use crate::http_util::create_http_client;
+use crate::http_util::fetch_once;
+use crate::http_util::FetchOnceResult;
+use crate::tokio_util;
+use deno_core::ErrBox;
 use log::Level;
+use percent_encoding::percent_decode_str;
 use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashSet;
 use std::env;
+use std::fmt;
 use std::fs;
 use std::fs::File;
+use std::io::BufRead;
 use std::io::Error;
 use std::io::ErrorKind;
+use std::io::Read;
 use std::io::Write;
 #[cfg(not(windows))]
 use std::os::unix::fs::PermissionsExt;
+use std::path::Component;
+use std::path::Path;
 use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use url::Url;
 
 lazy_static! {
     static ref EXEC_NAME_RE: Regex = RegexBuilder::new(
         r"^[a-z][\w-]*$"
     ).case_insensitive(true).build().unwrap();
+    // Names like @scope/tool, installed in a directory named after the scope.
+    static ref SCOPED_NAME_RE: Regex = RegexBuilder::new(
+        r"^@[a-z][\w-]*/[a-z][\w-]*$"
+    ).case_insensitive(true).build().unwrap();
     // Regular expression to test disk driver letter. eg "C:\\User\username\path\to"
     static ref DRIVE_LETTER_REG: Regex = RegexBuilder::new(
         r"^[c-z]:"
     ).case_insensitive(true).build().unwrap();
+    // Names that would shadow deno itself, or read as one of its subcommands.
+    static ref RESERVED_NAMES: HashSet<&'static str> = [
+        "deno", "bundle", "cache", "completions", "doc", "eval", "fmt", "help",
+        "info", "install", "lint", "repl", "run", "test", "types", "uninstall",
+        "upgrade",
+    ].iter().cloned().collect();
+    /// Device names Windows reserves in every directory, whatever the
+    /// extension.
+    static ref WINDOWS_DEVICE_NAMES: HashSet<&'static str> = [
+        "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5",
+        "com6", "com7", "com8", "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5",
+        "lpt6", "lpt7", "lpt8", "lpt9",
+    ].iter().cloned().collect();
 }
 
+/// The files looked for, in order, to install a local directory.
+const ENTRY_FILE_NAMES: &[&str] = &["mod.ts", "main.ts", "index.ts", "cli.ts"];
+
+/// Schemes of the module URLs that are fetched, as opposed to local paths.
+const REMOTE_SCHEMES: &[&str] = &["http", "https"];
+
+/// How many times a file write denied on Windows is attempted, and the delay
+/// before the first retry, doubled for each one after.
+const TRANSIENT_RETRY_ATTEMPTS: u32 = 4;
+const TRANSIENT_RETRY_DELAY_MS: u64 = 50;
+
 pub fn is_remote_url(module_url: &str) -> bool {
+  has_scheme(module_url, REMOTE_SCHEMES)
+}
+
+fn parse_remote_url(module_url: &str) -> Result<Url, Error> {
+  Url::parse(module_url).map_err(|err| {
+    Error::new(
+      ErrorKind::InvalidInput,
+      format!("Invalid module URL {}: {}", module_url, err),
+    )
+  })
+}
+
+fn has_scheme(module_url: &str, schemes: &[&str]) -> bool {
   let lower = module_url.to_lowercase();
-  lower.starts_with("http://") || lower.starts_with("https://")
+  schemes.iter().any(|scheme| {
+    lower.starts_with(scheme) && lower[scheme.len()..].starts_with(':')
+  })
 }
 
-fn validate_name(exec_name: &str) -> Result<(), Error> {
-  if EXEC_NAME_RE.is_match(exec_name) {
-    Ok(())
-  } else {
-    Err(Error::new(
-      ErrorKind::Other,
-      format!("Invalid executable name: {}", exec_name),
-    ))
+/// Heuristic for passthrough args that were likely meant as a second module
+/// to install, e.g. `deno install https://a/a.ts https://b/b.ts`.
+fn looks_like_module(arg: &str) -> bool {
+  if is_remote_url(arg) {
+    return true;
   }
+  let path = Path::new(arg);
+  let is_module_ext = match path.extension().and_then(|ext| ext.to_str()) {
+    Some(ext) => ["js", "jsx", "mjs", "ts", "tsx"].contains(&ext),
+    None => false,
+  };
+  is_module_ext && path.is_file()
 }
 
-#[cfg(windows)]
-/// On Windows if user is using Powershell .cmd extension is need to run the
-/// installed module.
-/// Generate batch script to satisfy that.
-fn generate_executable_file(
-  file_path: PathBuf,
-  args: Vec<String>,
-) -> Result<(), Error> {
-  let args: Vec<String> = args.iter().map(|c| format!("\"{}\"", c)).collect();
-  let template = format!(
-    "% generated by deno install %\n@deno.exe {} %*\n",
-    args.join(" ")
-  );
-  let mut file = File::create(&file_path)?;
-  file.write_all(template.as_bytes())?;
-  Ok(())
+/// The permission flags of `deno run`, which passed after the module go to
+/// the script instead of granting anything.
+const PERMISSION_FLAGS: &[&str] = &[
+  "-A",
+  "--allow-all",
+  "--allow-env",
+  "--allow-hrtime",
+  "--allow-net",
+  "--allow-plugin",
+  "--allow-read",
+  "--allow-run",
+  "--allow-write",
+];
+
+fn is_permission_flag(arg: &str) -> bool {
+  let flag = arg.splitn(2, '=').next().unwrap_or(arg);
+  PERMISSION_FLAGS.contains(&flag)
 }
 
-#[cfg(not(windows))]
-fn generate_executable_file(
-  file_path: PathBuf,
-  args: Vec<String>,
-) -> Result<(), Error> {
-  let args: Vec<String> = args.iter().map(|c| format!("\"{}\"", c)).collect();
-  let template = format!(
-    r#"#!/bin/sh
-# generated by deno install
-deno {} "$@"
-"#,
-    args.join(" "),
-  );
-  let mut file = File::create(&file_path)?;
-  file.write_all(template.as_bytes())?;
-  let _metadata = fs::metadata(&file_path)?;
-  let mut permissions = _metadata.permissions();
-  permissions.set_mode(0o755);
-  fs::set_permissions(&file_path, permissions)?;
-  Ok(())
+/// Expands a `gh:user/repo[@ref]/path/to/module.ts` shorthand to the raw
+/// GitHub URL of the module, `ref` defaulting to `main`. Other module URLs are
+/// returned unchanged.
+pub fn expand_module_shorthand(module_url: &str) -> Result<String, Error> {
+  let spec = match module_url.strip_prefix("gh:") {
+    Some(spec) => spec,
+    None => return Ok(module_url.to_string()),
+  };
+  let mut parts = spec.splitn(3, '/');
+  let user = parts.next().unwrap_or("");
+  let repo = parts.next().unwrap_or("");
+  let path = parts.next().unwrap_or("");
+  let (repo, git_ref) = match repo.find('@') {
+    Some(index) => (&repo[..index], &repo[index + 1..]),
+    None => (repo, "main"),
+  };
+  if [user, repo, git_ref, path]
+    .iter()
+    .any(|part| part.is_empty())
+    || path.ends_with('/')
+  {
+    return Err(Error::new(
+      ErrorKind::Other,
+      format!(
+        "Invalid GitHub shorthand: {} (expected gh:user/repo[@ref]/path/to/module.ts)",
+        module_url
+      ),
+    ));
+  }
+  Ok(format!(
+    "https://raw.githubusercontent.com/{}/{}/{}/{}",
+    user, repo, git_ref, path
+  ))
 }
 
-fn generate_config_file(
-  file_path: PathBuf,
-  config_file_name: String,
-) -> Result<(), Error> {
-  let config_file_copy_path = get_config_file_path(&file_path);
-  let cwd = std::env::current_dir().unwrap();
-  let config_file_path = cwd.join(config_file_name);
-  fs::copy(config_file_path, config_file_copy_path)?;
-  Ok(())
+/// The ways an installation can fail, each with a stable code for tooling.
+/// Returned by `install`, and carried inside the `std::io::Error` returned by
+/// the other functions.
+#[derive(Debug)]
+pub enum InstallError {
+  EmptyName,
+  /// A name like `a/b`, which isn't a scoped name.
+  NameWithPathSeparator(String),
+  InvalidName(String),
+  ReservedName(String),
+  DeviceName(String),
+  NameTooLong(String, usize),
+  NameInference(NameInferenceError),
+  ExistingInstallation,
+  NotADirectory,
+  RootNotADirectory(PathBuf),
+  BrokenSymlink,
+  ConfigCopy(PathBuf, Error),
+  ImportMapCopy(PathBuf, Error),
+  LockCopy(PathBuf, Error),
+  InvalidMode(u32),
+  DifferentModule(String),
+  ModuleNotFound(PathBuf),
+  NotWritable(PathBuf),
+  InvalidDenoArg(String),
+  InvalidConfigName(String),
+  /// Any other failure, e.g. reading a file.
+  Io(Error),
 }
 
-fn get_installer_root() -> Result<PathBuf, Error> {
-  if let Ok(env_dir) = env::var("DENO_INSTALL_ROOT") {
-    if !env_dir.is_empty() {
-      return PathBuf::from(env_dir).canonicalize();
+impl InstallError {
+  pub fn code(&self) -> &'static str {
+    match self {
+      InstallError::EmptyName => "empty_name",
+      InstallError::NameWithPathSeparator(_) => "name_with_path_separator",
+      InstallError::InvalidName(_) => "invalid_name",
+      InstallError::ReservedName(_) => "reserved_name",
+      InstallError::DeviceName(_) => "device_name",
+      InstallError::NameTooLong(_, _) => "name_too_long",
+      InstallError::NameInference(_) => "name_inference",
+      InstallError::ExistingInstallation => "existing_installation",
+      InstallError::NotADirectory => "not_a_directory",
+      InstallError::RootNotADirectory(_) => "root_not_a_directory",
+      InstallError::BrokenSymlink => "broken_symlink",
+      InstallError::ConfigCopy(_, _) => "config_copy",
+      InstallError::ImportMapCopy(_, _) => "import_map_copy",
+      InstallError::LockCopy(_, _) => "lock_copy",
+      InstallError::InvalidMode(_) => "invalid_mode",
+      InstallError::DifferentModule(_) => "different_module",
+      InstallError::ModuleNotFound(_) => "module_not_found",
+      InstallError::NotWritable(_) => "not_writable",
+      InstallError::InvalidDenoArg(_) => "invalid_deno_arg",
+      InstallError::InvalidConfigName(_) => "invalid_config_name",
+      InstallError::Io(err) if err.kind() == ErrorKind::Other => "other",
+      InstallError::Io(_) => "io",
     }
   }
-  // Note: on Windows, the $HOME environment variable may be set by users or by
-  // third party software, but it is non-standard and should not be relied upon.
-  let home_env_var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
-  let mut home_path =
-    env::var_os(home_env_var)
-      .map(PathBuf::from)
-      .ok_or_else(|| {
-        Error::new(
-          ErrorKind::NotFound,
-          format!("${} is not defined", home_env_var),
-        )
-      })?;
-  home_path.push(".deno");
-  Ok(home_path)
+
+  pub fn kind(&self) -> ErrorKind {
+    match self {
+      InstallError::EmptyName
+      | InstallError::NameWithPathSeparator(_)
+      | InstallError::InvalidName(_)
+      | InstallError::ReservedName(_)
+      | InstallError::DeviceName(_)
+      | InstallError::NameTooLong(_, _)
+      | InstallError::NameInference(_)
+      | InstallError::InvalidMode(_)
+      | InstallError::InvalidDenoArg(_)
+      | InstallError::InvalidConfigName(_) => ErrorKind::InvalidInput,
+      InstallError::ExistingInstallation | InstallError::DifferentModule(_) => {
+        ErrorKind::AlreadyExists
+      }
+      InstallError::BrokenSymlink | InstallError::ModuleNotFound(_) => {
+        ErrorKind::NotFound
+      }
+      InstallError::NotWritable(_) => ErrorKind::PermissionDenied,
+      InstallError::NotADirectory | InstallError::RootNotADirectory(_) => {
+        ErrorKind::Other
+      }
+      InstallError::ConfigCopy(_, err)
+      | InstallError::ImportMapCopy(_, err)
+      | InstallError::LockCopy(_, err)
+      | InstallError::Io(err) => err.kind(),
+    }
+  }
+
+  /// The `InstallError` carried by `err`, if any.
+  pub fn downcast(err: &Error) -> Option<&InstallError> {
+    err
+      .get_ref()
+      .and_then(|inner| inner.downcast_ref::<InstallError>())
+  }
 }
 
-fn infer_name_from_url(url: &Url) -> Option<String> {
-  let path = PathBuf::from(url.path());
-  let stem = match path.file_stem() {
-    Some(stem) => stem.to_string_lossy().to_string(),
-    None => return None,
-  };
-  if let Some(parent_path) = path.parent() {
-    if stem == "main" || stem == "mod" || stem == "index" || stem == "cli" {
-      if let Some(parent_name) = parent_path.file_name() {
-        return Some(parent_name.to_string_lossy().to_string());
+impl fmt::Display for InstallError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      InstallError::EmptyName => {
+        write!(f, "Executable name cannot be empty")
+      }
+      InstallError::NameWithPathSeparator(_) => write!(
+        f,
+        "Executable names cannot contain path separators; did you mean --root?"
+      ),
+      InstallError::InvalidName(name) => {
+        write!(f, "Invalid executable name: {}", name)
+      }
+      InstallError::ReservedName(name) => write!(
+        f,
+        "Executable name {} is reserved, it would shadow deno or one of its subcommands",
+        name
+      ),
+      InstallError::DeviceName(name) => write!(
+        f,
+        "Executable name {} is a device name reserved by Windows",
+        name
+      ),
+      InstallError::NameTooLong(name, max_length) => write!(
+        f,
+        "Executable name {} is longer than {} characters",
+        name, max_length
+      ),
+      InstallError::NameInference(reason) => write!(
+        f,
+        "An executable name was not provided and could not be inferred: {}. Pass one with --name.",
+        reason
+      ),
+      InstallError::ExistingInstallation => write!(
+        f,
+        "Existing installation found. Aborting (Use -f to overwrite)."
+      ),
+      InstallError::NotADirectory => {
+        write!(f, "Installation path is not a directory")
+      }
+      InstallError::RootNotADirectory(path) => write!(
+        f,
+        "Install root is not a directory: {}",
+        path.to_string_lossy()
+      ),
+      InstallError::BrokenSymlink => {
+        write!(f, "Installation path is a broken symlink")
+      }
+      InstallError::ConfigCopy(path, err) => write!(
+        f,
+        "Failed to copy config file {}: {}",
+        path.to_string_lossy(),
+        err
+      ),
+      InstallError::ImportMapCopy(path, err) => write!(
+        f,
+        "Failed to copy import map {}: {}",
+        path.to_string_lossy(),
+        err
+      ),
+      InstallError::LockCopy(path, err) => write!(
+        f,
+        "Failed to copy lock file {}: {}",
+        path.to_string_lossy(),
+        err
+      ),
+      InstallError::InvalidMode(mode) => write!(
+        f,
+        "File mode {:o} doesn't let the owner execute the script",
+        mode
+      ),
+      InstallError::DifferentModule(installed_url) => write!(
+        f,
+        "Existing installation of another module found: {}. Aborting (Use -f to overwrite).",
+        installed_url
+      ),
+      InstallError::ModuleNotFound(path) => {
+        write!(f, "Module not found: {}", path.to_string_lossy())
       }
+      InstallError::NotWritable(path) => write!(
+        f,
+        "Installation directory is not writable: {}",
+        path.to_string_lossy()
+      ),
+      InstallError::InvalidDenoArg(arg) => write!(
+        f,
+        "Unsupported deno run flag {}, only {} can be passed before the module",
+        arg,
+        DENO_ARGS_ALLOWLIST.join(", ")
+      ),
+      InstallError::InvalidConfigName(config_name) => write!(
+        f,
+        "Invalid config file name {}, it must be a .json file name without a directory and not the name of an existing file",
+        config_name
+      ),
+      InstallError::Io(err) => err.fmt(f),
     }
   }
-  Some(stem)
 }
 
-pub fn install(
-  flags: Flags,
-  module_url: &str,
-  args: Vec<String>,
-  name: Option<String>,
-  root: Option<PathBuf>,
-  force: bool,
-) -> Result<(), Error> {
-  let root = if let Some(root) = root {
-    root.canonicalize()?
-  } else {
-    get_installer_root()?
-  };
-  let installation_dir = root.join("bin");
+impl std::error::Error for InstallError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      InstallError::ConfigCopy(_, err)
+      | InstallError::ImportMapCopy(_, err)
+      | InstallError::LockCopy(_, err) => Some(err),
+      _ => None,
+    }
+  }
+}
 
-  // ensure directory exists
-  if let Ok(metadata) = fs::metadata(&installation_dir) {
-    if !metadata.is_dir() {
-      return Err(Error::new(
-        ErrorKind::Other,
-        "Installation path is not a directory",
-      ));
+impl From<Error> for InstallError {
+  fn from(err: Error) -> InstallError {
+    match InstallError::downcast(&err) {
+      // Unwrapped again, e.g. from `validate_name` through a helper.
+      Some(_) => *err
+        .into_inner()
+        .unwrap()
+        .downcast::<InstallError>()
+        .unwrap(),
+      None => InstallError::Io(err),
     }
-  } else {
-    fs::create_dir_all(&installation_dir)?;
-  };
+  }
+}
 
-  // Check if module_url is remote
-  let module_url = if is_remote_url(module_url) {
-    Url::parse(module_url).expect("Should be valid url")
-  } else {
-    let module_path = PathBuf::from(module_url);
-    let module_path = if module_path.is_absolute() {
-      module_path
-    } else {
-      let cwd = env::current_dir().unwrap();
-      cwd.join(module_path)
-    };
-    Url::from_file_path(module_path).expect("Path should be absolute")
-  };
+impl From<serde_json::Error> for InstallError {
+  fn from(err: serde_json::Error) -> InstallError {
+    InstallError::Io(err.into())
+  }
+}
 
-  let name = name.or_else(|| infer_name_from_url(&module_url));
+impl From<InstallError> for Error {
+  fn from(err: InstallError) -> Error {
+    match err {
+      InstallError::Io(err) => err,
+      err => Error::new(err.kind(), err),
+    }
+  }
+}
 
-  let name = match name {
-    Some(name) => name,
-    None => return Err(Error::new(
-      ErrorKind::Other,
-      "An executable name was not provided. One could not be inferred from the URL. Aborting.",
-    )),
-  };
+/// Renders an installation failure as `{"error":{"code":..,"message":..}}`.
+/// `InstallError::Io` failures get the `other` or `io` code.
+pub fn error_to_json(err: &InstallError) -> String {
+  serde_json::json!({ "error": error_to_value(err) }).to_string()
+}
 
-  validate_name(name.as_str())?;
-  let mut file_path = installation_dir.join(&name);
+fn error_to_value(err: &InstallError) -> serde_json::Value {
+  serde_json::json!({
+    "code": err.code(),
+    "message": err.to_string(),
+  })
+}
 
-  if cfg!(windows) {
-    file_path = file_path.with_extension("cmd");
+/// The flags of `deno run` that can be passed before the module, as they are
+/// or, for those ending with `=`, with a value.
+const DENO_ARGS_ALLOWLIST: &[&str] = &[
+  "--reload",
+  "--reload=",
+  "--no-check",
+  "--no-check=",
+  "--unstable",
+  "--quiet",
+  "--cached-only",
+  "--lock-write",
+  "--log-level=",
+  "--seed=",
+  "--v8-flags=",
+];
+
+fn validate_deno_arg(arg: &str) -> Result<(), InstallError> {
+  let is_allowed = DENO_ARGS_ALLOWLIST.iter().any(|allowed| {
+    if allowed.ends_with('=') {
+      arg.starts_with(allowed) && arg.len() > allowed.len()
+    } else {
+      arg == *allowed
+    }
+  });
+  if is_allowed {
+    Ok(())
+  } else {
+    Err(InstallError::InvalidDenoArg(arg.to_string()))
   }
+}
 
-  if file_path.exists() && !force {
-    return Err(Error::new(
-      ErrorKind::Other,
-      "Existing installation found. Aborting (Use -f to overwrite).",
-    ));
+fn validate_name(exec_name: &str) -> Result<(), InstallError> {
+  if exec_name.trim().is_empty() {
+    Err(InstallError::EmptyName)
+  } else if SCOPED_NAME_RE.is_match(exec_name) {
+    // Run through the scope directory, a scoped name shadows nothing.
+    Ok(())
+  } else if !exec_name.starts_with('@')
+    && (exec_name.contains('/') || exec_name.contains('\\'))
+  {
+    Err(InstallError::NameWithPathSeparator(exec_name.to_string()))
+  } else if !EXEC_NAME_RE.is_match(exec_name) {
+    Err(InstallError::InvalidName(exec_name.to_string()))
+  } else if RESERVED_NAMES.contains(exec_name.to_lowercase().as_str()) {
+    Err(InstallError::ReservedName(exec_name.to_string()))
+  } else if cfg!(windows)
+    && WINDOWS_DEVICE_NAMES.contains(exec_name.to_lowercase().as_str())
+  {
+    Err(InstallError::DeviceName(exec_name.to_string()))
+  } else {
+    Ok(())
+  }
+}
+
+/// A bin subdirectory is a single directory of the root, or `.` for the root.
+fn validate_bin_subdir(bin_subdir: &str) -> Result<(), Error> {
+  let mut components = Path::new(bin_subdir).components();
+  let is_single_dir = match (components.next(), components.next()) {
+    (Some(Component::Normal(_)), None) | (Some(Component::CurDir), None) => {
+      !bin_subdir.contains('/') && !bin_subdir.contains('\\')
+    }
+    _ => false,
   };
+  if is_single_dir {
+    Ok(())
+  } else {
+    Err(Error::new(
+      ErrorKind::InvalidInput,
+      format!(
+        "Invalid bin subdirectory {}, it must be the name of a directory of the root",
+        bin_subdir
+      ),
+    ))
+  }
+}
 
-  let mut executable_args = vec!["run".to_string()];
-  executable_args.extend_from_slice(&flags.to_permission_args());
-  if let Some(ca_file) = flags.ca_file {
-    executable_args.push("--cert".to_string());
-    executable_args.push(ca_file)
+/// Only portable names are set, as they are written into the executables
+/// unquoted.
+fn validate_env_name(key: &str) -> Result<(), Error> {
+  let mut chars = key.chars();
+  let is_valid = match chars.next() {
+    Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+      chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+    _ => false,
+  };
+  if is_valid {
+    Ok(())
+  } else {
+    Err(Error::new(
+      ErrorKind::InvalidInput,
+      format!("Invalid environment variable name: {}", key),
+    ))
   }
-  if let Some(log_level) = flags.log_level {
-    if log_level == Level::Error {
-      executable_args.push("--quiet".to_string());
-    } else {
-      executable_args.push("--log-level".to_string());
-      let log_level = match log_level {
-        Level::Debug => "debug",
-        Level::Info => "info",
-        _ => {
-          return Err(Error::new(
-            ErrorKind::Other,
-            format!("invalid log level {}", log_level),
-          ))
-        }
-      };
-      executable_args.push(log_level.to_string());
+}
+
+fn get_bin_dir(root: &Path, bin_subdir: Option<&str>) -> PathBuf {
+  match bin_subdir {
+    Some(".") => root.to_path_buf(),
+    Some(bin_subdir) => root.join(bin_subdir),
+    None => root.join("bin"),
+  }
+}
+
+/// Fails early, before anything is written, when no file can be created in
+/// `dir`. Found by creating and removing a file, which also takes ACLs and
+/// read-only file systems into account.
+fn check_dir_writable(dir: &Path) -> Result<(), Error> {
+  let probe_path =
+    dir.join(format!(".deno-install-{}.tmp", std::process::id()));
+  match File::create(&probe_path) {
+    Ok(_) => fs::remove_file(&probe_path),
+    Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+      Err(InstallError::NotWritable(dir.to_path_buf()).into())
     }
+    Err(err) => Err(err),
+  }
+}
+
+/// An exclusive advisory lock on the `.install.lock` file of an installation
+/// directory, so that concurrent installations into it run one after the
+/// other. Released when dropped, as the file is closed.
+///
+/// The file is in the sidecar directory, which is the bin directory unless
+/// that is shared with other software. It is kept after the last script is
+/// uninstalled: removing it while an installation waits on it would let the
+/// next one lock a new file and run alongside. --list, --doctor and --prune
+/// leave it out, it is neither an executable nor a file of a script.
+struct InstallLock {
+  _file: File,
+}
+
+impl InstallLock {
+  fn acquire(dir: &Path) -> Result<InstallLock, Error> {
+    let file = fs::OpenOptions::new()
+      .create(true)
+      .write(true)
+      .open(dir.join(".install.lock"))?;
+    debug!("Waiting for the lock of {}", dir.to_string_lossy());
+    lock_file(&file)?;
+    Ok(InstallLock { _file: file })
   }
+}
 
-  if flags.no_check {
-    executable_args.push("--no-check".to_string());
+#[cfg(unix)]
+fn lock_file(file: &File) -> Result<(), Error> {
+  use std::os::unix::io::AsRawFd;
+  if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+    return Err(Error::last_os_error());
   }
+  Ok(())
+}
 
-  if flags.unstable {
-    executable_args.push("--unstable".to_string());
+#[cfg(windows)]
+fn lock_file(file: &File) -> Result<(), Error> {
+  use std::os::windows::io::AsRawHandle;
+  use winapi::um::fileapi::LockFileEx;
+  use winapi::um::minwinbase::{LOCKFILE_EXCLUSIVE_LOCK, OVERLAPPED};
+  let mut overlapped: OVERLAPPED = unsafe { std::mem::zeroed() };
+  let locked = unsafe {
+    LockFileEx(
+      file.as_raw_handle() as _,
+      LOCKFILE_EXCLUSIVE_LOCK,
+      0,
+      !0,
+      !0,
+      &mut overlapped,
+    )
+  };
+  if locked == 0 {
+    return Err(Error::last_os_error());
   }
+  Ok(())
+}
 
-  if flags.config_path.is_some() {
-    let config_file_path = get_config_file_path(&file_path);
-    let config_file_path_option = config_file_path.to_str();
-    if let Some(config_file_path_string) = config_file_path_option {
-      executable_args.push("--config".to_string());
-      executable_args.push(config_file_path_string.to_string());
+/// Returns true if `path` is `dir` joined with plain file names only, so it
+/// can't escape `dir` through `..`, the root or a prefix.
+fn is_inside_dir(dir: &Path, path: &Path) -> bool {
+  match path.strip_prefix(dir) {
+    Ok(relative) => {
+      relative.components().next().is_some()
+        && relative
+          .components()
+          .all(|component| matches!(component, Component::Normal(_)))
     }
+    Err(_) => false,
   }
+}
 
-  executable_args.push(module_url.to_string());
-  executable_args.extend_from_slice(&args);
-
-  generate_executable_file(file_path.to_owned(), executable_args)?;
-  if let Some(config_path) = flags.config_path {
-    generate_config_file(file_path.to_owned(), config_path)?;
+/// Some shells don't run the last line of a script that lacks a newline, so
+/// every generated executable must end in one, whatever the template.
+fn ensure_trailing_newline(mut content: String) -> String {
+  if !content.ends_with('\n') {
+    content.push('\n');
   }
+  content
+}
 
-  println!("✅ Successfully installed {}", name);
-  println!("{}", file_path.to_string_lossy());
-  let installation_dir_str = installation_dir.to_string_lossy();
+/// The kinds of executable that can be generated for an installed script.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExecutableKind {
+  /// POSIX shell script, run natively on unix.
+  Sh,
+  /// Batch script. On Windows if user is using Powershell .cmd extension is
+  /// need to run the installed module.
+  Cmd,
+  /// PowerShell script, run by `pwsh` on any platform.
+  Ps1,
+}
 
-  if !is_in_path(&installation_dir) {
-    println!("ℹ️  Add {} to PATH", installation_dir_str);
+impl ExecutableKind {
+  /// The kind generated when no other is requested.
+  fn host() -> ExecutableKind {
     if cfg!(windows) {
-      println!("    set PATH=%PATH%;{}", installation_dir_str);
+      ExecutableKind::Cmd
     } else {
-      println!("    export PATH=\"{}:$PATH\"", installation_dir_str);
+      ExecutableKind::Sh
     }
   }
 
-  Ok(())
-}
+  /// The kinds generated when no other is requested. On Windows, a
+  /// PowerShell script is generated next to the batch file, as PowerShell
+  /// runs it in preference and forwards arguments and exit codes properly.
+  fn host_kinds() -> Vec<ExecutableKind> {
+    if cfg!(windows) {
+      vec![ExecutableKind::Cmd, ExecutableKind::Ps1]
+    } else {
+      vec![ExecutableKind::Sh]
+    }
+  }
 
-fn is_in_path(dir: &PathBuf) -> bool {
-  if let Some(paths) = env::var_os("PATH") {
-    for p in env::split_paths(&paths) {
-      if *dir == p {
-        return true;
+  pub fn from_name(name: &str) -> Option<ExecutableKind> {
+    match name {
+      "sh" => Some(ExecutableKind::Sh),
+      "cmd" => Some(ExecutableKind::Cmd),
+      "ps1" => Some(ExecutableKind::Ps1),
+      _ => None,
+    }
+  }
+
+  fn extension(self) -> Option<&'static str> {
+    match self {
+      ExecutableKind::Sh => None,
+      ExecutableKind::Cmd => Some("cmd"),
+      ExecutableKind::Ps1 => Some("ps1"),
+    }
+  }
+
+  /// Runs `deno_path`, or the deno found in PATH when not given, with the
+  /// variables of `env` set, from `run_dir` if given.
+  fn render(
+    self,
+    deno_path: Option<&Path>,
+    env: &[(String, String)],
+    run_dir: Option<&Path>,
+    args: &[String],
+  ) -> String {
+    let run_dir = run_dir.map(|path| path.to_string_lossy());
+    let deno_path = deno_path.map(|path| path.to_string_lossy());
+    match self {
+      ExecutableKind::Sh => {
+        let args: Vec<String> = args
+          .iter()
+          .map(|c| format!("\"{}\"", escape_sh(c)))
+          .collect();
+        let deno = match deno_path {
+          Some(deno_path) => format!("\"{}\"", escape_sh(&deno_path)),
+          None => "deno".to_string(),
+        };
+        let mut exports: String = env
+          .iter()
+          .map(|(key, value)| {
+            format!("export {}=\"{}\"\n", key, escape_sh(value))
+          })
+          .collect();
+        if let Some(run_dir) = &run_dir {
+          exports.push_str(&format!("cd \"{}\" || exit\n", escape_sh(run_dir)));
+        }
+        format!(
+          r#"#!/usr/bin/env sh
+# generated by deno install
+{}{} {} "$@"
+"#,
+          exports,
+          deno,
+          args.join(" "),
+        )
+      }
+      ExecutableKind::Cmd => {
+        let args: Vec<String> = args
+          .iter()
+          .map(|c| format!("\"{}\"", escape_cmd(c)))
+          .collect();
+        let deno = match deno_path {
+          Some(deno_path) => format!("\"{}\"", escape_cmd(&deno_path)),
+          None => "deno.exe".to_string(),
+        };
+        // Local to the batch file, so that the variables and the current
+        // directory aren't left changed in the cmd session that runs it.
+        let sets: String = if env.is_empty() && run_dir.is_none() {
+          String::new()
+        } else {
+          let mut sets: String = env
+            .iter()
+            .map(|(key, value)| {
+              format!("@set \"{}={}\"\n", key, escape_cmd_set_value(value))
+            })
+            .collect();
+          if let Some(run_dir) = &run_dir {
+            sets.push_str(&format!(
+              "@cd /d \"{}\" || exit /b 1\n",
+              escape_cmd(run_dir)
+            ));
+          }
+          format!("@setlocal\n{}", sets)
+        };
+        format!(
+          "% generated by deno install %\n{}@{} {} %*\n@exit /b %errorlevel%\n",
+          sets,
+          deno,
+          args.join(" ")
+        )
+      }
+      ExecutableKind::Ps1 => {
+        // Single quoted so that PowerShell doesn't expand `$` in the args.
+        let args: Vec<String> = args
+          .iter()
+          .map(|c| format!("'{}'", c.replace("'", "''")))
+          .collect();
+        let deno = match deno_path {
+          // A quoted command is only run with the call operator.
+          Some(deno_path) => format!("& '{}'", deno_path.replace("'", "''")),
+          None if cfg!(windows) => "deno.exe".to_string(),
+          None => "deno".to_string(),
+        };
+        let mut sets: String = env
+          .iter()
+          .map(|(key, value)| {
+            format!("$env:{} = '{}'\n", key, value.replace("'", "''"))
+          })
+          .collect();
+        // The location is the session's, so it is restored once deno exits.
+        let exit = match &run_dir {
+          Some(run_dir) => {
+            sets.push_str(&format!(
+              "Push-Location -LiteralPath '{}' -ErrorAction Stop\n",
+              run_dir.replace("'", "''")
+            ));
+            "$exitCode = $LASTEXITCODE\nPop-Location\nexit $exitCode\n"
+          }
+          None => "exit $LASTEXITCODE\n",
+        };
+        format!(
+          "#!/usr/bin/env pwsh\n# generated by deno install\n{}{} {} @args\n{}",
+          sets,
+          deno,
+          args.join(" "),
+          exit
+        )
       }
     }
   }
-  false
 }
 
-fn get_config_file_path(file_path: &PathBuf) -> PathBuf {
-  let mut config_file_copy_path = PathBuf::from(file_path);
-  config_file_copy_path.set_extension("tsconfig.json");
-  config_file_copy_path
+impl ExecutableKind {
+  /// Adds comments naming the module and when it was installed after the
+  /// first lines of `template`, which identify it as generated, preceded by
+  /// the single line `header` if given.
+  fn add_header(
+    self,
+    template: &str,
+    module_url: &str,
+    installed_at: u64,
+    header: Option<&str>,
+  ) -> String {
+    let installed_at = format_timestamp(installed_at);
+    let (mut comments, header_lines) = match self {
+      ExecutableKind::Sh | ExecutableKind::Ps1 => (
+        format!("# module: {}\n# installed: {}\n", module_url, installed_at),
+        2,
+      ),
+      // A `%` delimited comment couldn't hold the percent signs of a URL.
+      ExecutableKind::Cmd => (
+        format!(
+          "@rem module: {}\n@rem installed: {}\n",
+          escape_cmd(module_url),
+          installed_at
+        ),
+        1,
+      ),
+    };
+    if let Some(header) = header {
+      let header = match self {
+        ExecutableKind::Sh | ExecutableKind::Ps1 => format!("# {}\n", header),
+        ExecutableKind::Cmd => format!("@rem {}\n", escape_cmd(header)),
+      };
+      comments.insert_str(0, &header);
+    }
+    let split_index = template
+      .match_indices('\n')
+      .nth(header_lines - 1)
+      .map_or(template.len(), |(index, _)| index + 1);
+    format!(
+      "{}{}{}",
+      &template[..split_index],
+      comments,
+      &template[split_index..]
+    )
+  }
 }
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use std::sync::Mutex;
-  use tempfile::TempDir;
+/// `secs` since the unix epoch as an ISO 8601 date and time in UTC.
+fn format_timestamp(secs: u64) -> String {
+  let days = secs / 86_400;
+  let secs_of_day = secs % 86_400;
+  // The civil date of `days`, with years starting in March so that the leap
+  // day is the last of its year.
+  let z = days + 719_468;
+  let era = z / 146_097;
+  let day_of_era = z - era * 146_097;
+  let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+    - day_of_era / 146_096)
+    / 365;
+  let day_of_year =
+    day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+  let month_from_march = (5 * day_of_year + 2) / 153;
+  let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+  let month = if month_from_march < 10 {
+    month_from_march + 3
+  } else {
+    month_from_march - 9
+  };
+  let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+  format!(
+    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+    year,
+    month,
+    day,
+    secs_of_day / 3600,
+    secs_of_day / 60 % 60,
+    secs_of_day % 60
+  )
+}
 
-  lazy_static! {
-    pub static ref ENV_LOCK: Mutex<()> = Mutex::new(());
+/// Escapes the characters that keep their meaning inside a double quoted sh
+/// string.
+fn escape_sh(arg: &str) -> String {
+  let mut escaped = String::with_capacity(arg.len());
+  for c in arg.chars() {
+    if let '"' | '$' | '`' | '\\' = c {
+      escaped.push('\\');
+    }
+    escaped.push(c);
   }
+  escaped
+}
 
-  #[test]
-  fn test_is_remote_url() {
-    assert!(is_remote_url("https://deno.land/std/http/file_server.ts"));
-    assert!(is_remote_url("http://deno.land/std/http/file_server.ts"));
-    assert!(is_remote_url("HTTP://deno.land/std/http/file_server.ts"));
-    assert!(is_remote_url("HTTp://deno.land/std/http/file_server.ts"));
-    assert!(!is_remote_url("file:///dev/deno_std/http/file_server.ts"));
-    assert!(!is_remote_url("./dev/deno_std/http/file_server.ts"));
+/// Doubles the quotes, which would otherwise end the quoted cmd argument, and
+/// the percent signs, which would otherwise expand variables.
+fn escape_cmd(arg: &str) -> String {
+  arg.replace('"', "\"\"").replace('%', "%%")
+}
+
+/// Escapes the value of `@set "KEY=VALUE"`, which is taken as is up to the
+/// last quote, quotes included. Only the percent signs are doubled, and the
+/// characters cmd would act on where the quotes of the value leave the rest
+/// unquoted are escaped with a caret.
+fn escape_cmd_set_value(value: &str) -> String {
+  let mut escaped = String::with_capacity(value.len());
+  let mut quoted = true;
+  for c in value.chars() {
+    match c {
+      '%' => escaped.push('%'),
+      '"' => quoted = !quoted,
+      '&' | '|' | '<' | '>' | '^' if !quoted => escaped.push('^'),
+      _ => {}
+    }
+    escaped.push(c);
   }
+  escaped
+}
 
-  #[test]
-  fn install_infer_name_from_url() {
-    assert_eq!(
-      infer_name_from_url(
-        &Url::parse("https://example.com/abc/server.ts").unwrap()
-      ),
+/// `mode` is only applied on unix, as `0o755` if not given. With
+/// `trampoline`, a batch file is replaced with a copy of that stub, see
+/// `write_trampoline`. Returns the path of the file written.
+#[allow(clippy::too_many_arguments)]
+fn generate_executable_file(
+  kind: ExecutableKind,
+  file_path: PathBuf,
+  args: Vec<String>,
+  deno_path: Option<&Path>,
+  env: &[(String, String)],
+  run_dir: Option<&Path>,
+  mode: Option<u32>,
+  trampoline: Option<&Path>,
+) -> Result<PathBuf, Error> {
+  if let (ExecutableKind::Cmd, Some(stub)) = (kind, trampoline) {
+    return write_trampoline(stub, &file_path, deno_path, env, run_dir, &args);
+  }
+  let template =
+    ensure_trailing_newline(kind.render(deno_path, env, run_dir, &args));
+  write_executable_file(&file_path, &template, mode)?;
+  Ok(file_path)
+}
+
+/// What the stub of a trampoline runs, read from the file next to it.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct TrampolineArgs {
+  /// deno.exe is looked up in PATH.
+  deno: String,
+  args: Vec<String>,
+  env: Vec<(String, String)>,
+  cwd: Option<PathBuf>,
+}
+
+/// Copies the precompiled `stub` to `<name>.exe` in place of the batch file
+/// at `cmd_path`, with what it runs in `<name>.exe.json`. Unlike a batch
+/// file, it can be started with CreateProcess, without a shell.
+fn write_trampoline(
+  stub: &Path,
+  cmd_path: &Path,
+  deno_path: Option<&Path>,
+  env: &[(String, String)],
+  run_dir: Option<&Path>,
+  args: &[String],
+) -> Result<PathBuf, Error> {
+  let exe_path = cmd_path.with_extension("exe");
+  debug!(
+    "Copying trampoline {} to {}",
+    stub.to_string_lossy(),
+    exe_path.to_string_lossy()
+  );
+  retry_transient(|| fs::copy(stub, &exe_path))?;
+  let trampoline_args = TrampolineArgs {
+    deno: deno_path.map_or("deno.exe".to_string(), |deno_path| {
+      deno_path.to_string_lossy().to_string()
+    }),
+    args: args.to_vec(),
+    env: env.to_vec(),
+    cwd: run_dir.map(Path::to_path_buf),
+  };
+  let trampoline_args = serde_json::to_string_pretty(&trampoline_args)?;
+  retry_transient(|| {
+    fs::write(get_trampoline_args_path(&exe_path), &trampoline_args)
+  })?;
+  Ok(exe_path)
+}
+
+fn get_trampoline_args_path(exe_path: &Path) -> PathBuf {
+  append_extension(exe_path, "json")
+}
+
+/// Whether `exe_path` is a trampoline written by `write_trampoline`, as
+/// opposed to a program that happens to have the script's name.
+fn is_trampoline(exe_path: &Path) -> bool {
+  exe_path.is_file()
+    && fs::read_to_string(get_trampoline_args_path(exe_path))
+      .ok()
+      .and_then(|args| serde_json::from_str::<TrampolineArgs>(&args).ok())
+      .is_some()
+}
+
+/// Writes the executable `template` rendered by `generate_executable_file`.
+fn write_executable_file(
+  file_path: &Path,
+  template: &str,
+  mode: Option<u32>,
+) -> Result<(), Error> {
+  retry_transient(|| {
+    let mut file = File::create(file_path)?;
+    file.write_all(template.as_bytes())
+  })?;
+  #[cfg(not(windows))]
+  {
+    let _metadata = fs::metadata(file_path)?;
+    let mut permissions = _metadata.permissions();
+    permissions.set_mode(get_executable_file_mode(mode));
+    fs::set_permissions(file_path, permissions)?;
+  }
+  #[cfg(windows)]
+  let _ = mode;
+  Ok(())
+}
+
+fn get_executable_file_mode(mode: Option<u32>) -> u32 {
+  mode.unwrap_or(0o755)
+}
+
+/// Whether `file_path` holds exactly `content` and, on unix, has the
+/// permissions `mode` if given.
+fn is_file_up_to_date(
+  file_path: &Path,
+  content: &[u8],
+  mode: Option<u32>,
+) -> bool {
+  match fs::read(file_path) {
+    Ok(installed_content) if installed_content == content => {}
+    _ => return false,
+  }
+  #[cfg(not(windows))]
+  {
+    if let Some(mode) = mode {
+      return fs::metadata(file_path).map_or(false, |metadata| {
+        metadata.permissions().mode() & 0o7777 == mode
+      });
+    }
+  }
+  #[cfg(windows)]
+  let _ = mode;
+  true
+}
+
+/// Copies the config file at `config_file_path`, already resolved against the
+/// current directory, next to the executable. For --config-template, each
+/// `{{KEY}}` of `template_vars` is replaced with its value, as a JSON string.
+/// On unix the copy gets the permissions of `get_config_file_mode`, whatever
+/// those of the source.
+fn generate_config_file(
+  config_file_copy_path: &Path,
+  config_file_path: &Path,
+  template_vars: &[(&str, String)],
+  mode: Option<u32>,
+) -> Result<(), Error> {
+  let config_error =
+    |err| InstallError::ConfigCopy(config_file_path.to_path_buf(), err);
+  let config_file_path =
+    config_file_path.canonicalize().map_err(config_error)?;
+  debug!(
+    "Copying config {} to {}",
+    config_file_path.to_string_lossy(),
+    config_file_copy_path.to_string_lossy()
+  );
+  let config = render_config_file(&config_file_path, template_vars)
+    .map_err(config_error)?;
+  retry_transient(|| fs::write(&config_file_copy_path, &config))
+    .map_err(config_error)?;
+
+  #[cfg(not(windows))]
+  {
+    let permissions = fs::Permissions::from_mode(get_config_file_mode(mode));
+    fs::set_permissions(&config_file_copy_path, permissions)
+      .map_err(config_error)?;
+  }
+  #[cfg(windows)]
+  let _ = mode;
+  Ok(())
+}
+
+/// The content of the copy of the config file at `config_file_path`, with the
+/// `template_vars` substituted.
+fn render_config_file(
+  config_file_path: &Path,
+  template_vars: &[(&str, String)],
+) -> Result<Vec<u8>, Error> {
+  if template_vars.is_empty() {
+    return fs::read(config_file_path);
+  }
+  let mut config = fs::read_to_string(config_file_path)?;
+  for (key, value) in template_vars {
+    // Escaped so that the config stays valid JSON, eg. with Windows paths.
+    let value = serde_json::to_string(value)?;
+    let value = &value[1..value.len() - 1];
+    config = config.replace(&format!("{{{{{}}}}}", key), value);
+  }
+  if let Err(err) = parse_config_json(&config) {
+    return Err(Error::new(
+      ErrorKind::InvalidData,
+      format!("not valid JSON after substitution: {}", err),
+    ));
+  }
+  Ok(config.into_bytes())
+}
+
+/// Reads the file at `path` as the JSON deno would read it, a config file
+/// with the comments and trailing commas TypeScript allows in them.
+fn check_json_file(path: &Path, is_config: bool) -> Result<(), Error> {
+  let content = fs::read_to_string(path)?;
+  let result = if is_config {
+    parse_config_json(&content).map(|_| ())
+  } else {
+    serde_json::from_str::<serde_json::Value>(&content).map(|_| ())
+  };
+  result.map_err(|err| {
+    Error::new(ErrorKind::InvalidData, format!("invalid JSON: {}", err))
+  })
+}
+
+/// Comments and trailing commas are blanked out rather than removed, so that
+/// the positions in errors are those of `config`.
+fn parse_config_json(
+  config: &str,
+) -> Result<serde_json::Value, serde_json::Error> {
+  let mut json = String::with_capacity(config.len());
+  let mut chars = config.chars().peekable();
+  let mut in_string = false;
+  while let Some(c) = chars.next() {
+    if in_string {
+      json.push(c);
+      if c == '\\' {
+        json.extend(chars.next());
+      } else if c == '"' {
+        in_string = false;
+      }
+      continue;
+    }
+    match (c, chars.peek()) {
+      ('"', _) => {
+        in_string = true;
+        json.push(c);
+      }
+      ('/', Some('/')) => {
+        json.push(' ');
+        while let Some(c) = chars.peek() {
+          if *c == '\n' {
+            break;
+          }
+          json.push(' ');
+          chars.next();
+        }
+      }
+      ('/', Some('*')) => {
+        json.push(' ');
+        json.push(' ');
+        chars.next();
+        while let Some(c) = chars.next() {
+          if c == '*' && chars.peek() == Some(&'/') {
+            chars.next();
+            json.push_str("  ");
+            break;
+          }
+          json.push(if c == '\n' { '\n' } else { ' ' });
+        }
+      }
+      _ => json.push(c),
+    }
+  }
+  // A comma followed by nothing but whitespace and a closing bracket.
+  let mut bytes = json.into_bytes();
+  for index in 0..bytes.len() {
+    if bytes[index] == b',' {
+      let next = bytes[index + 1..].iter().find(|b| !b.is_ascii_whitespace());
+      if let Some(b'}') | Some(b']') = next {
+        bytes[index] = b' ';
+      }
+    }
+  }
+  let json = String::from_utf8(bytes).unwrap_or_default();
+  serde_json::from_str(&json)
+}
+
+/// The config copy is only read, by everyone unless `mode`, the permissions
+/// of the executables, keeps the group and others from reading them.
+fn get_config_file_mode(mode: Option<u32>) -> u32 {
+  match mode {
+    Some(mode) if mode & 0o044 == 0 => 0o600,
+    _ => 0o644,
+  }
+}
+
+/// Copies the import map at `import_map_path`, already resolved against the
+/// current directory, next to the executable.
+fn generate_import_map_file(
+  sidecar_path: &Path,
+  import_map_path: &Path,
+) -> Result<(), Error> {
+  let import_map_copy_path = get_import_map_file_path(sidecar_path);
+  debug!(
+    "Copying import map {} to {}",
+    import_map_path.to_string_lossy(),
+    import_map_copy_path.to_string_lossy()
+  );
+  import_map_path
+    .canonicalize()
+    .and_then(|import_map_path| {
+      retry_transient(|| fs::copy(&import_map_path, &import_map_copy_path))
+    })
+    .map_err(|err| {
+      InstallError::ImportMapCopy(import_map_path.to_path_buf(), err)
+    })?;
+  Ok(())
+}
+
+/// Copies the lock file at `lock_path`, already resolved against the current
+/// directory, next to the executable.
+fn generate_lock_file(
+  sidecar_path: &Path,
+  lock_path: &Path,
+) -> Result<(), Error> {
+  let lock_copy_path = get_lock_file_path(sidecar_path);
+  debug!(
+    "Copying lock file {} to {}",
+    lock_path.to_string_lossy(),
+    lock_copy_path.to_string_lossy()
+  );
+  lock_path
+    .canonicalize()
+    .and_then(|lock_path| {
+      retry_transient(|| fs::copy(&lock_path, &lock_copy_path))
+    })
+    .map_err(|err| InstallError::LockCopy(lock_path.to_path_buf(), err))?;
+  Ok(())
+}
+
+/// Describes how a script was installed. Written next to the executable.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InstallMetadata {
+  pub name: String,
+  pub module_url: String,
+  pub args: Vec<String>,
+  /// The permission and runtime flags passed to `deno run`, without the
+  /// --config, --importmap and --lock pointing at the copies.
+  #[serde(default)]
+  pub flag_args: Vec<String>,
+  /// The arguments following the module URL.
+  #[serde(default)]
+  pub script_args: Vec<String>,
+  pub config_path: Option<String>,
+  pub lock_path: Option<String>,
+  pub import_map_path: Option<String>,
+  /// Seconds since the unix epoch.
+  pub installed_at: u64,
+  pub version: String,
+  /// Checksum of the generated executable.
+  pub checksum: String,
+  /// Additional names the script was installed under.
+  #[serde(default)]
+  pub aliases: Vec<String>,
+  /// Whether each executable passes its own name as the first argument, as
+  /// with --names.
+  #[serde(default)]
+  pub pass_name: bool,
+  /// The environment variables the executables set.
+  #[serde(default)]
+  pub env: Vec<(String, String)>,
+  /// Checksum of the remote module when it was installed, with --pin.
+  #[serde(default)]
+  pub module_checksum: Option<String>,
+  /// The directory the executables run the script from, with --cwd.
+  #[serde(default)]
+  pub run_dir: Option<PathBuf>,
+  /// The flags of `deno run` given with --deno-arg, before the module URL.
+  #[serde(default)]
+  pub deno_args: Vec<String>,
+  /// A local module being developed, installed with --dev.
+  #[serde(default)]
+  pub dev: bool,
+  /// The shell and subcommand a completion file was written for.
+  #[serde(default)]
+  pub completions: Option<(String, String)>,
+  /// Whether `config_path` is a copy, as opposed to a config used directly
+  /// with --no-copy-config.
+  #[serde(default)]
+  pub config_copied: bool,
+  /// The copies are under `$DENO_DIR/installs`, with --copies-in-deno-dir.
+  #[serde(default)]
+  pub copies_in_deno_dir: bool,
+  /// The comment given with --header for the executables.
+  #[serde(default)]
+  pub header: Option<String>,
+  /// The directory of the root the executables are in, with --bin-subdir.
+  #[serde(default)]
+  pub bin_subdir: Option<String>,
+  /// The kinds of executable given with --target, the platform's own if
+  /// empty.
+  #[serde(default)]
+  pub targets: Vec<String>,
+  /// The deno the executables run, with --deno-path.
+  #[serde(default)]
+  pub deno_path: Option<PathBuf>,
+  /// Permissions of the executables, with --mode.
+  #[serde(default)]
+  pub mode: Option<u32>,
+  /// The stub written as `<name>.exe`, with --trampoline.
+  #[serde(default)]
+  pub trampoline: Option<PathBuf>,
+  /// No config was picked up, with --no-config.
+  #[serde(default)]
+  pub no_config: bool,
+}
+
+impl InstallMetadata {
+  pub fn write(&self, sidecar_path: &Path) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(self)?;
+    retry_transient(|| fs::write(Self::filename(sidecar_path), &json))
+  }
+
+  pub fn read(sidecar_path: &Path) -> Result<InstallMetadata, Error> {
+    let metadata = fs::read_to_string(Self::filename(sidecar_path))?;
+    let metadata: InstallMetadata = serde_json::from_str(&metadata)?;
+    Ok(metadata)
+  }
+
+  /// Ex: $HOME/.deno/bin/file_server.metadata.json
+  pub fn filename(sidecar_path: &Path) -> PathBuf {
+    append_extension(sidecar_path, "metadata.json")
+  }
+}
+
+fn get_installer_root() -> Result<PathBuf, Error> {
+  if let Ok(env_dir) = env::var("DENO_INSTALL_ROOT") {
+    // Stray whitespace, e.g. from `export DENO_INSTALL_ROOT="$(...) "`.
+    let env_dir = env_dir.trim();
+    if !env_dir.is_empty() {
+      return expand_home_dir(env_dir)?.canonicalize();
+    }
+  }
+  if let Some(xdg_data_root) = get_xdg_data_root() {
+    return Ok(xdg_data_root);
+  }
+  let mut home_path = get_home_dir()?;
+  home_path.push(".deno");
+  Ok(home_path)
+}
+
+fn get_home_dir() -> Result<PathBuf, Error> {
+  // Note: on Windows, the $HOME environment variable may be set by users or by
+  // third party software, but it is non-standard and should not be relied upon.
+  let home_env_var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+  env::var_os(home_env_var).map(PathBuf::from).ok_or_else(|| {
+    Error::new(
+      ErrorKind::NotFound,
+      format!("${} is not defined", home_env_var),
+    )
+  })
+}
+
+/// Expands a leading `~` to the home directory, as a shell would have if the
+/// path had not been quoted. On Windows, so is a leading `%USERPROFILE%`, which
+/// is left as is when set from a program that doesn't expand variables.
+fn expand_home_dir(path: &str) -> Result<PathBuf, Error> {
+  if path == "~" || (cfg!(windows) && path == "%USERPROFILE%") {
+    return get_home_dir();
+  }
+  let rest = path.strip_prefix("~/").or_else(|| {
+    if cfg!(windows) {
+      path
+        .strip_prefix("~\\")
+        .or_else(|| path.strip_prefix("%USERPROFILE%\\"))
+        .or_else(|| path.strip_prefix("%USERPROFILE%/"))
+    } else {
+      None
+    }
+  });
+  match rest {
+    Some(rest) => Ok(get_home_dir()?.join(rest)),
+    None => Ok(PathBuf::from(path)),
+  }
+}
+
+/// Persisted installer preferences, in `$HOME/.deno/config`.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct InstallerSettings {
+  /// Shell whose syntax is used in PATH hints, instead of `$SHELL`'s.
+  default_shell: Option<String>,
+}
+
+impl InstallerSettings {
+  fn filename() -> Result<PathBuf, Error> {
+    Ok(get_home_dir()?.join(".deno").join("config"))
+  }
+
+  fn read() -> Result<InstallerSettings, Error> {
+    match fs::read_to_string(Self::filename()?) {
+      Ok(settings) => Ok(serde_json::from_str(&settings)?),
+      Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+      Err(err) => Err(err),
+    }
+  }
+
+  fn write(&self) -> Result<(), Error> {
+    let filename = Self::filename()?;
+    if let Some(parent) = filename.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::write(filename, serde_json::to_string_pretty(self)?)
+  }
+}
+
+/// Shells that PATH hints can be given for.
+const SHELLS: &[&str] = &[
+  "sh",
+  "bash",
+  "zsh",
+  "fish",
+  "csh",
+  "tcsh",
+  "powershell",
+  "cmd",
+];
+
+/// Shells that completion files can be written for, with the extension of the
+/// files.
+const COMPLETION_SHELLS: &[(&str, &str)] = &[
+  ("bash", "bash"),
+  ("zsh", "zsh"),
+  ("fish", "fish"),
+  ("powershell", "ps1"),
+];
+
+/// The completion file of the script `name` for `shell`, in the completions
+/// directory next to the bin directory `bin_dir`.
+fn get_completions_path(
+  bin_dir: &Path,
+  name: &str,
+  shell: &str,
+) -> Option<PathBuf> {
+  let (_, extension) = COMPLETION_SHELLS
+    .iter()
+    .find(|(known, _)| *known == shell)?;
+  let completions_dir = bin_dir.parent().unwrap_or(bin_dir).join("completions");
+  Some(append_extension(&completions_dir.join(name), extension))
+}
+
+/// A completion file that sources what the executable at `path` prints when
+/// run with `subcommand` and the name of `shell`.
+fn render_completions_file(
+  name: &str,
+  path: &Path,
+  shell: &str,
+  subcommand: &str,
+) -> String {
+  let command =
+    format!("{} {} {}", get_run_command(path, shell), subcommand, shell);
+  let source = match shell {
+    "fish" => format!("{} | source", command),
+    "powershell" => format!("{} | Out-String | Invoke-Expression", command),
+    _ => format!("source <({})", command),
+  };
+  format!(
+    "# {} completions, generated by deno install\n{}\n",
+    name, source
+  )
+}
+
+/// Checks that a script can be installed as `name` without --force: the name
+/// must be valid, not reserved, and not installed in the target directory.
+/// Other installation directories with the name are only warned about.
+pub fn check_name(
+  name: &str,
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  validate_name(name)?;
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  let planned_paths: Vec<PathBuf> =
+    [ExecutableKind::Sh, ExecutableKind::Cmd, ExecutableKind::Ps1]
+      .iter()
+      .map(|kind| get_executable_path_for(*kind, &dirs.bin, name))
+      .collect();
+  let availability =
+    verify_name_available(&dirs.bin, &[name.to_string()], &planned_paths);
+  if !availability.taken_in_target.is_empty() {
+    return Err(InstallError::ExistingInstallation.into());
+  }
+  for taken_path in &availability.taken_elsewhere {
+    writeln!(
+      out,
+      "⚠️  {} already exists, the one found first in PATH will be run",
+      taken_path.to_string_lossy()
+    )?;
+  }
+  writeln!(out, "✅ {} can be installed", name)?;
+  Ok(())
+}
+
+/// Pins the shell whose syntax is used in PATH hints.
+pub fn set_default_shell(
+  shell: &str,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  if !SHELLS.contains(&shell) {
+    return Err(Error::new(
+      ErrorKind::Other,
+      format!("Unsupported shell: {}", shell),
+    ));
+  }
+  let mut settings = InstallerSettings::read()?;
+  settings.default_shell = Some(shell.to_string());
+  settings.write()?;
+  writeln!(out, "✅ PATH hints will be given for {}", shell)?;
+  Ok(())
+}
+
+/// The shell PATH hints are given for: `shell` if given with --shell, else the
+/// pinned one, else `$SHELL`, else the platform's.
+fn get_hint_shell(shell: Option<&str>) -> String {
+  if let Some(shell) = shell {
+    return shell.to_string();
+  }
+  let pinned = InstallerSettings::read()
+    .ok()
+    .and_then(|settings| settings.default_shell);
+  if let Some(shell) = pinned {
+    return shell;
+  }
+  let from_env = env::var_os("SHELL").and_then(|shell| {
+    Path::new(&shell)
+      .file_name()
+      .and_then(|name| name.to_str())
+      .map(String::from)
+  });
+  match from_env {
+    Some(shell) if !shell.is_empty() => shell,
+    _ if cfg!(windows) => "cmd".to_string(),
+    _ => "sh".to_string(),
+  }
+}
+
+fn infer_name_from_url(url: &Url) -> Option<String> {
+  get_url_stem(url).filter(|stem| EXEC_NAME_RE.is_match(stem))
+}
+
+/// Directories that say how a module is laid out rather than what it is.
+const GENERIC_DIR_NAMES: &[&str] = &["src", "dist", "lib", "bin"];
+
+/// The percent-decoded name a URL refers to, which may not be a valid
+/// executable name.
+fn get_url_stem(url: &Url) -> Option<String> {
+  let path = percent_decode_str(url.path())
+    .decode_utf8_lossy()
+    .to_string();
+  let is_dir = path.ends_with('/');
+  let path = PathBuf::from(path);
+  // A directory like `/x/tool@1.2.3/` is named after itself.
+  if is_dir {
+    return path
+      .file_name()
+      .map(|dir_name| strip_version(&dir_name.to_string_lossy()).to_string());
+  }
+  let stem = match path.file_name() {
+    // The leading dot of a dotfile like `.env.ts` is not part of the name,
+    // and `.ts` is only an extension.
+    Some(file_name) if file_name.to_string_lossy().starts_with('.') => {
+      let file_name = file_name.to_string_lossy();
+      let file_name = file_name.trim_start_matches('.');
+      match file_name.rfind('.') {
+        Some(index) if index > 0 => file_name[..index].to_string(),
+        _ => return None,
+      }
+    }
+    Some(_) => path.file_stem().unwrap().to_string_lossy().to_string(),
+    None => return None,
+  };
+  if let Some(parent_path) = path.parent() {
+    if stem == "main" || stem == "mod" || stem == "index" || stem == "cli" {
+      if let Some(name) = get_registry_name(url, parent_path) {
+        return Some(name);
+      }
+      if let Some(parent_name) = parent_path.file_name() {
+        let parent_name = parent_name.to_string_lossy();
+        let parent_name = strip_version(&parent_name);
+        // `/tool/src/mod.ts` is named after `tool`, not `src`.
+        if GENERIC_DIR_NAMES.contains(&parent_name) {
+          if let Some(grandparent_name) =
+            parent_path.parent().and_then(|path| path.file_name())
+          {
+            return Some(
+              strip_version(&grandparent_name.to_string_lossy()).to_string(),
+            );
+          }
+        }
+        return Some(parent_name.to_string());
+      }
+    }
+  }
+  Some(stem)
+}
+
+/// The module name of a `deno.land/x/<name>@<version>/...` registry URL, so
+/// that entry files nested in the module are named after the module rather
+/// than their directory.
+fn get_registry_name(url: &Url, dir: &Path) -> Option<String> {
+  if url.host_str() != Some("deno.land") {
+    return None;
+  }
+  let mut components = dir.components().skip_while(|c| match c {
+    Component::Normal(segment) => *segment != "x",
+    _ => true,
+  });
+  components.next()?;
+  match components.next() {
+    Some(Component::Normal(segment)) => {
+      Some(strip_version(&segment.to_string_lossy()).to_string())
+    }
+    _ => None,
+  }
+}
+
+/// The config files of a local directory that can name the script.
+const PACKAGE_CONFIG_FILE_NAMES: &[&str] = &["deno.json", "deno.jsonc"];
+
+/// The `name` of the first of `PACKAGE_CONFIG_FILE_NAMES` found in the local
+/// directory `dir`, if it is a valid executable name.
+fn get_package_name(dir: &Path) -> Option<String> {
+  let config_path = PACKAGE_CONFIG_FILE_NAMES
+    .iter()
+    .map(|file_name| dir.join(file_name))
+    .find(|path| path.is_file())?;
+  let config = fs::read_to_string(&config_path).ok()?;
+  let name = parse_config_json(&config).ok()?["name"]
+    .as_str()?
+    .to_string();
+  if validate_name(&name).is_ok() {
+    Some(name)
+  } else {
+    debug!(
+      "Ignoring invalid name {} in {}",
+      name,
+      config_path.to_string_lossy()
+    );
+    None
+  }
+}
+
+/// Returns the entry file of the local directory `dir`, which is named after
+/// it as the first of `ENTRY_FILE_NAMES` found.
+fn get_entry_file(dir: &Path) -> Result<PathBuf, Error> {
+  ENTRY_FILE_NAMES
+    .iter()
+    .map(|file_name| dir.join(file_name))
+    .find(|path| path.is_file())
+    .ok_or_else(|| {
+      Error::new(
+        ErrorKind::NotFound,
+        format!(
+          "No entry file found in {}, looked for {}",
+          dir.to_string_lossy(),
+          ENTRY_FILE_NAMES.join(", ")
+        ),
+      )
+    })
+}
+
+/// Resolves the module given to deno install to the URL the executables run:
+/// a local directory to its entry file, a path to a file URL through
+/// symlinks. Also returns the name in the deno.json of a directory, if any.
+pub fn resolve_module_url(
+  module_url: &str,
+  cwd: &Path,
+) -> Result<(Url, Option<String>), InstallError> {
+  let mut package_name = None;
+  // Check if module_url is remote
+  let module_url = if is_remote_url(module_url) {
+    parse_remote_url(module_url)?
+  } else if has_scheme(module_url, &["data"]) {
+    // The file fetcher only loads http, https and file URLs.
+    return Err(
+      Error::new(
+        ErrorKind::InvalidInput,
+        "data: URLs can't be installed, save the module to a file and install that instead",
+      )
+      .into(),
+    );
+  } else if has_scheme(module_url, &["file"]) {
+    // Kept as given, unlike a path, which would be joined to the current
+    // directory as is.
+    let url = Url::parse(module_url).map_err(|err| {
+      Error::new(
+        ErrorKind::InvalidInput,
+        format!("Invalid file URL {}: {}", module_url, err),
+      )
+    })?;
+    let module_path = url.to_file_path().map_err(|_| {
+      Error::new(
+        ErrorKind::InvalidInput,
+        format!("File URL {} is not a local path", module_url),
+      )
+    })?;
+    if module_path.is_dir() {
+      package_name = get_package_name(&module_path);
+      Url::from_file_path(get_entry_file(&module_path)?)
+        .expect("Path should be absolute")
+    } else if module_path.exists() {
+      url
+    } else {
+      return Err(InstallError::ModuleNotFound(module_path));
+    }
+  } else {
+    let module_path = PathBuf::from(module_url);
+    let module_path = if module_path.is_absolute() {
+      module_path
+    } else {
+      cwd.join(module_path)
+    };
+    // Through symlinks, so the script runs the module they point to now.
+    let module_path = module_path.canonicalize().map_err(|err| {
+      if err.kind() == ErrorKind::NotFound {
+        InstallError::ModuleNotFound(module_path.clone()).into()
+      } else {
+        err
+      }
+    })?;
+    let module_path = if module_path.is_dir() {
+      package_name = get_package_name(&module_path);
+      get_entry_file(&module_path)?
+    } else {
+      module_path
+    };
+    Url::from_file_path(module_path).expect("Path should be absolute")
+  };
+  Ok((module_url, package_name))
+}
+
+/// The part of a name taken from a subcommand like `serve`, with the
+/// characters not allowed in executable names replaced by dashes.
+fn sanitize_subcommand(subcommand: &str) -> String {
+  subcommand
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+        c
+      } else {
+        '-'
+      }
+    })
+    .collect::<String>()
+    .trim_matches('-')
+    .to_string()
+}
+
+/// Runs the file operation `op`, retrying it with a backoff when denied on
+/// Windows, where antivirus or indexing can briefly hold a file just written.
+fn retry_transient<T>(
+  op: impl FnMut() -> Result<T, Error>,
+) -> Result<T, Error> {
+  let attempts = if cfg!(windows) {
+    TRANSIENT_RETRY_ATTEMPTS
+  } else {
+    1
+  };
+  retry_transient_with(attempts, op)
+}
+
+fn retry_transient_with<T>(
+  attempts: u32,
+  mut op: impl FnMut() -> Result<T, Error>,
+) -> Result<T, Error> {
+  let mut delay = Duration::from_millis(TRANSIENT_RETRY_DELAY_MS);
+  let mut attempt = 1;
+  loop {
+    match op() {
+      Err(err)
+        if err.kind() == ErrorKind::PermissionDenied && attempt < attempts =>
+      {
+        debug!("Retrying in {:?} after: {}", delay, err);
+        thread::sleep(delay);
+        delay *= 2;
+        attempt += 1;
+      }
+      result => return result,
+    }
+  }
+}
+
+/// Strips the version of a path segment like `tool@1.2.3`.
+fn strip_version(segment: &str) -> &str {
+  match segment.rfind('@') {
+    Some(index) if index > 0 => &segment[..index],
+    _ => segment,
+  }
+}
+
+/// Why an executable name could not be inferred from a module URL.
+#[derive(Debug, PartialEq)]
+pub enum NameInferenceError {
+  /// The URL has neither a path nor a host, eg. `file:///`.
+  EmptyPath,
+  /// The URL is only a host, eg. `https://example.com`.
+  OnlyHost,
+  /// The file name is only an extension, eg. `.ts`.
+  ExtensionOnly(String),
+  /// The name taken from the URL is not a valid executable name.
+  UnsanitizableStem(String),
+}
+
+impl fmt::Display for NameInferenceError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      NameInferenceError::EmptyPath => {
+        write!(f, "the URL has no path to take a name from")
+      }
+      NameInferenceError::OnlyHost => {
+        write!(f, "the URL has no path, only a host")
+      }
+      NameInferenceError::ExtensionOnly(file_name) => {
+        write!(f, "\"{}\" has no name before its extension", file_name)
+      }
+      NameInferenceError::UnsanitizableStem(stem) => {
+        write!(f, "\"{}\" is not a valid executable name", stem)
+      }
+    }
+  }
+}
+
+/// Like `infer_name_from_url`, but also checks the inferred name is valid and
+/// tells why no name could be inferred.
+fn infer_name(url: &Url) -> Result<String, NameInferenceError> {
+  match get_url_stem(url) {
+    Some(name) if validate_name(&name).is_ok() => Ok(name),
+    Some(name) => Err(NameInferenceError::UnsanitizableStem(name)),
+    None if url.path().len() > 1 && !url.path().ends_with('/') => {
+      let path = PathBuf::from(url.path());
+      let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+      Err(NameInferenceError::ExtensionOnly(file_name))
+    }
+    None if url.host_str().map_or(false, |host| !host.is_empty()) => {
+      Err(NameInferenceError::OnlyHost)
+    }
+    None => Err(NameInferenceError::EmptyPath),
+  }
+}
+
+/// Collects the arguments of `// deno-install: ...` comments at the top of a
+/// module, eg. `// deno-install: --allow-net --allow-read`.
+fn parse_module_hints(source: &str) -> Vec<String> {
+  let mut hints = vec![];
+  for (i, line) in source.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || (i == 0 && line.starts_with("#!")) {
+      continue;
+    }
+    if !line.starts_with("//") {
+      break;
+    }
+    let comment = line.trim_start_matches('/').trim();
+    if let Some(hint) = comment.strip_prefix("deno-install:") {
+      hints.extend(hint.split_whitespace().map(String::from));
+    }
+  }
+  hints
+}
+
+/// Merges the permissions requested by module hints into `flags`. A
+/// permission that was already passed on the command line, either broadly or
+/// with an allowlist, is left untouched. Returns the hints that were ignored.
+fn apply_module_hints(flags: &mut Flags, hints: &[String]) -> Vec<String> {
+  let user_flags = flags.clone();
+  let read_set = user_flags.allow_read || !user_flags.read_allowlist.is_empty();
+  let write_set =
+    user_flags.allow_write || !user_flags.write_allowlist.is_empty();
+  let net_set = user_flags.allow_net || !user_flags.net_allowlist.is_empty();
+
+  let mut ignored = vec![];
+  for hint in hints {
+    let mut parts = hint.splitn(2, '=');
+    let flag = parts.next().unwrap();
+    let values: Vec<String> = parts
+      .next()
+      .map(|v| v.split(',').map(String::from).collect())
+      .unwrap_or_default();
+    match flag {
+      "--allow-read" if !read_set => {
+        if values.is_empty() {
+          flags.allow_read = true;
+        } else {
+          flags.read_allowlist = values.iter().map(PathBuf::from).collect();
+        }
+      }
+      "--allow-write" if !write_set => {
+        if values.is_empty() {
+          flags.allow_write = true;
+        } else {
+          flags.write_allowlist = values.iter().map(PathBuf::from).collect();
+        }
+      }
+      "--allow-net" if !net_set => {
+        if values.is_empty() {
+          flags.allow_net = true;
+        } else {
+          match crate::flags_allow_net::parse(values) {
+            Ok(net_allowlist) => flags.net_allowlist = net_allowlist,
+            Err(_) => ignored.push(hint.to_string()),
+          }
+        }
+      }
+      "--allow-read" | "--allow-write" | "--allow-net" => {}
+      "--allow-env" => flags.allow_env = true,
+      "--allow-run" => flags.allow_run = true,
+      "--allow-plugin" => flags.allow_plugin = true,
+      "--allow-hrtime" => flags.allow_hrtime = true,
+      "-A" | "--allow-all" => {
+        flags.allow_read = !read_set || user_flags.allow_read;
+        flags.allow_write = !write_set || user_flags.allow_write;
+        flags.allow_net = !net_set || user_flags.allow_net;
+        flags.allow_env = true;
+        flags.allow_run = true;
+        flags.allow_plugin = true;
+        flags.allow_hrtime = true;
+      }
+      _ => ignored.push(hint.to_string()),
+    }
+  }
+  ignored
+}
+
+const PROJECT_CONFIG_FILE_NAME: &str = ".deno-install.json";
+
+/// Installation defaults shared by a project, read from a
+/// `.deno-install.json` in the current directory. Command line flags win.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProjectConfig {
+  /// Permission flags, eg. `["--allow-net", "--allow-read=./data"]`.
+  permissions: Vec<String>,
+  /// Installation root, relative to the config file.
+  root: Option<PathBuf>,
+  /// Maximum length of inferred executable names.
+  max_name_length: Option<usize>,
+}
+
+fn apply_project_config(
+  dir: &Path,
+  flags: &mut Flags,
+  install_flags: &mut InstallFlags,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  let config_path = dir.join(PROJECT_CONFIG_FILE_NAME);
+  let config = match fs::read_to_string(&config_path) {
+    Ok(config) => config,
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+    Err(err) => return Err(err),
+  };
+  let config: ProjectConfig = serde_json::from_str(&config).map_err(|err| {
+    Error::new(
+      ErrorKind::Other,
+      format!("Invalid {}: {}", config_path.to_string_lossy(), err),
+    )
+  })?;
+
+  for permission in apply_module_hints(flags, &config.permissions) {
+    writeln!(
+      out,
+      "⚠️  Ignoring unsupported permission in {}: {}",
+      PROJECT_CONFIG_FILE_NAME, permission
+    )?;
+  }
+  if install_flags.root.is_none() {
+    install_flags.root = config.root.map(|root| dir.join(root));
+  }
+  if install_flags.max_name_length.is_none() {
+    install_flags.max_name_length = config.max_name_length;
+  }
+  Ok(())
+}
+
+/// Without --config or --no-config, the config file of DENO_INSTALL_CONFIG is
+/// used, copied like one given with --config.
+fn apply_config_env(flags: &mut Flags) -> Result<(), Error> {
+  if flags.config_path.is_some() || flags.no_config {
+    return Ok(());
+  }
+  if let Ok(config_path) = env::var("DENO_INSTALL_CONFIG") {
+    let config_path = config_path.trim();
+    if !config_path.is_empty() {
+      let config_path = expand_home_dir(config_path)?;
+      flags.config_path = Some(config_path.to_string_lossy().to_string());
+    }
+  }
+  Ok(())
+}
+
+async fn fetch_remote_module_async(
+  url: Url,
+  ca_file: Option<String>,
+) -> Result<Vec<u8>, ErrBox> {
+  let client = create_http_client(ca_file.as_deref())?;
+  let mut url = url;
+  // Same redirect limit as the file fetcher.
+  for _ in 0..10 {
+    match fetch_once(client.clone(), &url, None).await? {
+      FetchOnceResult::Code(body, _) => return Ok(body),
+      FetchOnceResult::Redirect(redirect_url, _) => url = redirect_url,
+      FetchOnceResult::NotModified => unreachable!(),
+    }
+  }
+  Err(ErrBox::error(format!(
+    "Too many redirects fetching {}",
+    url
+  )))
+}
+
+/// Fetches the source of a remote module. `install` is called from within the
+/// CLI's runtime, so the request is driven by a runtime on its own thread.
+fn fetch_remote_module(
+  url: &Url,
+  ca_file: Option<String>,
+) -> Result<Vec<u8>, Error> {
+  let url = url.clone();
+  thread::spawn(move || {
+    tokio_util::run_basic(fetch_remote_module_async(url, ca_file))
+  })
+  .join()
+  .unwrap()
+  .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))
+}
+
+fn read_module_source(
+  module_url: &Url,
+  ca_file: Option<String>,
+) -> Result<String, Error> {
+  if module_url.scheme() == "file" {
+    let module_path = module_url.to_file_path().unwrap();
+    fs::read_to_string(module_path)
+  } else {
+    let source = fetch_remote_module(module_url, ca_file)?;
+    Ok(String::from_utf8_lossy(&source).to_string())
+  }
+}
+
+/// On unix, `$XDG_BIN_HOME` is used for executables when no installation
+/// root was given through `--root` or `DENO_INSTALL_ROOT`.
+fn get_xdg_bin_home() -> Option<PathBuf> {
+  if cfg!(windows) {
+    return None;
+  }
+  if let Ok(env_dir) = env::var("DENO_INSTALL_ROOT") {
+    if !env_dir.is_empty() {
+      return None;
+    }
+  }
+  env::var_os("XDG_BIN_HOME")
+    .filter(|dir| !dir.is_empty())
+    .map(PathBuf::from)
+}
+
+/// Except on Windows and macOS, which have conventions of their own,
+/// `$XDG_DATA_HOME/deno` is the installation root when it is set.
+fn get_xdg_data_root() -> Option<PathBuf> {
+  if cfg!(windows) || cfg!(target_os = "macos") {
+    return None;
+  }
+  env::var_os("XDG_DATA_HOME")
+    .filter(|dir| !dir.is_empty())
+    .map(|dir| PathBuf::from(dir).join("deno"))
+}
+
+struct InstallDirs {
+  /// Where the executables are written.
+  bin: PathBuf,
+  /// Where the files accompanying an executable (config copy, metadata) are
+  /// written. Same as `bin`, unless `bin` is shared with other software.
+  sidecar: PathBuf,
+}
+
+/// With `bin_subdir`, the executables are written to that directory of the
+/// root instead of `bin`. It doesn't apply to XDG_BIN_HOME, which already is
+/// a bin directory.
+fn get_install_dirs(
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+) -> Result<InstallDirs, Error> {
+  if let Some(bin_subdir) = bin_subdir {
+    validate_bin_subdir(bin_subdir)?;
+  }
+  let root = if let Some(root) = root {
+    root.canonicalize()?
+  } else if let Some(xdg_bin_home) = get_xdg_bin_home() {
+    return Ok(InstallDirs {
+      sidecar: xdg_bin_home.join(".deno"),
+      bin: xdg_bin_home,
+    });
+  } else {
+    get_installer_root()?
+  };
+  // Otherwise creating the bin directory in it fails with a less clear error.
+  if root.exists() && !root.is_dir() {
+    return Err(InstallError::RootNotADirectory(root).into());
+  }
+  let bin = get_bin_dir(&root, bin_subdir);
+  Ok(InstallDirs {
+    sidecar: bin.clone(),
+    bin,
+  })
+}
+
+fn get_executable_path(installation_dir: &Path, name: &str) -> PathBuf {
+  get_executable_path_for(ExecutableKind::host(), installation_dir, name)
+}
+
+fn get_executable_path_for(
+  kind: ExecutableKind,
+  installation_dir: &Path,
+  name: &str,
+) -> PathBuf {
+  let file_path = installation_dir.join(name);
+  match kind.extension() {
+    Some(extension) => append_extension(&file_path, extension),
+    None => file_path,
+  }
+}
+
+/// The module URL the executable at `file_path` was installed from, taken
+/// from its metadata or, for executables installed without any, from its
+/// arguments.
+fn get_installed_module_url(
+  file_path: &Path,
+  sidecar_path: &Path,
+) -> Option<String> {
+  if let Ok(metadata) = InstallMetadata::read(sidecar_path) {
+    return Some(metadata.module_url);
+  }
+  let content = fs::read_to_string(file_path).ok()?;
+  parse_quoted_args(&content)
+    .into_iter()
+    .find(|arg| has_scheme(arg, &["http", "https", "file", "data"]))
+}
+
+/// The double quoted arguments of a sh or cmd executable, unescaped.
+fn parse_quoted_args(content: &str) -> Vec<String> {
+  let mut args = vec![];
+  let mut chars = content.chars().peekable();
+  while let Some(c) = chars.next() {
+    if c != '"' {
+      continue;
+    }
+    let mut arg = String::new();
+    while let Some(c) = chars.next() {
+      match c {
+        '\\'
+          if matches!(
+            chars.peek(),
+            Some('"') | Some('$') | Some('`') | Some('\\')
+          ) =>
+        {
+          arg.push(chars.next().unwrap())
+        }
+        '"' if chars.peek() == Some(&'"') => {
+          chars.next();
+          arg.push('"');
+        }
+        '"' => break,
+        c => arg.push(c),
+      }
+    }
+    args.push(arg);
+  }
+  args
+}
+
+/// The single quoted arguments of a PowerShell executable, unescaped.
+fn parse_single_quoted_args(content: &str) -> Vec<String> {
+  let mut args = vec![];
+  let mut chars = content.chars().peekable();
+  while let Some(c) = chars.next() {
+    if c != '\'' {
+      continue;
+    }
+    let mut arg = String::new();
+    while let Some(c) = chars.next() {
+      match c {
+        '\'' if chars.peek() == Some(&'\'') => {
+          chars.next();
+          arg.push('\'');
+        }
+        '\'' => break,
+        c => arg.push(c),
+      }
+    }
+    args.push(arg);
+  }
+  args
+}
+
+/// What an executable written by `generate_executable_file` runs.
+#[derive(Debug, PartialEq)]
+pub struct ShimInfo {
+  pub module_url: String,
+  /// The flags passed to `deno run` before the module URL.
+  pub flags: Vec<String>,
+  /// The arguments following the module URL.
+  pub args: Vec<String>,
+}
+
+/// Reads back what the executable at `path` runs, in any of the formats of
+/// `ExecutableKind`, e.g. for executables installed without metadata.
+pub fn inspect_shim(path: &PathBuf) -> Result<ShimInfo, Error> {
+  let invalid_shim = || {
+    Error::new(
+      ErrorKind::InvalidData,
+      format!(
+        "{} is not an executable generated by deno install",
+        path.to_string_lossy()
+      ),
+    )
+  };
+  let kind = get_generated_executable_kind(path).ok_or_else(invalid_shim)?;
+  let content = fs::read_to_string(path)?;
+  // The line running deno, after the comments and the variables it sets.
+  let command = content
+    .lines()
+    .find(|line| match kind {
+      ExecutableKind::Sh => {
+        !line.starts_with('#')
+          && !line.starts_with("export ")
+          && !line.starts_with("cd ")
+      }
+      ExecutableKind::Cmd => {
+        line.starts_with('@')
+          && !line.starts_with("@rem ")
+          && !line.starts_with("@cd ")
+          && !line.starts_with("@setlocal")
+          && !line.starts_with("@set ")
+          && !line.starts_with("@exit ")
+      }
+      ExecutableKind::Ps1 => {
+        !line.starts_with('#')
+          && !line.starts_with("$env:")
+          && !line.starts_with("Push-Location ")
+      }
+    })
+    .ok_or_else(invalid_shim)?;
+  let (mut args, quoted_deno) = match kind {
+    ExecutableKind::Sh => {
+      (parse_quoted_args(command), command.starts_with('"'))
+    }
+    ExecutableKind::Cmd => (
+      parse_quoted_args(command)
+        .into_iter()
+        .map(|arg| arg.replace("%%", "%"))
+        .collect(),
+      command.starts_with("@\""),
+    ),
+    ExecutableKind::Ps1 => {
+      (parse_single_quoted_args(command), command.starts_with('&'))
+    }
+  };
+  // Apart from the arguments the executable is run with.
+  if kind == ExecutableKind::Sh && args.last().map(String::as_str) == Some("$@")
+  {
+    args.pop();
+  }
+  // The path of deno is only quoted when given with --deno-path.
+  if quoted_deno && !args.is_empty() {
+    args.remove(0);
+  }
+  if args.first().map(String::as_str) != Some("run") {
+    return Err(invalid_shim());
+  }
+  let url_index = args
+    .iter()
+    .position(|arg| {
+      !arg.starts_with('-')
+        && has_scheme(arg, &["http", "https", "file", "data"])
+    })
+    .ok_or_else(invalid_shim)?;
+  let script_args = args.split_off(url_index + 1);
+  let module_url = args.pop().unwrap();
+  Ok(ShimInfo {
+    module_url,
+    flags: args.split_off(1),
+    args: script_args,
+  })
+}
+
+/// Where the names an installation would take are already in use.
+struct NameAvailability {
+  /// Files in the target bin directory, which are only replaced with --force.
+  taken_in_target: Vec<PathBuf>,
+  /// Executables in the other installation directories, only warned about.
+  taken_elsewhere: Vec<PathBuf>,
+  /// Files of `taken_in_target` named like a planned executable but for the
+  /// case, which are the same executable on case-insensitive file systems.
+  case_variants: Vec<PathBuf>,
+}
+
+/// The bin directories `deno install` writes to, depending on the options and
+/// environment, for finding names that are shadowed.
+fn get_known_installation_dirs() -> Vec<PathBuf> {
+  let mut dirs = vec![];
+  if let Ok(env_dir) = env::var("DENO_INSTALL_ROOT") {
+    if !env_dir.is_empty() {
+      if let Ok(env_dir) = expand_home_dir(&env_dir) {
+        dirs.push(env_dir.join("bin"));
+      }
+    }
+  }
+  if !cfg!(windows) {
+    if let Some(xdg_bin_home) = env::var_os("XDG_BIN_HOME") {
+      if !xdg_bin_home.is_empty() {
+        dirs.push(PathBuf::from(xdg_bin_home));
+      }
+    }
+  }
+  if let Some(xdg_data_root) = get_xdg_data_root() {
+    dirs.push(xdg_data_root.join("bin"));
+  }
+  if let Ok(home_dir) = get_home_dir() {
+    dirs.push(home_dir.join(".deno").join("bin"));
+  }
+  dirs
+}
+
+fn verify_name_available(
+  installation_dir: &Path,
+  names: &[String],
+  planned_paths: &[PathBuf],
+) -> NameAvailability {
+  let mut taken_in_target: Vec<PathBuf> = planned_paths
+    .iter()
+    .filter(|path| path.exists())
+    .cloned()
+    .collect();
+  // On Windows, any file with an extension of PATHEXT runs as `name`.
+  if cfg!(windows) {
+    let path_ext = env::var("PATHEXT").unwrap_or_default();
+    for name in names {
+      for ext in path_ext.split(';').filter(|ext| !ext.is_empty()) {
+        let path = append_extension(
+          &installation_dir.join(name),
+          ext.trim_start_matches('.'),
+        );
+        let is_taken = path.exists()
+          && !taken_in_target.iter().any(|taken| {
+            taken
+              .to_string_lossy()
+              .eq_ignore_ascii_case(&path.to_string_lossy())
+          });
+        if is_taken {
+          taken_in_target.push(path);
+        }
+      }
+    }
+  }
+  // Like on macOS and Windows, where `Tool` and `tool` are one file.
+  let mut case_variants = vec![];
+  for planned_path in planned_paths {
+    let (dir, file_name) =
+      match (planned_path.parent(), planned_path.file_name()) {
+        (Some(dir), Some(file_name)) => (dir, file_name.to_string_lossy()),
+        _ => continue,
+      };
+    let entries = match fs::read_dir(dir) {
+      Ok(entries) => entries,
+      Err(_) => continue,
+    };
+    for entry in entries.flatten() {
+      let entry_name = entry.file_name();
+      let entry_name = entry_name.to_string_lossy();
+      if entry_name == file_name || !entry_name.eq_ignore_ascii_case(&file_name)
+      {
+        continue;
+      }
+      let path = entry.path();
+      if !is_same_file(&path, planned_path) {
+        case_variants.push(path);
+      }
+    }
+  }
+  taken_in_target.extend(case_variants.iter().cloned());
+
+  let canonical_target = installation_dir
+    .canonicalize()
+    .unwrap_or_else(|_| installation_dir.to_path_buf());
+  let mut taken_elsewhere = vec![];
+  let mut seen_dirs = vec![canonical_target];
+  for dir in get_known_installation_dirs() {
+    let canonical_dir = dir.canonicalize().unwrap_or(dir);
+    if seen_dirs.contains(&canonical_dir) {
+      continue;
+    }
+    for name in names {
+      for kind in
+        &[ExecutableKind::Sh, ExecutableKind::Cmd, ExecutableKind::Ps1]
+      {
+        let path = get_executable_path_for(*kind, &canonical_dir, name);
+        if path.is_file() {
+          taken_elsewhere.push(path);
+        }
+      }
+    }
+    seen_dirs.push(canonical_dir);
+  }
+
+  NameAvailability {
+    taken_in_target,
+    taken_elsewhere,
+    case_variants,
+  }
+}
+
+/// Whether any kind of executable is installed for `name`, a trampoline
+/// included.
+fn is_installed(installation_dir: &Path, name: &str) -> bool {
+  [ExecutableKind::Sh, ExecutableKind::Cmd, ExecutableKind::Ps1]
+    .iter()
+    .any(|kind| get_executable_path_for(*kind, installation_dir, name).exists())
+    || is_trampoline(&get_trampoline_path(installation_dir, name))
+}
+
+fn get_trampoline_path(installation_dir: &Path, name: &str) -> PathBuf {
+  get_executable_path_for(ExecutableKind::Cmd, installation_dir, name)
+    .with_extension("exe")
+}
+
+/// Returns the path of the executable `name` is installed as in the bin
+/// directory of `root`, or of the default root, for the host platform.
+pub fn shim_path(
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+  name: &str,
+) -> Result<PathBuf, Error> {
+  validate_name(name)?;
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  Ok(get_executable_path(&dirs.bin, name))
+}
+
+/// The install metadata, as read back to reinstall a script with the options
+/// it was originally installed with.
+pub type InstallManifest = InstallMetadata;
+
+/// Reads the manifest written when `name` was installed.
+pub fn load_manifest(
+  name: &str,
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+) -> Result<InstallManifest, Error> {
+  validate_name(name)?;
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  match InstallMetadata::read(&dirs.sidecar.join(name)) {
+    Err(err) if err.kind() == ErrorKind::NotFound => Err(Error::new(
+      ErrorKind::NotFound,
+      format!("No install manifest found for {}", name),
+    )),
+    result => result,
+  }
+}
+
+/// Prints the metadata recorded when `name` was installed.
+pub fn info(
+  name: &str,
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+  json: bool,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  let file_path = shim_path(root.clone(), bin_subdir, name)?;
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  let metadata = match InstallMetadata::read(&dirs.sidecar.join(name)) {
+    Ok(metadata) => metadata,
+    Err(err) if err.kind() == ErrorKind::NotFound => {
+      return Err(Error::new(
+        ErrorKind::NotFound,
+        format!("No installation metadata found for {}", name),
+      ))
+    }
+    Err(err) => return Err(err),
+  };
+
+  if json {
+    writeln!(out, "{}", serde_json::to_string_pretty(&metadata)?)?;
+    return Ok(());
+  }
+
+  let none = "(none)".to_string();
+  writeln!(out, "name: {}", metadata.name)?;
+  writeln!(out, "path: {}", file_path.to_string_lossy())?;
+  writeln!(out, "module: {}", metadata.module_url)?;
+  writeln!(out, "args: {}", metadata.args.join(" "))?;
+  writeln!(
+    out,
+    "config: {}",
+    metadata.config_path.as_ref().unwrap_or(&none)
+  )?;
+  writeln!(
+    out,
+    "lock: {}",
+    metadata.lock_path.as_ref().unwrap_or(&none)
+  )?;
+  writeln!(
+    out,
+    "import map: {}",
+    metadata.import_map_path.as_ref().unwrap_or(&none)
+  )?;
+  writeln!(out, "installed at: {}", metadata.installed_at)?;
+  writeln!(out, "deno version: {}", metadata.version)?;
+  writeln!(out, "checksum: {}", metadata.checksum)?;
+  writeln!(
+    out,
+    "module checksum: {}",
+    metadata.module_checksum.as_ref().unwrap_or(&none)
+  )?;
+  Ok(())
+}
+
+/// The kind of `file_path` if it is an executable written by
+/// `generate_executable_file`, as opposed to anything else living in the same
+/// bin directory.
+fn get_generated_executable_kind(file_path: &Path) -> Option<ExecutableKind> {
+  let mut header = String::new();
+  let file = File::open(file_path).ok()?;
+  file.take(64).read_to_string(&mut header).ok()?;
+  // Executables from before sh was looked up on PATH start with `#!/bin/sh`.
+  if header.starts_with("#!/usr/bin/env sh\n# generated by deno install\n")
+    || header.starts_with("#!/bin/sh\n# generated by deno install\n")
+  {
+    Some(ExecutableKind::Sh)
+  } else if header.starts_with("% generated by deno install %") {
+    Some(ExecutableKind::Cmd)
+  } else if header
+    .starts_with("#!/usr/bin/env pwsh\n# generated by deno install\n")
+  {
+    Some(ExecutableKind::Ps1)
+  } else {
+    None
+  }
+}
+
+/// Names of the scripts installed in `installation_dir`, sorted.
+fn get_installed_names(installation_dir: &Path) -> Result<Vec<String>, Error> {
+  let mut names = vec![];
+  for entry in fs::read_dir(installation_dir)? {
+    let file_path = entry?.path();
+    let is_backup = file_path.extension().map_or(false, |ext| ext == "bak");
+    if !file_path.is_file() || is_backup {
+      continue;
+    }
+    let name = match get_generated_executable_kind(&file_path) {
+      Some(ExecutableKind::Sh) => file_path.file_name(),
+      Some(_) => file_path.file_stem(),
+      None
+        if file_path.extension().map_or(false, |ext| ext == "exe")
+          && is_trampoline(&file_path) =>
+      {
+        file_path.file_stem()
+      }
+      None => continue,
+    };
+    if let Some(name) = name.and_then(|name| name.to_str()) {
+      names.push(name.to_string());
+    }
+  }
+  names.sort();
+  names.dedup();
+  Ok(names)
+}
+
+/// The copies of the config, import map and lock file the metadata of the
+/// scripts installed in `dirs` refer to, except those of `except`.
+fn get_referenced_copies(
+  dirs: &InstallDirs,
+  except: Option<&str>,
+) -> Result<Vec<PathBuf>, Error> {
+  let mut copies = vec![];
+  if !dirs.bin.is_dir() {
+    return Ok(copies);
+  }
+  for name in get_installed_names(&dirs.bin)? {
+    if Some(name.as_str()) == except {
+      continue;
+    }
+    let sidecar_path = dirs.sidecar.join(&name);
+    let metadata = match InstallMetadata::read(&sidecar_path) {
+      Ok(metadata) => metadata,
+      Err(_) => continue,
+    };
+    if let Some(config_path) = metadata.config_path.map(PathBuf::from) {
+      if is_config_copy(metadata.config_copied, &config_path, &sidecar_path) {
+        copies.push(config_path);
+      }
+    }
+    copies.extend(metadata.import_map_path.map(PathBuf::from));
+    copies.extend(metadata.lock_path.map(PathBuf::from));
+  }
+  Ok(copies)
+}
+
+/// Sidecar files (config and import map copies, metadata), and copies in
+/// `$DENO_DIR/installs`, no installed script uses anymore.
+fn get_orphaned_files(dirs: &InstallDirs) -> Result<Vec<PathBuf>, Error> {
+  let mut orphans = vec![];
+  let referenced = get_referenced_copies(dirs, None)?;
+  if let Ok(copies_dir) = get_deno_dir_copies_dir(&dirs.sidecar) {
+    let mut dirs_to_read = vec![copies_dir];
+    while let Some(dir) = dirs_to_read.pop() {
+      if !dir.is_dir() {
+        continue;
+      }
+      for entry in fs::read_dir(&dir)? {
+        let file_path = entry?.path();
+        if file_path.is_dir() {
+          dirs_to_read.push(file_path);
+        } else if !referenced.contains(&file_path) {
+          orphans.push(file_path);
+        }
+      }
+    }
+  }
+  if !dirs.sidecar.is_dir() {
+    orphans.sort();
+    return Ok(orphans);
+  }
+  for entry in fs::read_dir(&dirs.sidecar)? {
+    let file_path = entry?.path();
+    let file_name = match file_path.file_name().and_then(|name| name.to_str()) {
+      Some(file_name) => file_name.to_string(),
+      None => continue,
+    };
+    let name = [
+      ".metadata.json",
+      ".tsconfig.json",
+      ".import_map.json",
+      ".lock.json",
+    ]
+    .iter()
+    .find(|ext| file_name.ends_with(*ext))
+    .map(|ext| &file_name[..file_name.len() - ext.len()]);
+    if let Some(name) = name {
+      if !is_installed(&dirs.bin, name) && !referenced.contains(&file_path) {
+        orphans.push(file_path);
+      }
+    }
+  }
+  orphans.sort();
+  Ok(orphans)
+}
+
+/// Other executables on PATH with the same name as an installed script.
+fn get_conflicting_paths(installation_dir: &Path, name: &str) -> Vec<PathBuf> {
+  let mut conflicts = vec![];
+  if let Some(paths) = env::var_os("PATH") {
+    for dir in env::split_paths(&paths) {
+      if dir == installation_dir {
+        continue;
+      }
+      let file_path = get_executable_path(&dir, name);
+      if file_path.is_file() && !conflicts.contains(&file_path) {
+        conflicts.push(file_path);
+      }
+    }
+  }
+  conflicts
+}
+
+/// Executables named `name` in the PATH entries before `installation_dir`,
+/// which run instead of the script installed there. All of them if
+/// `installation_dir` isn't in PATH.
+fn get_shadowing_paths(installation_dir: &Path, name: &str) -> Vec<PathBuf> {
+  let mut shadowing_paths = vec![];
+  let paths = match env::var_os("PATH") {
+    Some(paths) => paths,
+    None => return shadowing_paths,
+  };
+  // On Windows, any file with an extension of PATHEXT runs as `name`.
+  let extensions: Vec<String> = if cfg!(windows) {
+    env::var("PATHEXT")
+      .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+      .split(';')
+      .filter(|ext| !ext.is_empty())
+      .map(|ext| ext.trim_start_matches('.').to_string())
+      .collect()
+  } else {
+    vec![]
+  };
+  for dir in env::split_paths(&paths) {
+    if dir == installation_dir || is_same_file(&dir, installation_dir) {
+      break;
+    }
+    let mut file_paths = vec![];
+    if extensions.is_empty() {
+      file_paths.push(dir.join(name));
+    }
+    for ext in &extensions {
+      file_paths.push(append_extension(&dir.join(name), ext));
+    }
+    for file_path in file_paths {
+      if file_path.is_file() && !shadowing_paths.contains(&file_path) {
+        shadowing_paths.push(file_path);
+      }
+    }
+  }
+  shadowing_paths
+}
+
+/// Names of the scripts installed in the bin directory of `root`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct InstalledScript {
+  pub name: String,
+  /// Installed with --dev, according to its metadata.
+  pub dev: bool,
+}
+
+pub fn list_installed(
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+) -> Result<Vec<InstalledScript>, Error> {
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  if !dirs.bin.is_dir() {
+    return Ok(vec![]);
+  }
+  let names = get_installed_names(&dirs.bin)?;
+  Ok(
+    names
+      .into_iter()
+      .filter(|name| EXEC_NAME_RE.is_match(name))
+      .map(|name| {
+        let dev = InstallMetadata::read(&dirs.sidecar.join(&name))
+          .map_or(false, |metadata| metadata.dev);
+        InstalledScript { name, dev }
+      })
+      .collect(),
+  )
+}
+
+/// Prints the names of the installed scripts, one per line with development
+/// installs marked, or as a JSON array.
+pub fn list(
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+  json: bool,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  let scripts = list_installed(root, bin_subdir)?;
+  if json {
+    let names: Vec<&str> =
+      scripts.iter().map(|script| script.name.as_str()).collect();
+    writeln!(out, "{}", serde_json::to_string_pretty(&names)?)?;
+  } else {
+    for script in scripts {
+      if script.dev {
+        writeln!(out, "{} (dev)", script.name)?;
+      } else {
+        writeln!(out, "{}", script.name)?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Whether the module an installed script runs can still be found.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetStatus {
+  Ok,
+  /// The local file it runs was moved or deleted.
+  Missing,
+  /// Remote modules aren't fetched to check them.
+  Remote,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct VerifyReport {
+  pub name: String,
+  pub module_url: String,
+  pub status: TargetStatus,
+}
+
+/// Checks that the module the executable of `name` runs is still there.
+pub fn verify_install(
+  name: &str,
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+) -> Result<VerifyReport, Error> {
+  validate_name(name)?;
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  verify_executable(name, &get_executable_path(&dirs.bin, name))
+}
+
+fn verify_executable(
+  name: &str,
+  file_path: &PathBuf,
+) -> Result<VerifyReport, Error> {
+  let shim_info = inspect_shim(file_path)?;
+  let status = match Url::parse(&shim_info.module_url) {
+    Ok(url) if url.scheme() == "file" => match url.to_file_path() {
+      Ok(path) if path.exists() => TargetStatus::Ok,
+      _ => TargetStatus::Missing,
+    },
+    _ => TargetStatus::Remote,
+  };
+  Ok(VerifyReport {
+    name: name.to_string(),
+    module_url: shim_info.module_url,
+    status,
+  })
+}
+
+/// Removes the files left next to the executables (config and import map
+/// copies, metadata), or in `$DENO_DIR/installs`, of scripts that are no
+/// longer installed. Copies the metadata of an installed script refers to
+/// are kept. Returns the paths of the removed files.
+pub fn prune(
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+) -> Result<Vec<PathBuf>, Error> {
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  let orphans = get_orphaned_files(&dirs)?;
+  for file_path in &orphans {
+    debug!("Removing orphaned file {}", file_path.to_string_lossy());
+    fs::remove_file(file_path)?;
+  }
+  if let Ok(copies_dir) = get_deno_dir_copies_dir(&dirs.sidecar) {
+    for file_path in &orphans {
+      let mut dir = file_path.parent();
+      while let Some(parent_dir) = dir {
+        if !parent_dir.starts_with(&copies_dir)
+          || fs::remove_dir(parent_dir).is_err()
+        {
+          break;
+        }
+        dir = parent_dir.parent();
+      }
+    }
+  }
+  Ok(orphans)
+}
+
+/// Reports the problems found with the installation directory and the scripts
+/// installed in it, along with how to fix each of them.
+pub fn doctor(
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  let installation_dir = &dirs.bin;
+  let mut problems = 0;
+
+  writeln!(
+    out,
+    "Installation directory: {}",
+    installation_dir.to_string_lossy()
+  )?;
+  if !installation_dir.is_dir() {
+    writeln!(out, "No scripts installed")?;
+    return Ok(());
+  }
+
+  if !is_in_path(installation_dir) {
+    problems += 1;
+    writeln!(
+      out,
+      "⚠️  {} is not in PATH, installed scripts can only be run by their full path",
+      installation_dir.to_string_lossy()
+    )?;
+    if let Some(root) = get_parent_in_path(installation_dir) {
+      writeln!(
+        out,
+        "    You added the root {} to PATH, add the bin subdirectory instead:",
+        root.to_string_lossy()
+      )?;
+    } else {
+      writeln!(out, "    Add it to PATH with:")?;
+    }
+    write_path_command(installation_dir, None, out)?;
+  }
+
+  // Aliases are part of the script they point at, not scripts of their own.
+  let mut aliases = vec![];
+  let mut names = get_installed_names(installation_dir)?;
+  for name in &names {
+    if let Ok(metadata) = InstallMetadata::read(&dirs.sidecar.join(name)) {
+      aliases.extend(metadata.aliases);
+    }
+  }
+  names.retain(|name| !aliases.contains(name));
+  match names.len() {
+    1 => writeln!(out, "1 script installed")?,
+    n => writeln!(out, "{} scripts installed", n)?,
+  }
+
+  for file_path in get_orphaned_files(&dirs)? {
+    problems += 1;
+    writeln!(
+      out,
+      "⚠️  Orphaned file {}, its script is not installed",
+      file_path.to_string_lossy()
+    )?;
+    writeln!(out, "    Remove it with --prune, or reinstall the script")?;
+  }
+
+  for name in &names {
+    if !InstallMetadata::filename(&dirs.sidecar.join(name)).exists() {
+      problems += 1;
+      writeln!(out, "⚠️  {} has no installation metadata", name)?;
+      writeln!(out, "    Reinstall it with -f to record it")?;
+    }
+    for conflict in get_conflicting_paths(installation_dir, name) {
+      problems += 1;
+      writeln!(
+        out,
+        "⚠️  {} is also provided by {}",
+        name,
+        conflict.to_string_lossy()
+      )?;
+      writeln!(
+        out,
+        "    The one found first in PATH is run, reinstall with -n to pick another name"
+      )?;
+    }
+    let file_path = get_executable_path(installation_dir, name);
+    if let Ok(report) = verify_executable(name, &file_path) {
+      if report.status == TargetStatus::Missing {
+        problems += 1;
+        writeln!(
+          out,
+          "⚠️  {} runs {}, which no longer exists",
+          name, report.module_url
+        )?;
+        writeln!(
+          out,
+          "    Install it again from where the module is now, or uninstall it"
+        )?;
+      }
+    }
+  }
+
+  if problems == 0 {
+    writeln!(out, "✅ No problems found")?;
+  }
+  Ok(())
+}
+
+/// What `install` did, or would have done with --dry-run or --print-args.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct InstallResult {
+  pub name: String,
+  pub path: PathBuf,
+  /// False if nothing was written, eg. with --if-not-installed when the
+  /// script was already installed.
+  pub installed: bool,
+  /// The config file the script is run with, if any.
+  pub config_path: Option<PathBuf>,
+  /// True if the installation directory has to be added to PATH to run the
+  /// script by its name.
+  pub path_update_needed: bool,
+  /// True if an executable already installed under the name was replaced,
+  /// with -f/--force or --update.
+  pub overwrote: bool,
+}
+
+/// Reports a successful installation and how to run the script, with a hint
+/// for the shell to add the installation directory to PATH if `path_hint`.
+pub fn write_install_result(
+  result: &InstallResult,
+  path_hint: bool,
+  shell: Option<&str>,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  writeln!(out, "✅ Successfully installed {}", result.name)?;
+  writeln!(out, "{}", result.path.to_string_lossy())?;
+  if !result.path_update_needed {
+    writeln!(out, "Run it with: {}", result.name)?;
+    return Ok(());
+  }
+  let hint_shell = get_hint_shell(shell);
+  let run_command = get_run_command(&result.path, &hint_shell);
+  if !path_hint {
+    writeln!(out, "Run it with: {}", run_command)?;
+    return Ok(());
+  }
+  let installation_dir = result.path.parent().unwrap_or_else(|| Path::new(""));
+  if let Some(root) = get_parent_in_path(installation_dir) {
+    writeln!(
+      out,
+      "ℹ️  You added the root {} to PATH, add the bin subdirectory {} instead",
+      root.to_string_lossy(),
+      installation_dir.to_string_lossy()
+    )?;
+  } else {
+    writeln!(out, "ℹ️  Add {} to PATH", installation_dir.to_string_lossy())?;
+  }
+  write_path_command(installation_dir, Some(&hint_shell), out)?;
+  writeln!(out, "Run it with: {}", run_command)?;
+  Ok(())
+}
+
+pub fn install(
+  mut flags: Flags,
+  mut install_flags: InstallFlags,
+  json_out: &mut dyn Write,
+) -> Result<InstallResult, InstallError> {
+  // With --json, only the outcome is written, as JSON. With -q/--quiet,
+  // nothing is, errors are returned.
+  let text_suppressed = install_flags.json || install_flags.quiet;
+  let mut text_sink = std::io::sink();
+  let out: &mut dyn Write = if text_suppressed {
+    &mut text_sink
+  } else {
+    &mut *json_out
+  };
+  let cwd = env::current_dir()?;
+  resolve_module_file(&mut install_flags)?;
+  apply_project_config(&cwd, &mut flags, &mut install_flags, out)?;
+  apply_config_env(&mut flags)?;
+
+  let InstallFlags {
+    module_url,
+    args,
+    name,
+    root,
+    force,
+    if_not_installed,
+    update,
+    keep_existing_config,
+    no_copy_config,
+    copies_in_deno_dir,
+    config_dest_name,
+    config_template,
+    max_name_length,
+    name_from_subcommand,
+    use_module_hints,
+    targets,
+    mut aliases,
+    names,
+    local,
+    ephemeral,
+    bin_subdir,
+    quiet_runtime,
+    quiet_path_hint,
+    modify_path,
+    run_dir,
+    deno_args,
+    backup_dir,
+    trampoline,
+    completions,
+    header,
+    dry_run,
+    print_args,
+    json,
+    mode,
+    verify,
+    pin,
+    module_checksum,
+    dev,
+    deno_path,
+    shell,
+    env,
+    ..
+  } = install_flags;
+  // Relative to where deno install runs, not to where the script will.
+  let run_dir = run_dir.map(|run_dir| cwd.join(run_dir));
+  // Left in place for the script to be tried out, and not in PATH, once it
+  // is installed. Removed again when the installation fails.
+  let ephemeral_dir = if ephemeral && !dry_run && !print_args {
+    Some(tempfile::Builder::new().prefix("deno-install-").tempdir()?)
+  } else {
+    None
+  };
+  let root = match &ephemeral_dir {
+    Some(ephemeral_dir) => Some(ephemeral_dir.path().to_path_buf()),
+    None => root,
+  };
+  let quiet_path_hint = quiet_path_hint || ephemeral;
+  let modify_path = modify_path && !ephemeral;
+  for deno_arg in &deno_args {
+    validate_deno_arg(deno_arg)?;
+  }
+  if trampoline.is_some() && !cfg!(windows) {
+    return Err(
+      Error::new(
+        ErrorKind::InvalidInput,
+        "--trampoline is only supported on Windows",
+      )
+      .into(),
+    );
+  }
+  if let Some(mode) = mode {
+    if mode & 0o100 == 0 {
+      return Err(InstallError::InvalidMode(mode));
+    }
+  }
+  // A line break would end the comment and run the rest.
+  if header
+    .as_ref()
+    .map_or(false, |header| header.contains(&['\n', '\r'][..]))
+  {
+    return Err(
+      Error::new(ErrorKind::InvalidInput, "--header must be a single line")
+        .into(),
+    );
+  }
+  if let Some(bin_subdir) = &bin_subdir {
+    validate_bin_subdir(bin_subdir)?;
+  }
+  for (key, _) in &env {
+    validate_env_name(key)?;
+  }
+  let module_url = expand_module_shorthand(&module_url)?;
+  let module_url = module_url.as_str();
+  // Local modules are checked anyway when they are resolved.
+  if verify && is_remote_url(module_url) {
+    let url = parse_remote_url(module_url)?;
+    fetch_remote_module(&url, flags.ca_file.clone()).map_err(|err| {
+      Error::new(
+        ErrorKind::Other,
+        format!("Module {} can't be fetched: {}", module_url, err),
+      )
+    })?;
+  }
+  // The checksum of the source the script runs, as it was cached when the
+  // module was fetched, not of the module downloaded once more.
+  let module_checksum = if !pin {
+    None
+  } else if is_remote_url(module_url) {
+    if module_checksum.is_none() {
+      return Err(
+        Error::new(
+          ErrorKind::InvalidInput,
+          format!(
+            "Module {} has to be fetched and cached before it can be pinned",
+            module_url
+          ),
+        )
+        .into(),
+      );
+    }
+    module_checksum
+  } else {
+    writeln!(
+      out,
+      "⚠️  Only remote modules are pinned, {} is not pinned",
+      module_url
+    )?;
+    None
+  };
+  // With --local, the project gets its own root in the current directory,
+  // created along with the bin directory.
+  let dirs = if local {
+    let bin =
+      get_bin_dir(&cwd.canonicalize()?.join(".deno"), bin_subdir.as_deref());
+    InstallDirs {
+      sidecar: bin.clone(),
+      bin,
+    }
+  } else {
+    get_install_dirs(root, bin_subdir.as_deref())?
+  };
+  let installation_dir = dirs.bin.clone();
+  debug!("Installing into {}", installation_dir.to_string_lossy());
+
+  // ensure directory exists, a symlink to one is followed
+  if let Ok(metadata) = fs::metadata(&installation_dir) {
+    if !metadata.is_dir() {
+      return Err(InstallError::NotADirectory);
+    }
+  } else if fs::symlink_metadata(&installation_dir).is_ok() {
+    return Err(InstallError::BrokenSymlink);
+  } else if !dry_run && !print_args {
+    fs::create_dir_all(&installation_dir).map_err(|err| {
+      if err.kind() == ErrorKind::PermissionDenied {
+        InstallError::NotWritable(installation_dir.clone()).into()
+      } else {
+        err
+      }
+    })?;
+  };
+  // Held until the installation is written.
+  let _lock = if !dry_run && !print_args {
+    check_dir_writable(&installation_dir)?;
+    fs::create_dir_all(&dirs.sidecar)?;
+    Some(InstallLock::acquire(&dirs.sidecar)?)
+  } else {
+    None
+  };
+
+  // Takes precedence over the name inferred from a local directory.
+  let (module_url, package_name) = resolve_module_url(module_url, &cwd)?;
+  if dev && module_url.scheme() != "file" {
+    return Err(
+      Error::new(
+        ErrorKind::InvalidInput,
+        format!("--dev only applies to local modules, not {}", module_url),
+      )
+      .into(),
+    );
+  }
+
+  if use_module_hints {
+    let source = read_module_source(&module_url, flags.ca_file.clone())?;
+    let hints = parse_module_hints(&source);
+    for hint in apply_module_hints(&mut flags, &hints) {
+      writeln!(out, "⚠️  Ignoring unsupported module hint: {}", hint)?;
+    }
+  }
+
+  for arg in args.iter().filter(|arg| looks_like_module(arg)) {
+    writeln!(
+      out,
+      "⚠️  \"{}\" looks like a module but will be passed to {} as an argument. To install several modules, use --many.",
+      arg, module_url
+    )?;
+  }
+  for arg in args.iter().filter(|arg| is_permission_flag(arg)) {
+    writeln!(
+      out,
+      "⚠️  {} follows the module, so it will be passed to {} as an argument, not to deno. To grant the permission, pass it before the module.",
+      arg, module_url
+    )?;
+  }
+
+  // With --names, the first name is the one the installation is known by.
+  let pass_name = !names.is_empty();
+  let mut name = name;
+  if pass_name {
+    name = Some(names[0].clone());
+    aliases = names[1..].to_vec();
+  }
+  let name_given = name.is_some() && !pass_name;
+  let name = match name {
+    Some(name) => {
+      let name = name.trim().to_string();
+      match max_name_length {
+        Some(max_length) if name.chars().count() > max_length => {
+          return Err(InstallError::NameTooLong(name, max_length))
+        }
+        _ => name,
+      }
+    }
+    None => {
+      let mut name = match package_name {
+        Some(package_name) => package_name,
+        None => infer_name(&module_url).map_err(InstallError::NameInference)?,
+      };
+      if name_from_subcommand {
+        let subcommand = args
+          .first()
+          .filter(|arg| !arg.starts_with('-'))
+          .map(|arg| sanitize_subcommand(arg))
+          .filter(|subcommand| !subcommand.is_empty())
+          .ok_or_else(|| {
+            Error::new(
+              ErrorKind::InvalidInput,
+              "--name-from-subcommand needs a subcommand as the first argument of the script",
+            )
+          })?;
+        name = format!("{}-{}", name, subcommand);
+      }
+      // Inferred names are valid ASCII, so truncating keeps them valid.
+      match max_name_length {
+        Some(max_length) => name.chars().take(max_length).collect(),
+        None => name,
+      }
+    }
+  };
+
+  validate_name(name.as_str())?;
+  let mut kinds = vec![];
+  for target in &targets {
+    let kind = ExecutableKind::from_name(target).ok_or_else(|| {
+      Error::new(
+        ErrorKind::Other,
+        format!("Unknown executable target: {}", target),
+      )
+    })?;
+    if !kinds.contains(&kind) {
+      kinds.push(kind);
+    }
+  }
+  if kinds.is_empty() {
+    kinds = ExecutableKind::host_kinds();
+  }
+  let mut file_paths: Vec<(ExecutableKind, PathBuf)> = kinds
+    .iter()
+    .map(|kind| {
+      (
+        *kind,
+        get_executable_path_for(*kind, &installation_dir, &name),
+      )
+    })
+    .collect();
+  // The first one is reported and its checksum recorded.
+  let file_path = file_paths[0].1.clone();
+  let path_update_needed = !is_in_path(&installation_dir);
+  let sidecar_path = dirs.sidecar.join(&name);
+  let copies_path = get_copies_path(&dirs.sidecar, &name, copies_in_deno_dir)?;
+  let completions_path = match &completions {
+    Some((shell, _)) => Some(
+      get_completions_path(&installation_dir, &name, shell).ok_or_else(
+        || {
+          Error::new(
+            ErrorKind::InvalidInput,
+            format!("Completions are not supported for shell: {}", shell),
+          )
+        },
+      )?,
+    ),
+    None => None,
+  };
+  let config_copy_path = match &config_dest_name {
+    Some(config_dest_name) => {
+      let config_copy_path = copies_path.with_file_name(config_dest_name);
+      // An existing file is only replaced if it is a config copy already,
+      // as when several scripts share one with the same --config-name.
+      let replaces_other_file = config_copy_path.exists()
+        && !get_referenced_copies(&dirs, None)?.contains(&config_copy_path);
+      if !is_valid_config_name(config_dest_name)
+        || file_paths.iter().any(|(_, path)| *path == config_copy_path)
+        || replaces_other_file
+      {
+        return Err(InstallError::InvalidConfigName(
+          config_dest_name.to_string(),
+        ));
+      }
+      config_copy_path
+    }
+    None => get_config_file_path(&copies_path),
+  };
+
+  let mut alias_names = vec![];
+  for alias in aliases {
+    validate_name(&alias)?;
+    if alias == name {
+      return Err(
+        Error::new(
+          ErrorKind::InvalidInput,
+          format!("Alias {} is the name of the script itself", alias),
+        )
+        .into(),
+      );
+    }
+    if !alias_names.contains(&alias) {
+      alias_names.push(alias);
+    }
+  }
+  for alias in &alias_names {
+    for kind in &kinds {
+      let path = get_executable_path_for(*kind, &installation_dir, alias);
+      file_paths.push((*kind, path));
+    }
+  }
+
+  let mut names = vec![name.clone()];
+  names.extend(alias_names.iter().cloned());
+  // The names are validated above, this keeps a script from being written
+  // outside of the installation directory should they ever be let through.
+  for name in &names {
+    if !is_inside_dir(&installation_dir, &installation_dir.join(name)) {
+      return Err(InstallError::InvalidName(name.to_string()));
+    }
+  }
+  let mut planned_paths: Vec<PathBuf> =
+    file_paths.iter().map(|(_, path)| path.clone()).collect();
+  // A trampoline is written in place of each batch file.
+  if trampoline.is_some() {
+    for (kind, path) in &file_paths {
+      if *kind == ExecutableKind::Cmd {
+        let exe_path = path.with_extension("exe");
+        planned_paths.push(get_trampoline_args_path(&exe_path));
+        planned_paths.push(exe_path);
+      }
+    }
+  }
+  let availability =
+    verify_name_available(&installation_dir, &names, &planned_paths);
+  if !availability.taken_in_target.is_empty() && !force && update {
+    // Only an installation of the same module is updated.
+    match get_installed_module_url(&file_path, &sidecar_path) {
+      Some(installed_url) if installed_url == module_url.as_str() => {}
+      Some(installed_url) => {
+        return Err(InstallError::DifferentModule(installed_url))
+      }
+      None => return Err(InstallError::ExistingInstallation),
+    }
+  } else if !availability.taken_in_target.is_empty() && !force {
+    if if_not_installed {
+      return Ok(InstallResult {
+        name,
+        path: file_path,
+        installed: false,
+        config_path: None,
+        path_update_needed,
+        overwrote: false,
+      });
+    }
+    return Err(InstallError::ExistingInstallation);
+  };
+  // A name shared with an unrelated tool shouldn't be taken over unnoticed.
+  if !availability.taken_in_target.is_empty() {
+    if let Some(installed_url) =
+      get_installed_module_url(&file_path, &sidecar_path)
+    {
+      if installed_url != module_url.as_str() {
+        writeln!(
+          out,
+          "⚠️  Replacing different module: {} was installed from {}",
+          name, installed_url
+        )?;
+      }
+    }
+  }
+  for taken_path in &availability.taken_elsewhere {
+    writeln!(
+      out,
+      "⚠️  {} already exists, the one found first in PATH will be run",
+      taken_path.to_string_lossy()
+    )?;
+  }
+  for name in &names {
+    for shadowing_path in get_shadowing_paths(&installation_dir, name) {
+      if availability.taken_elsewhere.contains(&shadowing_path) {
+        continue;
+      }
+      writeln!(
+        out,
+        "⚠️  {} comes first in PATH, running {} will run it instead",
+        shadowing_path.to_string_lossy(),
+        name
+      )?;
+    }
+  }
+
+  let quiet = flags.log_level == Some(Level::Error);
+  // Whatever the log level of deno install itself.
+  if quiet_runtime {
+    flags.log_level = Some(Level::Error);
+  }
+  // Only these are passed on to deno run, see get_log_level_args.
+  if let Some(log_level) = flags.log_level {
+    if !matches!(log_level, Level::Error | Level::Info | Level::Debug) {
+      return Err(
+        Error::new(
+          ErrorKind::Other,
+          format!("invalid log level {}", log_level),
+        )
+        .into(),
+      );
+    }
+  }
+  let flag_args = build_flag_args(&flags);
+
+  // With --keep-existing-config, a config copy left by a previous install
+  // is reused as is, even if it was edited by hand since.
+  let keep_config = force && keep_existing_config && config_copy_path.exists();
+
+  let config_source_path = flags
+    .config_path
+    .as_ref()
+    .map(|config_path| cwd.join(config_path));
+  // With --no-copy-config, the executable uses the given config directly.
+  let shared_config_path = match &config_source_path {
+    Some(config_source_path) if no_copy_config => {
+      Some(config_source_path.canonicalize()?)
+    }
+    _ => None,
+  };
+  // Checked before anything is written, so a missing config file doesn't
+  // leave behind an executable pointing to a copy that was never made.
+  if let Some(config_source_path) = &config_source_path {
+    if !keep_config && !no_copy_config {
+      // A template is only JSON once substituted, see render_config_file.
+      config_source_path
+        .canonicalize()
+        .and_then(|path| {
+          if config_template {
+            Ok(())
+          } else {
+            check_json_file(&path, true)
+          }
+        })
+        .map_err(|err| {
+          InstallError::ConfigCopy(config_source_path.to_path_buf(), err)
+        })?;
+    }
+  }
+
+  let import_map_source_path = flags
+    .import_map_path
+    .as_ref()
+    .map(|import_map_path| cwd.join(import_map_path));
+  if let Some(import_map_source_path) = &import_map_source_path {
+    import_map_source_path
+      .canonicalize()
+      .and_then(|path| check_json_file(&path, false))
+      .map_err(|err| {
+        InstallError::ImportMapCopy(import_map_source_path.to_path_buf(), err)
+      })?;
+  }
+  let lock_source_path =
+    flags.lock.as_ref().map(|lock_path| cwd.join(lock_path));
+  if let Some(lock_source_path) = &lock_source_path {
+    lock_source_path.canonicalize().map_err(|err| {
+      InstallError::LockCopy(lock_source_path.to_path_buf(), err)
+    })?;
+  }
+  // A reinstall points at the copies themselves, which are kept as they are.
+  let keep_import_map = import_map_source_path.as_ref().map_or(false, |path| {
+    is_same_file(path, &get_import_map_file_path(&copies_path))
+  });
+  let keep_lock = lock_source_path.as_ref().map_or(false, |path| {
+    is_same_file(path, &get_lock_file_path(&copies_path))
+  });
+
+  let mut config_file_copy = None;
+  if !flags.no_config && (flags.config_path.is_some() || keep_config) {
+    let config_file_path = match &shared_config_path {
+      Some(shared_config_path) => shared_config_path.clone(),
+      None => config_copy_path.clone(),
+    };
+    config_file_copy = config_file_path.to_str().map(String::from);
+  }
+  let mut import_map_file_copy = None;
+  if import_map_source_path.is_some() {
+    let import_map_file_path = get_import_map_file_path(&copies_path);
+    import_map_file_copy = import_map_file_path.to_str().map(String::from);
+  }
+  // The lock file is copied so that the script keeps loading exactly the
+  // dependencies it was installed with.
+  let mut lock_file_copy = None;
+  if lock_source_path.is_some() {
+    let lock_file_path = get_lock_file_path(&copies_path);
+    lock_file_copy = lock_file_path.to_str().map(String::from);
+  }
+
+  // The executables point at the copies instead of the given files.
+  let mut shim_flags = flags.clone();
+  shim_flags.config_path = config_file_copy.clone();
+  shim_flags.import_map_path = import_map_file_copy.clone();
+  shim_flags.lock = lock_file_copy.clone();
+  let mut executable_args =
+    build_executable_args(&shim_flags, module_url.as_str(), &args);
+  let module_index = executable_args.len() - args.len() - 1;
+  executable_args.splice(module_index..module_index, deno_args.clone());
+  let name_index = if pass_name {
+    Some(module_index + deno_args.len() + 1)
+  } else {
+    None
+  };
+
+  let mut overwritten_files: Vec<PathBuf> = planned_paths
+    .iter()
+    .filter(|path| path.exists())
+    .cloned()
+    .collect();
+  let shim_existed = !overwritten_files.is_empty();
+  overwritten_files.extend(availability.case_variants.iter().cloned());
+  if flags.config_path.is_some()
+    && !keep_config
+    && !no_copy_config
+    && config_copy_path.exists()
+  {
+    overwritten_files.push(config_copy_path.clone());
+  }
+  let import_map_file_path = get_import_map_file_path(&copies_path);
+  if import_map_source_path.is_some()
+    && !keep_import_map
+    && import_map_file_path.exists()
+  {
+    overwritten_files.push(import_map_file_path);
+  }
+  let lock_file_path = get_lock_file_path(&copies_path);
+  if lock_source_path.is_some() && !keep_lock && lock_file_path.exists() {
+    overwritten_files.push(lock_file_path);
+  }
+  let metadata_file_path = InstallMetadata::filename(&sidecar_path);
+  if metadata_file_path.exists() {
+    overwritten_files.push(metadata_file_path.clone());
+  }
+  // With --force, what an existing installation would be replaced with.
+  let installed_module_url = if overwritten_files.is_empty() {
+    None
+  } else {
+    get_installed_module_url(&file_path, &sidecar_path)
+  };
+
+  if print_args || dry_run {
+    if print_args {
+      writeln!(
+        json_out,
+        "{}",
+        serde_json::to_string_pretty(&executable_args)?
+      )?;
+    } else {
+      let executables: Vec<(PathBuf, String)> = file_paths
+        .iter()
+        .map(|(kind, path)| {
+          let args = get_wrapper_args(&executable_args, name_index, path);
+          (
+            path.clone(),
+            kind.render(deno_path.as_deref(), &env, run_dir.as_deref(), &args),
+          )
+        })
+        .collect();
+      write_install_preview(
+        &name,
+        &executables,
+        &executable_args,
+        config_file_copy.as_deref(),
+        module_url.as_str(),
+        &overwritten_files,
+        installed_module_url.as_deref(),
+        json,
+        json_out,
+      )?;
+    }
+    return Ok(InstallResult {
+      name,
+      path: file_path,
+      installed: false,
+      config_path: config_file_copy.map(PathBuf::from),
+      path_update_needed,
+      overwrote: shim_existed,
+    });
+  }
+
+  // Copies of an earlier installation that the new one doesn't refer to.
+  let stale_files: Vec<PathBuf> = vec![
+    (get_config_file_path(&copies_path), &config_file_copy),
+    (
+      get_import_map_file_path(&copies_path),
+      &import_map_file_copy,
+    ),
+    (get_lock_file_path(&copies_path), &lock_file_copy),
+  ]
+  .into_iter()
+  .filter(|(path, copy)| path.exists() && copy.as_deref() != path.to_str())
+  .map(|(path, _)| path)
+  .collect();
+
+  let installed_at = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or(0);
+  let template_vars = if config_template {
+    vec![
+      (
+        "INSTALL_DIR",
+        installation_dir.to_string_lossy().to_string(),
+      ),
+      ("NAME", name.clone()),
+    ]
+  } else {
+    vec![]
+  };
+  // In the order of `file_paths`, without the header comments.
+  let templates: Vec<(ExecutableKind, &PathBuf, String)> = file_paths
+    .iter()
+    .map(|(kind, path)| {
+      let args = get_wrapper_args(&executable_args, name_index, path);
+      let template =
+        kind.render(deno_path.as_deref(), &env, run_dir.as_deref(), &args);
+      (*kind, path, ensure_trailing_newline(template))
+    })
+    .collect();
+  let add_headers = |installed_at: u64| -> Vec<(PathBuf, String)> {
+    templates
+      .iter()
+      .map(|(kind, path, template)| {
+        let template = kind.add_header(
+          template,
+          module_url.as_str(),
+          installed_at,
+          header.as_deref(),
+        );
+        ((*path).clone(), template)
+      })
+      .collect()
+  };
+  let executables = add_headers(installed_at);
+  let metadata = InstallMetadata {
+    name: name.clone(),
+    module_url: module_url.to_string(),
+    args: executable_args,
+    flag_args,
+    script_args: args,
+    config_path: config_file_copy.clone(),
+    config_copied: config_file_copy.is_some() && shared_config_path.is_none(),
+    copies_in_deno_dir,
+    lock_path: lock_file_copy,
+    import_map_path: import_map_file_copy,
+    installed_at,
+    version: crate::version::DENO.to_string(),
+    checksum: crate::checksum::gen(&[executables[0].1.as_bytes()]),
+    aliases: alias_names,
+    pass_name,
+    env: env.clone(),
+    module_checksum,
+    run_dir: run_dir.clone(),
+    deno_args,
+    dev,
+    completions: completions.clone(),
+    header: header.clone(),
+    bin_subdir,
+    targets,
+    deno_path: deno_path.clone(),
+    mode,
+    // Read again when the executables are regenerated, from anywhere.
+    trampoline: trampoline
+      .as_ref()
+      .map(|stub| stub.canonicalize().unwrap_or_else(|_| stub.clone())),
+    no_config: flags.no_config,
+  };
+
+  // A forced reinstall that would write exactly what is installed leaves the
+  // files, and their modification times, as they are.
+  let installed_metadata = if force && stale_files.is_empty() {
+    InstallMetadata::read(&sidecar_path).ok()
+  } else {
+    None
+  };
+  if let Some(installed_metadata) = installed_metadata {
+    // As if installed at the same time, which only shows in the headers.
+    let executables = add_headers(installed_metadata.installed_at);
+    let expected_metadata = InstallMetadata {
+      installed_at: installed_metadata.installed_at,
+      checksum: crate::checksum::gen(&[executables[0].1.as_bytes()]),
+      ..metadata.clone()
+    };
+    let mut expected_files: Vec<(PathBuf, Vec<u8>, Option<u32>)> = executables
+      .into_iter()
+      .map(|(path, template)| {
+        (
+          path,
+          template.into_bytes(),
+          Some(get_executable_file_mode(mode)),
+        )
+      })
+      .collect();
+    let mut sources_readable = true;
+    if let Some(config_source_path) = &config_source_path {
+      if !keep_config && !no_copy_config {
+        match config_source_path
+          .canonicalize()
+          .and_then(|path| render_config_file(&path, &template_vars))
+        {
+          Ok(config) => expected_files.push((
+            config_copy_path.clone(),
+            config,
+            Some(get_config_file_mode(mode)),
+          )),
+          Err(_) => sources_readable = false,
+        }
+      }
+    }
+    let copied_files = vec![
+      (
+        &import_map_source_path,
+        keep_import_map,
+        get_import_map_file_path(&copies_path),
+      ),
+      (
+        &lock_source_path,
+        keep_lock,
+        get_lock_file_path(&copies_path),
+      ),
+    ];
+    for (source_path, keep, copy_path) in copied_files {
+      if let Some(source_path) = source_path {
+        if !keep {
+          match fs::read(source_path) {
+            Ok(content) => expected_files.push((copy_path, content, None)),
+            Err(_) => sources_readable = false,
+          }
+        }
+      }
+    }
+    if sources_readable
+      && installed_metadata == expected_metadata
+      && expected_files
+        .iter()
+        .all(|(path, content, mode)| is_file_up_to_date(path, content, *mode))
+    {
+      writeln!(out, "✅ {} is already up to date", name)?;
+      return Ok(InstallResult {
+        name,
+        path: file_path,
+        installed: false,
+        config_path: config_file_copy.map(PathBuf::from),
+        path_update_needed,
+        overwrote: false,
+      });
+    }
+  }
+
+  if !overwritten_files.is_empty() {
+    writeln!(out, "The following files will be overwritten:")?;
+    for overwritten_file in &overwritten_files {
+      writeln!(out, "  {}", overwritten_file.to_string_lossy())?;
+    }
+    // The prompt would go to the sink too, and wait for an answer to a
+    // question that was never shown.
+    let interactive = !text_suppressed && is_terminal();
+    let stdin = std::io::stdin();
+    if !confirm_overwrite(out, &mut stdin.lock(), interactive)? {
+      return Err(
+        Error::new(
+          ErrorKind::Other,
+          "Installation aborted, no file was overwritten.",
+        )
+        .into(),
+      );
+    }
+  }
+
+  // The metadata is rewritten, everything else overwritten is backed up.
+  let backed_up_files: Vec<&PathBuf> = overwritten_files
+    .iter()
+    .filter(|path| **path != metadata_file_path)
+    .collect();
+  if !backed_up_files.is_empty() {
+    let backup_dir = backup_dir.map(|dir| dir.join(installed_at.to_string()));
+    if let Some(backup_dir) = &backup_dir {
+      fs::create_dir_all(backup_dir)?;
+    }
+    for path in backed_up_files {
+      let backup_path = get_backup_path(path, backup_dir.as_deref());
+      move_file(path, &backup_path)?;
+      writeln!(
+        out,
+        "Backed up {} to {}",
+        path.to_string_lossy(),
+        backup_path.to_string_lossy()
+      )?;
+    }
+  }
+
+  // The config is copied first, the executables are only written once
+  // everything they refer to is in place.
+  if let Some(sidecar_dir) = sidecar_path.parent() {
+    fs::create_dir_all(sidecar_dir)?;
+  }
+  let makes_copies =
+    (config_source_path.is_some() && !keep_config && !no_copy_config)
+      || (import_map_source_path.is_some() && !keep_import_map)
+      || (lock_source_path.is_some() && !keep_lock);
+  if makes_copies {
+    if let Some(copies_dir) = copies_path.parent() {
+      fs::create_dir_all(copies_dir)?;
+    }
+  }
+  if let Some(config_source_path) = &config_source_path {
+    if !keep_config && !no_copy_config {
+      generate_config_file(
+        &config_copy_path,
+        config_source_path,
+        &template_vars,
+        mode,
+      )?;
+    }
+  }
+  if let Some(import_map_source_path) = &import_map_source_path {
+    if !keep_import_map {
+      generate_import_map_file(&copies_path, import_map_source_path)?;
+    }
+  }
+  if let Some(lock_source_path) = &lock_source_path {
+    if !keep_lock {
+      generate_lock_file(&copies_path, lock_source_path)?;
+    }
+  }
+  let mut file_path = file_path;
+  for ((kind, _, _), (path, template)) in templates.iter().zip(&executables) {
+    if let Some(scope_dir) = path.parent() {
+      fs::create_dir_all(scope_dir)?;
+    }
+    if let (ExecutableKind::Cmd, Some(stub)) = (kind, &trampoline) {
+      let args = get_wrapper_args(&metadata.args, name_index, path);
+      let exe_path = write_trampoline(
+        stub,
+        path,
+        deno_path.as_deref(),
+        &env,
+        run_dir.as_deref(),
+        &args,
+      )?;
+      if *path == file_path {
+        file_path = exe_path;
+      }
+      continue;
+    }
+    debug!("Writing shim {}", path.to_string_lossy());
+    write_executable_file(path, template, mode)?;
+  }
+  if let (Some(completions_path), Some((shell, subcommand))) =
+    (&completions_path, &completions)
+  {
+    if let Some(completions_dir) = completions_path.parent() {
+      fs::create_dir_all(completions_dir)?;
+    }
+    debug!("Writing completions {}", completions_path.to_string_lossy());
+    let completions_file =
+      render_completions_file(&name, &file_path, shell, subcommand);
+    retry_transient(|| fs::write(completions_path, &completions_file))?;
+  }
+  if let Some(shared_config_path) = &shared_config_path {
+    writeln!(
+      out,
+      "⚠️  {} uses {} directly, it will break if that file is moved or deleted.",
+      name,
+      shared_config_path.to_string_lossy()
+    )?;
+  }
+
+  debug!("Writing metadata for {}", name);
+  metadata.write(&sidecar_path)?;
+  for stale_file in &stale_files {
+    fs::remove_file(stale_file)?;
+    writeln!(out, "Removed {}", stale_file.to_string_lossy())?;
+  }
+
+  // A name unlike the module's can be a mistake in either.
+  if name_given {
+    if let Ok(inferred_name) = infer_name(&module_url) {
+      let base_name = name.rsplit('/').next().unwrap_or(&name);
+      if base_name != inferred_name {
+        writeln!(
+          out,
+          "ℹ️  Installing as {}, the name inferred from the module is {}",
+          name, inferred_name
+        )?;
+      }
+    }
+  }
+  if let Some(ephemeral_dir) = ephemeral_dir {
+    ephemeral_dir.into_path();
+  }
+  let result = InstallResult {
+    name,
+    path: file_path,
+    installed: true,
+    config_path: config_file_copy.map(PathBuf::from),
+    path_update_needed,
+    overwrote: shim_existed,
+  };
+  let modifies_path = modify_path && path_update_needed;
+  write_install_result(
+    &result,
+    !quiet_path_hint && !modifies_path,
+    shell.as_deref(),
+    out,
+  )?;
+  if modifies_path {
+    add_to_path(&installation_dir, shell.as_deref(), out)?;
+  }
+  if ephemeral {
+    writeln!(
+      out,
+      "ℹ️  Installed into {}, try it out with:",
+      installation_dir.to_string_lossy()
+    )?;
+    let hint_shell = get_hint_shell(shell.as_deref());
+    writeln!(out, "  {}", get_run_command(&result.path, &hint_shell))?;
+  }
+  // To catch permissions broader than needed.
+  let permission_args = get_permission_args(&flags);
+  if !quiet && !permission_args.is_empty() {
+    writeln!(out, "Granted: {}", permission_args.join(", "))?;
+  }
+  if json {
+    let output = serde_json::json!({
+      "name": result.name,
+      "path": result.path,
+      "module_url": module_url.as_str(),
+      "config_path": result.config_path,
+      "path_update_needed": result.path_update_needed,
+      "overwrote": result.overwrote,
+    });
+    writeln!(json_out, "{}", serde_json::to_string_pretty(&output)?)?;
+  }
+  Ok(result)
+}
+
+/// Installs the module at `module_url` like `install`, for callers that
+/// already have it as a `Url`.
+pub fn install_url(
+  flags: Flags,
+  install_flags: InstallFlags,
+  module_url: &Url,
+  out: &mut dyn Write,
+) -> Result<InstallResult, InstallError> {
+  install(
+    flags,
+    InstallFlags {
+      module_url: module_url.to_string(),
+      ..install_flags
+    },
+    out,
+  )
+}
+
+/// Installs each of `modules` with the same options, which can't include a
+/// name, and carries on after a failure. Each module comes with the checksum
+/// of its cached source, for --pin. Returns the outcome for each module, in
+/// order.
+pub fn install_many(
+  flags: Flags,
+  install_flags: InstallFlags,
+  modules: Vec<(String, Option<String>)>,
+  out: &mut dyn Write,
+) -> Vec<(String, Result<InstallResult, InstallError>)> {
+  modules
+    .into_iter()
+    .map(|(module_url, module_checksum)| {
+      let result = install(
+        flags.clone(),
+        InstallFlags {
+          module_url: module_url.clone(),
+          args: vec![],
+          module_checksum,
+          ..install_flags.clone()
+        },
+        out,
+      );
+      (module_url, result)
+    })
+    .collect()
+}
+
+/// Reports the outcomes of `install_many`, as a JSON array with --json.
+pub fn write_install_summary(
+  results: &[(String, Result<InstallResult, InstallError>)],
+  json: bool,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  if json {
+    let results: Vec<serde_json::Value> = results
+      .iter()
+      .map(|(module_url, result)| match result {
+        Ok(result) => serde_json::json!({
+          "module": module_url,
+          "name": result.name,
+          "path": result.path,
+          "installed": result.installed,
+        }),
+        Err(err) => serde_json::json!({
+          "module": module_url,
+          "error": error_to_value(err),
+        }),
+      })
+      .collect();
+    writeln!(out, "{}", serde_json::to_string_pretty(&results)?)?;
+    return Ok(());
+  }
+
+  let failures = results.iter().filter(|(_, result)| result.is_err()).count();
+  writeln!(
+    out,
+    "{} of {} module(s) installed",
+    results.len() - failures,
+    results.len()
+  )?;
+  for (module_url, result) in results {
+    if let Err(err) = result {
+      writeln!(out, "❌ {}: {}", module_url, err)?;
+    }
+  }
+  Ok(())
+}
+
+/// Takes the module URL from the first non-empty line of the --module-file,
+/// if one was given.
+pub fn resolve_module_file(
+  install_flags: &mut InstallFlags,
+) -> Result<(), Error> {
+  if let Some(module_file) = install_flags.module_file.take() {
+    install_flags.module_url = read_module_file(&module_file)?;
+  }
+  Ok(())
+}
+
+/// With `-` as the module, takes the module from the first line of `input`,
+/// normally stdin. A name must be given, it isn't inferred from piped input.
+pub fn resolve_module_stdin(
+  install_flags: &mut InstallFlags,
+  input: &mut dyn BufRead,
+) -> Result<(), Error> {
+  if install_flags.module_url != "-" {
+    return Ok(());
+  }
+  if install_flags.name.is_none() && install_flags.names.is_empty() {
+    return Err(Error::new(
+      ErrorKind::InvalidInput,
+      "A name must be given with --name when reading the module from stdin",
+    ));
+  }
+  let mut line = String::new();
+  input.read_line(&mut line)?;
+  let module_url = line.trim();
+  if module_url.is_empty() {
+    return Err(Error::new(
+      ErrorKind::InvalidData,
+      "No module was given on stdin",
+    ));
+  }
+  install_flags.module_url = module_url.to_string();
+  Ok(())
+}
+
+/// The URL itself is left out of the errors, it may hold secrets.
+fn read_module_file(path: &Path) -> Result<String, Error> {
+  let contents = fs::read_to_string(path)?;
+  let module_url = contents
+    .lines()
+    .map(str::trim)
+    .find(|line| !line.is_empty())
+    .ok_or_else(|| {
+      Error::new(
+        ErrorKind::InvalidData,
+        format!("{} doesn't contain a module URL", path.to_string_lossy()),
+      )
+    })?;
+  Url::parse(module_url).map_err(|err| {
+    Error::new(
+      ErrorKind::InvalidData,
+      format!(
+        "{} doesn't contain a valid module URL: {}",
+        path.to_string_lossy(),
+        err
+      ),
+    )
+  })?;
+  Ok(module_url.to_string())
+}
+
+/// Rebuilds the flags `name` was installed with from its manifest, to install
+/// it again over the existing installation.
+pub fn get_reinstall_flags(
+  name: &str,
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+) -> Result<(Flags, InstallFlags), Error> {
+  let manifest = match load_manifest(name, root.clone(), bin_subdir) {
+    Err(err) if err.kind() == ErrorKind::NotFound => {
+      return Err(Error::new(
+        ErrorKind::NotFound,
+        format!(
+          "No install manifest found for {}, reinstall it manually with deno install",
+          name
+        ),
+      ))
+    }
+    result => result?,
+  };
+  let mut flags = get_manifest_flags(&manifest)?;
+  flags.import_map_path = manifest.import_map_path;
+  flags.lock = manifest.lock_path;
+
+  // A config copy is kept, a config used directly is used again.
+  let dirs = get_install_dirs(root.clone(), bin_subdir)?;
+  let sidecar_path = dirs.sidecar.join(name);
+  let mut no_copy_config = false;
+  let mut config_dest_name = None;
+  if let Some(config_path) = manifest.config_path {
+    let config_path = PathBuf::from(config_path);
+    if !is_config_copy(manifest.config_copied, &config_path, &sidecar_path) {
+      flags.config_path = Some(config_path.to_string_lossy().to_string());
+      no_copy_config = true;
+    } else if config_path.file_name()
+      != get_config_file_path(&sidecar_path).file_name()
+    {
+      // A copy named with --config-name.
+      config_dest_name = config_path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string());
+    }
+  }
+
+  let (aliases, names) = if manifest.pass_name {
+    let mut names = vec![name.to_string()];
+    names.extend(manifest.aliases);
+    (vec![], names)
+  } else {
+    (manifest.aliases, vec![])
+  };
+  let install_flags = InstallFlags {
+    module_url: manifest.module_url,
+    args: manifest.script_args,
+    name: Some(name.to_string()),
+    root,
+    force: true,
+    keep_existing_config: true,
+    no_copy_config,
+    copies_in_deno_dir: manifest.copies_in_deno_dir,
+    config_dest_name,
+    aliases,
+    names,
+    env: manifest.env,
+    run_dir: manifest.run_dir,
+    deno_args: manifest.deno_args,
+    dev: manifest.dev,
+    completions: manifest.completions,
+    header: manifest.header,
+    bin_subdir: manifest.bin_subdir,
+    targets: manifest.targets,
+    deno_path: manifest.deno_path,
+    mode: manifest.mode,
+    trampoline: manifest.trampoline,
+    pin: manifest.module_checksum.is_some(),
+    // Until the module is fetched again.
+    module_checksum: manifest.module_checksum,
+    ..InstallFlags::default()
+  };
+  Ok((flags, install_flags))
+}
+
+/// The flags of `deno run` recorded in `manifest`, without the files the
+/// executables refer to.
+fn get_manifest_flags(manifest: &InstallManifest) -> Result<Flags, Error> {
+  let mut run_args = vec!["deno".to_string(), "run".to_string()];
+  run_args.extend_from_slice(&manifest.flag_args);
+  run_args.push(manifest.module_url.clone());
+  // Recorded among the flags before deno run stopped taking it.
+  let no_config_index = run_args.iter().position(|arg| arg == "--no-config");
+  if let Some(index) = no_config_index {
+    run_args.remove(index);
+  }
+  let mut flags = flags_from_vec_safe(run_args)
+    .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
+  flags.subcommand = DenoSubcommand::default();
+  flags.no_config = manifest.no_config || no_config_index.is_some();
+  Ok(flags)
+}
+
+/// Writes the executables of `name` and its aliases again from its manifest,
+/// e.g. after `deno upgrade` changed how they are generated. The module isn't
+/// fetched and the copies next to the executables are left as they are.
+pub fn regenerate_executables(
+  name: &str,
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  let mut manifest = load_manifest(name, root.clone(), bin_subdir)?;
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  let mut flags = get_manifest_flags(&manifest)?;
+  flags.config_path = manifest.config_path.clone();
+  flags.import_map_path = manifest.import_map_path.clone();
+  flags.lock = manifest.lock_path.clone();
+  let mut executable_args =
+    build_executable_args(&flags, &manifest.module_url, &manifest.script_args);
+  let module_index = executable_args.len() - manifest.script_args.len() - 1;
+  executable_args.splice(
+    module_index..module_index,
+    manifest.deno_args.iter().cloned(),
+  );
+  let name_index = if manifest.pass_name {
+    Some(module_index + manifest.deno_args.len() + 1)
+  } else {
+    None
+  };
+
+  let mut kinds = vec![];
+  for target in &manifest.targets {
+    if let Some(kind) = ExecutableKind::from_name(target) {
+      if !kinds.contains(&kind) {
+        kinds.push(kind);
+      }
+    }
+  }
+  // Without recorded targets, other kinds than the platform's own are only
+  // written again if they are still there.
+  if kinds.is_empty() {
+    kinds = ExecutableKind::host_kinds();
+    for kind in &[ExecutableKind::Sh, ExecutableKind::Cmd, ExecutableKind::Ps1]
+    {
+      if !kinds.contains(kind)
+        && get_executable_path_for(*kind, &dirs.bin, name).exists()
+      {
+        kinds.push(*kind);
+      }
+    }
+  }
+  let mut names = vec![name.to_string()];
+  names.extend(manifest.aliases.iter().cloned());
+  let mut checksum = None;
+  for name in &names {
+    for kind in &kinds {
+      let path = get_executable_path_for(*kind, &dirs.bin, name);
+      let args = get_wrapper_args(&executable_args, name_index, &path);
+      let template = ensure_trailing_newline(kind.render(
+        manifest.deno_path.as_deref(),
+        &manifest.env,
+        manifest.run_dir.as_deref(),
+        &args,
+      ));
+      let template = kind.add_header(
+        &template,
+        &manifest.module_url,
+        manifest.installed_at,
+        manifest.header.as_deref(),
+      );
+      if checksum.is_none() {
+        checksum = Some(crate::checksum::gen(&[template.as_bytes()]));
+      }
+      if let Some(scope_dir) = path.parent() {
+        fs::create_dir_all(scope_dir)?;
+      }
+      if let (ExecutableKind::Cmd, Some(stub)) = (kind, &manifest.trampoline) {
+        let exe_path = write_trampoline(
+          stub,
+          &path,
+          manifest.deno_path.as_deref(),
+          &manifest.env,
+          manifest.run_dir.as_deref(),
+          &args,
+        )?;
+        writeln!(out, "✅ Regenerated {}", exe_path.to_string_lossy())?;
+        continue;
+      }
+      debug!("Writing shim {}", path.to_string_lossy());
+      write_executable_file(&path, &template, manifest.mode)?;
+      writeln!(out, "✅ Regenerated {}", path.to_string_lossy())?;
+    }
+  }
+
+  manifest.args = executable_args;
+  if let Some(checksum) = checksum {
+    manifest.checksum = checksum;
+  }
+  manifest.write(&dirs.sidecar.join(name))?;
+  Ok(())
+}
+
+/// Installs `name` again with the options recorded in its manifest.
+pub fn reinstall(
+  name: String,
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  let (flags, install_flags) = get_reinstall_flags(&name, root, bin_subdir)?;
+  install(flags, install_flags, out)?;
+  Ok(())
+}
+
+/// Removes the executables installed for `name`, its aliases, and the files
+/// accompanying them, and reports each removed file and the space freed.
+pub fn uninstall(
+  name: String,
+  root: Option<PathBuf>,
+  bin_subdir: Option<&str>,
+  json: bool,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  validate_name(&name)?;
+  let dirs = get_install_dirs(root, bin_subdir)?;
+  let sidecar_path = dirs.sidecar.join(&name);
+  let metadata_file_path = InstallMetadata::filename(&sidecar_path);
+
+  let mut copy_paths = vec![
+    get_config_file_path(&sidecar_path),
+    get_import_map_file_path(&sidecar_path),
+    get_lock_file_path(&sidecar_path),
+  ];
+  let mut names = vec![name.clone()];
+  let mut completions_path = None;
+  if let Ok(metadata) = InstallMetadata::read(&sidecar_path) {
+    names.extend(metadata.aliases);
+    if let Some((shell, _)) = &metadata.completions {
+      completions_path = get_completions_path(&dirs.bin, &name, shell);
+    }
+    // Where the installation made them, wherever DENO_DIR points now.
+    copy_paths = vec![];
+    if let Some(config_path) = metadata.config_path.map(PathBuf::from) {
+      if is_config_copy(metadata.config_copied, &config_path, &sidecar_path) {
+        copy_paths.push(config_path);
+      }
+    }
+    copy_paths.extend(metadata.import_map_path.map(PathBuf::from));
+    copy_paths.extend(metadata.lock_path.map(PathBuf::from));
+  }
+  // A copy shared with --config-name stays until its last script goes.
+  let shared_copies = get_referenced_copies(&dirs, Some(&name))?;
+  copy_paths.retain(|path| !shared_copies.contains(path));
+  let mut file_paths = vec![];
+  for name in &names {
+    // Files of other software with the same name, in a shared bin directory,
+    // are left alone.
+    for kind in &[ExecutableKind::Sh, ExecutableKind::Cmd, ExecutableKind::Ps1]
+    {
+      let path = get_executable_path_for(*kind, &dirs.bin, name);
+      if get_generated_executable_kind(&path).is_some() {
+        file_paths.push(path);
+      }
+    }
+    let exe_path = get_trampoline_path(&dirs.bin, name);
+    if is_trampoline(&exe_path) {
+      file_paths.push(get_trampoline_args_path(&exe_path));
+      file_paths.push(exe_path);
+    }
+  }
+  file_paths.extend(copy_paths);
+  file_paths.extend(completions_path);
+
+  let file_paths: Vec<PathBuf> = file_paths
+    .into_iter()
+    .filter(|path| path.exists())
+    .collect();
+  if file_paths.is_empty() {
+    return Err(Error::new(
+      ErrorKind::NotFound,
+      format!("No installation found for {}", name),
+    ));
+  }
+  let mut removed_files = vec![];
+  for file_path in file_paths
+    .into_iter()
+    .chain(Some(metadata_file_path).filter(|path| path.exists()))
+  {
+    let size = fs::metadata(&file_path)?.len();
+    fs::remove_file(&file_path)?;
+    removed_files.push((file_path, size));
+  }
+  let freed_bytes: u64 = removed_files.iter().map(|(_, size)| size).sum();
+  // The directories of scoped names, and of copies in DENO_DIR, go along
+  // with their last file.
+  let copies_dirs: Vec<PathBuf> = removed_files
+    .iter()
+    .filter_map(|(path, _)| get_recorded_copies_dir(path, &dirs.sidecar))
+    .chain(get_deno_dir_copies_dir(&dirs.sidecar).ok())
+    .collect();
+  for (path, _) in &removed_files {
+    let mut dir = path.parent();
+    while let Some(parent_dir) = dir {
+      let owned = ((parent_dir.starts_with(&dirs.bin)
+        || parent_dir.starts_with(&dirs.sidecar))
+        && parent_dir != dirs.bin
+        && parent_dir != dirs.sidecar)
+        || copies_dirs
+          .iter()
+          .any(|copies_dir| parent_dir.starts_with(copies_dir));
+      if !owned || fs::remove_dir(parent_dir).is_err() {
+        break;
+      }
+      dir = parent_dir.parent();
+    }
+  }
+
+  if json {
+    let removed: Vec<serde_json::Value> = removed_files
+      .iter()
+      .map(|(path, size)| serde_json::json!({ "path": path, "bytes": size }))
+      .collect();
+    let result = serde_json::json!({
+      "name": name,
+      "removed": removed,
+      "freed_bytes": freed_bytes,
+    });
+    writeln!(out, "{}", serde_json::to_string_pretty(&result)?)?;
+    return Ok(());
+  }
+
+  for (path, size) in &removed_files {
+    writeln!(out, "Removed {} ({} bytes)", path.to_string_lossy(), size)?;
+  }
+  writeln!(
+    out,
+    "✅ Successfully uninstalled {}, freeing {} bytes",
+    name, freed_bytes
+  )?;
+  Ok(())
+}
+
+/// Where an overwritten file is kept: `backup_dir` if given, otherwise next to
+/// the file with a `.bak` extension appended.
+fn get_backup_path(file_path: &Path, backup_dir: Option<&Path>) -> PathBuf {
+  let mut file_name = file_path.file_name().unwrap_or_default().to_owned();
+  match backup_dir {
+    Some(backup_dir) => backup_dir.join(file_name),
+    None => {
+      file_name.push(".bak");
+      file_path.with_file_name(file_name)
+    }
+  }
+}
+
+/// The permission flags of `flags`, so that equal permissions always give the
+/// same executable: in the order of `Flags::to_permission_args`, read, write,
+/// net, env, run, plugin and hrtime, each allowlist sorted and without
+/// duplicates, and left out next to the flag allowing everything. All of the
+/// permissions are granted with `--allow-all` alone.
+fn get_permission_args(flags: &Flags) -> Vec<String> {
+  if flags.allow_read
+    && flags.allow_write
+    && flags.allow_net
+    && flags.allow_env
+    && flags.allow_run
+    && flags.allow_plugin
+    && flags.allow_hrtime
+  {
+    return vec!["--allow-all".to_string()];
+  }
+  let mut flags = flags.clone();
+  if flags.allow_read {
+    flags.read_allowlist.clear();
+  }
+  flags.read_allowlist.sort();
+  flags.read_allowlist.dedup();
+  if flags.allow_write {
+    flags.write_allowlist.clear();
+  }
+  flags.write_allowlist.sort();
+  flags.write_allowlist.dedup();
+  if flags.allow_net {
+    flags.net_allowlist.clear();
+  }
+  flags.net_allowlist.sort();
+  flags.net_allowlist.dedup();
+  flags.to_permission_args()
+}
+
+/// The arguments of `deno run` in the executables, in the order they are
+/// written: the flags, then the module and the arguments of the script.
+fn build_executable_args(
+  flags: &Flags,
+  module_url: &str,
+  args: &[String],
+) -> Vec<String> {
+  let mut executable_args = vec!["run".to_string()];
+  executable_args.extend(build_flag_args(flags));
+  if let Some(config_path) = &flags.config_path {
+    executable_args.push("--config".to_string());
+    executable_args.push(config_path.to_string());
+  }
+  if let Some(import_map_path) = &flags.import_map_path {
+    executable_args.push("--importmap".to_string());
+    executable_args.push(import_map_path.to_string());
+  }
+  if let Some(lock) = &flags.lock {
+    executable_args.push("--lock".to_string());
+    executable_args.push(lock.to_string());
+  }
+  executable_args.push(module_url.to_string());
+  executable_args.extend_from_slice(args);
+  executable_args
+}
+
+/// The flags of `deno run` kept in the metadata, without the files the
+/// executables refer to.
+fn build_flag_args(flags: &Flags) -> Vec<String> {
+  let mut flag_args = get_permission_args(flags);
+  if let Some(ca_file) = &flags.ca_file {
+    flag_args.push("--cert".to_string());
+    flag_args.push(ca_file.to_string())
+  }
+  flag_args.extend(get_log_level_args(flags.log_level));
+
+  if let Some(seed) = flags.seed {
+    flag_args.push("--seed".to_string());
+    flag_args.push(seed.to_string());
+  }
+  if let Some(v8_flags) = &flags.v8_flags {
+    // --seed adds its own V8 flag again when the script is run.
+    let seed_flag = flags.seed.map(|seed| format!("--random-seed={}", seed));
+    let v8_flags: Vec<&str> = v8_flags
+      .iter()
+      .map(String::as_str)
+      .filter(|v8_flag| Some(*v8_flag) != seed_flag.as_deref())
+      .collect();
+    if !v8_flags.is_empty() {
+      flag_args.push(format!("--v8-flags={}", v8_flags.join(",")));
+    }
+  }
+  if let Some(inspect) = flags.inspect {
+    flag_args.push(format!("--inspect={}", inspect));
+  }
+  if let Some(inspect_brk) = flags.inspect_brk {
+    flag_args.push(format!("--inspect-brk={}", inspect_brk));
+  }
+
+  match flags.no_check {
+    NoCheck::All => flag_args.push("--no-check".to_string()),
+    NoCheck::Remote => flag_args.push("--no-check=remote".to_string()),
+    NoCheck::None => {}
+  }
+
+  if flags.unstable {
+    flag_args.push("--unstable".to_string());
+  }
+  if flags.cached_only {
+    flag_args.push("--cached-only".to_string());
+  }
+  flag_args
+}
+
+/// deno run only takes --quiet and the debug and info levels, other levels
+/// are rejected by `install`.
+fn get_log_level_args(log_level: Option<Level>) -> Vec<String> {
+  match log_level {
+    Some(Level::Error) => vec!["--quiet".to_string()],
+    Some(Level::Debug) => vec!["--log-level".to_string(), "debug".to_string()],
+    Some(Level::Info) => vec!["--log-level".to_string(), "info".to_string()],
+    _ => vec![],
+  }
+}
+
+/// The arguments of the executable at `path`. With --names, its name is
+/// inserted at `name_index`, before the arguments of the script.
+fn get_wrapper_args(
+  args: &[String],
+  name_index: Option<usize>,
+  path: &Path,
+) -> Vec<String> {
+  let mut args = args.to_vec();
+  if let Some(name_index) = name_index {
+    // Names have no dots, so the stem is the name without the extension.
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    args.insert(name_index, name.to_string());
+  }
+  args
+}
+
+/// Describes the executables `install` would write, given as their path and
+/// content, without writing them, and the files of an existing installation
+/// they would replace.
+#[allow(clippy::too_many_arguments)]
+fn write_install_preview(
+  name: &str,
+  executables: &[(PathBuf, String)],
+  args: &[String],
+  config_path: Option<&str>,
+  module_url: &str,
+  overwritten_files: &[PathBuf],
+  installed_module_url: Option<&str>,
+  json: bool,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  if json {
+    let executables: Vec<serde_json::Value> = executables
+      .iter()
+      .map(|(path, content)| {
+        serde_json::json!({
+          "path": path.to_string_lossy(),
+          "content": ensure_trailing_newline(content.to_string()),
+        })
+      })
+      .collect();
+    let preview = serde_json::json!({
+      "name": name,
+      "path": executables[0]["path"],
+      "args": args,
+      "config_path": config_path,
+      "content": executables[0]["content"],
+      "executables": executables,
+      "overwritten_files": overwritten_files,
+      "installed_module_url": installed_module_url,
+    });
+    writeln!(out, "{}", serde_json::to_string_pretty(&preview)?)?;
+    return Ok(());
+  }
+
+  writeln!(out, "Would install {}", name)?;
+  writeln!(out, "args: {}", args.join(" "))?;
+  for (path, content) in executables {
+    writeln!(out, "{}:", path.to_string_lossy())?;
+    write!(out, "{}", ensure_trailing_newline(content.to_string()))?;
+  }
+  if !overwritten_files.is_empty() {
+    writeln!(out, "Would overwrite:")?;
+    for path in overwritten_files {
+      writeln!(out, "  {}", path.to_string_lossy())?;
+    }
+  }
+  match installed_module_url {
+    Some(installed_module_url) if installed_module_url != module_url => {
+      writeln!(
+        out,
+        "Would replace {} with {}",
+        installed_module_url, module_url
+      )?;
+    }
+    _ => {}
+  }
+  Ok(())
+}
+
+fn is_same_file(a: &Path, b: &Path) -> bool {
+  match (a.canonicalize(), b.canonicalize()) {
+    (Ok(a), Ok(b)) => a == b,
+    _ => false,
+  }
+}
+
+fn move_file(from: &Path, to: &Path) -> Result<(), Error> {
+  if fs::rename(from, to).is_err() {
+    // Renaming fails across file systems.
+    fs::copy(from, to)?;
+    fs::remove_file(from)?;
+  }
+  Ok(())
+}
+
+/// Whether both stdin and stdout are a terminal, so that a prompt can be
+/// seen and answered.
+#[cfg(not(test))]
+fn is_terminal() -> bool {
+  atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout)
+}
+
+#[cfg(test)]
+fn is_terminal() -> bool {
+  false
+}
+
+/// Asks on `out` whether to go on and overwrite, reading the answer from
+/// `input`. Goes on without asking when not `interactive`.
+fn confirm_overwrite(
+  out: &mut dyn Write,
+  input: &mut dyn BufRead,
+  interactive: bool,
+) -> Result<bool, Error> {
+  if !interactive {
+    return Ok(true);
+  }
+  loop {
+    write!(out, "Continue? [y/n] ")?;
+    out.flush()?;
+    let mut answer = String::new();
+    if input.read_line(&mut answer)? == 0 {
+      return Ok(false);
+    }
+    match answer.trim().to_ascii_lowercase().as_str() {
+      "y" | "yes" => return Ok(true),
+      "n" | "no" => return Ok(false),
+      _ => {}
+    }
+  }
+}
+
+fn write_path_command(
+  dir: &Path,
+  shell: Option<&str>,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  let command = get_path_command(dir, &get_hint_shell(shell));
+  writeln!(out, "    {}", command)?;
+  Ok(())
+}
+
+/// The command adding `dir` to PATH in `shell`, quoted so that a directory
+/// with spaces, like `C:\Program Files\deno\bin`, is one entry.
+fn get_path_command(dir: &Path, shell: &str) -> String {
+  let dir = dir.to_string_lossy();
+  match shell {
+    // The quotes around the whole assignment aren't part of the value.
+    "cmd" => format!("set \"PATH=%PATH%;{}\"", dir),
+    "powershell" | "pwsh" => {
+      format!("$env:PATH += ';{}'", dir.replace('\'', "''"))
+    }
+    "fish" => format!("set -gx PATH \"{}\" $PATH", escape_fish(&dir)),
+    "csh" | "tcsh" => format!("setenv PATH \"{}:$PATH\"", dir),
+    _ => format!("export PATH=\"{}:$PATH\"", escape_sh(&dir)),
+  }
+}
+
+/// Escapes what fish expands in double quotes, where unlike sh a backtick
+/// is taken as is.
+fn escape_fish(arg: &str) -> String {
+  arg
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('$', "\\$")
+}
+
+/// The command running the executable at `path` in `shell`, quoted if the
+/// path has spaces.
+fn get_run_command(path: &Path, shell: &str) -> String {
+  let path = path.to_string_lossy();
+  if !path.contains(char::is_whitespace) {
+    return path.to_string();
+  }
+  match shell {
+    "cmd" => format!("\"{}\"", path),
+    "powershell" | "pwsh" => format!("& '{}'", path.replace('\'', "''")),
+    "fish" => format!("\"{}\"", escape_fish(&path)),
+    _ => format!("\"{}\"", escape_sh(&path)),
+  }
+}
+
+/// The profile, relative to the home directory, that the shells started as
+/// `shell` read, and the shell whose syntax it is in.
+fn get_shell_profile(shell: &str) -> (PathBuf, &'static str) {
+  match shell {
+    "bash" => (PathBuf::from(".bashrc"), "bash"),
+    "zsh" => (PathBuf::from(".zshrc"), "zsh"),
+    "fish" => (
+      Path::new(".config").join("fish").join("config.fish"),
+      "fish",
+    ),
+    "csh" => (PathBuf::from(".cshrc"), "csh"),
+    "tcsh" => (PathBuf::from(".tcshrc"), "tcsh"),
+    _ => (PathBuf::from(".profile"), "sh"),
+  }
+}
+
+/// Appends the command adding `dir` to PATH to the profile of the shell, once.
+/// On Windows, where PATH lives in the registry, the command to run is only
+/// printed.
+fn add_to_path(
+  dir: &Path,
+  shell: Option<&str>,
+  out: &mut dyn Write,
+) -> Result<(), Error> {
+  if cfg!(windows) {
+    writeln!(out, "ℹ️  Add {} to PATH with:", dir.to_string_lossy())?;
+    writeln!(out, "    setx PATH \"%PATH%;{}\"", dir.to_string_lossy())?;
+    writeln!(out, "Then restart your shell")?;
+    return Ok(());
+  }
+  let (profile, profile_shell) = get_shell_profile(&get_hint_shell(shell));
+  let profile = get_home_dir()?.join(profile);
+  let command = get_path_command(dir, profile_shell);
+  let content = match fs::read_to_string(&profile) {
+    Ok(content) => content,
+    Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+    Err(err) => return Err(err),
+  };
+  if content.lines().any(|line| line.trim() == command) {
+    writeln!(
+      out,
+      "ℹ️  {} already adds {} to PATH",
+      profile.to_string_lossy(),
+      dir.to_string_lossy()
+    )?;
+    return Ok(());
+  }
+  if let Some(profile_dir) = profile.parent() {
+    fs::create_dir_all(profile_dir)?;
+  }
+  let mut file = fs::OpenOptions::new()
+    .create(true)
+    .append(true)
+    .open(&profile)?;
+  if !content.is_empty() && !content.ends_with('\n') {
+    writeln!(file)?;
+  }
+  writeln!(file, "{}", command)?;
+  writeln!(
+    out,
+    "✅ Added {} to PATH in {}",
+    dir.to_string_lossy(),
+    profile.to_string_lossy()
+  )?;
+  writeln!(out, "Restart your shell to run it by its name")?;
+  Ok(())
+}
+
+/// PATH entries are compared once resolved, so that a symlink to `dir` or a
+/// path spelled differently counts.
+fn is_in_path(dir: &PathBuf) -> bool {
+  if let Some(paths) = env::var_os("PATH") {
+    for p in env::split_paths(&paths) {
+      if *dir == p || is_same_file(dir, &p) {
+        return true;
+      }
+    }
+  }
+  false
+}
+
+/// The parent of `dir` if it is in PATH though `dir` isn't, as happens when the
+/// installation root was added to PATH instead of its bin directory.
+fn get_parent_in_path(dir: &Path) -> Option<PathBuf> {
+  let parent = dir.parent()?.to_path_buf();
+  if is_in_path(&dir.to_path_buf()) || !is_in_path(&parent) {
+    return None;
+  }
+  Some(parent)
+}
+
+/// Returns the path the copies of the config, import map and lock file are
+/// named after: next to the metadata in `sidecar_dir`, or with `in_deno_dir`
+/// in `$DENO_DIR/installs/<hash>/<name>/`, so they live with the rest of the
+/// Deno state. The hash of `sidecar_dir` keeps the copies of scripts of the
+/// same name in different roots apart.
+fn get_copies_path(
+  sidecar_dir: &Path,
+  name: &str,
+  in_deno_dir: bool,
+) -> Result<PathBuf, Error> {
+  if !in_deno_dir {
+    return Ok(sidecar_dir.join(name));
+  }
+  let file_name = name.rsplit('/').next().unwrap_or(name);
+  Ok(
+    get_deno_dir_copies_dir(sidecar_dir)?
+      .join(name)
+      .join(file_name),
+  )
+}
+
+/// The directory of `$DENO_DIR/installs` for the copies of the scripts of
+/// `sidecar_dir`.
+fn get_deno_dir_copies_dir(sidecar_dir: &Path) -> Result<PathBuf, Error> {
+  let deno_dir = match env::var_os("DENO_DIR") {
+    Some(deno_dir) if !deno_dir.is_empty() => PathBuf::from(deno_dir),
+    _ => return Err(Error::new(
+      ErrorKind::InvalidInput,
+      "--copies-in-deno-dir needs the DENO_DIR environment variable to be set",
+    )),
+  };
+  let deno_dir = if deno_dir.is_absolute() {
+    deno_dir
+  } else {
+    env::current_dir()?.join(deno_dir)
+  };
+  Ok(deno_dir.join(get_copies_dir_suffix(sidecar_dir)))
+}
+
+/// The directory of `$DENO_DIR/installs` that `copy_path` was made in, if it
+/// is a copy for the scripts of `sidecar_dir` there, whatever DENO_DIR
+/// points to now.
+fn get_recorded_copies_dir(
+  copy_path: &Path,
+  sidecar_dir: &Path,
+) -> Option<PathBuf> {
+  let suffix = get_copies_dir_suffix(sidecar_dir);
+  copy_path
+    .ancestors()
+    .find(|dir| dir.ends_with(&suffix))
+    .map(PathBuf::from)
+}
+
+fn get_copies_dir_suffix(sidecar_dir: &Path) -> PathBuf {
+  let root_hash =
+    crate::checksum::gen(&[sidecar_dir.to_string_lossy().as_bytes()]);
+  Path::new("installs").join(&root_hash[..16])
+}
+
+/// Whether the recorded `config_path` of the script at `sidecar_path` is a
+/// copy of its own. Metadata written before `config_copied` was recorded only
+/// has copies next to it.
+fn is_config_copy(
+  config_copied: bool,
+  config_path: &Path,
+  sidecar_path: &Path,
+) -> bool {
+  config_copied || config_path.parent() == sidecar_path.parent()
+}
+
+/// A name given with --config-name is a JSON file name of its own, next to
+/// the default copy.
+fn is_valid_config_name(config_name: &str) -> bool {
+  config_name.len() > ".json".len()
+    && config_name.ends_with(".json")
+    && !config_name.contains(|c| c == '/' || c == '\\' || c == '\0')
+}
+
+fn get_config_file_path(file_path: &PathBuf) -> PathBuf {
+  append_extension(file_path, "tsconfig.json")
+}
+
+fn get_import_map_file_path(file_path: &Path) -> PathBuf {
+  append_extension(file_path, "import_map.json")
+}
+
+fn get_lock_file_path(file_path: &Path) -> PathBuf {
+  append_extension(file_path, "lock.json")
+}
+
+/// Unlike `Path::with_extension`, keeps any dot in the file name: `tool.v2`
+/// becomes `tool.v2.tsconfig.json`, not `tool.tsconfig.json`.
+fn append_extension(file_path: &Path, extension: &str) -> PathBuf {
+  let mut file_path = file_path.as_os_str().to_owned();
+  file_path.push(".");
+  file_path.push(extension);
+  PathBuf::from(file_path)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io;
+  use std::sync::Mutex;
+  use tempfile::TempDir;
+
+  lazy_static! {
+    pub static ref ENV_LOCK: Mutex<()> = Mutex::new(());
+    static ref LOG_MESSAGES: Mutex<Vec<String>> = Mutex::new(vec![]);
+  }
+
+  /// Records the installer's log messages for `captured_log_messages`.
+  struct CapturingLogger;
+
+  impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+      metadata.target().ends_with("installer")
+    }
+
+    fn log(&self, record: &log::Record) {
+      if self.enabled(record.metadata()) {
+        LOG_MESSAGES.lock().unwrap().push(format!(
+          "{} {}",
+          record.level(),
+          record.args()
+        ));
+      }
+    }
+
+    fn flush(&self) {}
+  }
+
+  static CAPTURING_LOGGER: CapturingLogger = CapturingLogger;
+  static INIT_LOGGER: std::sync::Once = std::sync::Once::new();
+
+  /// The log messages captured so far that mention `filter`, as tests run
+  /// in parallel.
+  fn captured_log_messages(filter: &str) -> Vec<String> {
+    INIT_LOGGER.call_once(|| {
+      log::set_logger(&CAPTURING_LOGGER).unwrap();
+      log::set_max_level(log::LevelFilter::Debug);
+    });
+    LOG_MESSAGES
+      .lock()
+      .unwrap()
+      .iter()
+      .filter(|message| message.contains(filter))
+      .cloned()
+      .collect()
+  }
+
+  #[test]
+  fn test_is_remote_url() {
+    assert!(is_remote_url("https://deno.land/std/http/file_server.ts"));
+    assert!(is_remote_url("http://deno.land/std/http/file_server.ts"));
+    assert!(is_remote_url("HTTP://deno.land/std/http/file_server.ts"));
+    assert!(is_remote_url("HTTp://deno.land/std/http/file_server.ts"));
+    assert!(!is_remote_url("file:///dev/deno_std/http/file_server.ts"));
+    assert!(!is_remote_url("./dev/deno_std/http/file_server.ts"));
+    assert!(!is_remote_url("https_server.ts"));
+    assert!(!is_remote_url("jsr:@std/http"));
+  }
+
+  #[test]
+  fn test_has_scheme() {
+    let schemes = &["http", "https", "jsr"];
+    assert!(has_scheme("jsr:@std/http/file-server", schemes));
+    assert!(has_scheme("JSR:@std/http/file-server", schemes));
+    assert!(has_scheme(
+      "https://deno.land/std/http/file_server.ts",
+      schemes
+    ));
+    assert!(!has_scheme("jsr_tool.ts", schemes));
+    assert!(!has_scheme("npm:chalk", schemes));
+    assert!(!has_scheme("C:\\dev\\file_server.ts", schemes));
+  }
+
+  #[test]
+  fn install_infer_name_from_versioned_url() {
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://deno.land/x/tool@1.2.3/mod.ts").unwrap()
+      ),
+      Some("tool".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://deno.land/x/tool@1.2.3/").unwrap()
+      ),
+      Some("tool".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://deno.land/x/tool/mod.ts").unwrap()
+      ),
+      Some("tool".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("https://deno.land/x/tool/").unwrap()),
+      Some("tool".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://deno.land/x/tool@1.0.0/cli.ts").unwrap()
+      ),
+      Some("tool".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://deno.land/x/tool@1.0.0/src/mod.ts").unwrap()
+      ),
+      Some("tool".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://deno.land/x/tool@1.0.0/server.ts").unwrap()
+      ),
+      Some("server".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/x/tool@1.0.0/src/mod.ts").unwrap()
+      ),
+      Some("tool".to_string())
+    );
+  }
+
+  #[test]
+  fn install_infer_name_from_generic_dir() {
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/foo/src/mod.ts").unwrap()
+      ),
+      Some("foo".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/foo/dist/main.ts").unwrap()
+      ),
+      Some("foo".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("file:///foo/lib/index.js").unwrap()),
+      Some("foo".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/foo/server.ts").unwrap()
+      ),
+      Some("server".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/foo/src/server.ts").unwrap()
+      ),
+      Some("server".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/src/mod.ts").unwrap()
+      ),
+      Some("src".to_string())
+    );
+  }
+
+  #[test]
+  fn install_infer_name_from_url() {
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/abc/server.ts").unwrap()
+      ),
       Some("server".to_string())
     );
     assert_eq!(
-      infer_name_from_url(
-        &Url::parse("https://example.com/abc/main.ts").unwrap()
-      ),
-      Some("abc".to_string())
+      infer_name_from_url(
+        &Url::parse("https://example.com/abc/main.ts").unwrap()
+      ),
+      Some("abc".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/abc/mod.ts").unwrap()
+      ),
+      Some("abc".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/abc/index.ts").unwrap()
+      ),
+      Some("abc".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/abc/cli.ts").unwrap()
+      ),
+      Some("abc".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("https://example.com/main.ts").unwrap()),
+      Some("main".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("https://example.com").unwrap()),
+      None
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("file:///abc/server.ts").unwrap()),
+      Some("server".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("file:///abc/main.ts").unwrap()),
+      Some("abc".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("file:///main.ts").unwrap()),
+      Some("main".to_string())
+    );
+    assert_eq!(infer_name_from_url(&Url::parse("file:///").unwrap()), None);
+    assert_eq!(
+      infer_name_from_url(&Url::parse("file:///.env.ts").unwrap()),
+      Some("env".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("file:///.hidden.ts").unwrap()),
+      Some("hidden".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(&Url::parse("file:///.ts").unwrap()),
+      None
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/abc/my%2Dtool.ts?v=1#top").unwrap()
+      ),
+      Some("my-tool".to_string())
+    );
+    assert_eq!(
+      infer_name_from_url(
+        &Url::parse("https://example.com/abc/my%20tool.ts?v=1").unwrap()
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn install_infer_name_reasons() {
+    assert_eq!(
+      infer_name(&Url::parse("https://example.com/abc/server.ts").unwrap()),
+      Ok("server".to_string())
+    );
+    assert_eq!(
+      infer_name(&Url::parse("https://example.com").unwrap()),
+      Err(NameInferenceError::OnlyHost)
+    );
+    assert_eq!(
+      infer_name(&Url::parse("https://example.com/").unwrap()),
+      Err(NameInferenceError::OnlyHost)
+    );
+    assert_eq!(
+      infer_name(&Url::parse("file:///").unwrap()),
+      Err(NameInferenceError::EmptyPath)
+    );
+    assert_eq!(
+      infer_name(&Url::parse("file:///.ts").unwrap()),
+      Err(NameInferenceError::ExtensionOnly(".ts".to_string()))
+    );
+    assert_eq!(
+      infer_name(&Url::parse("https://example.com/.ts").unwrap()),
+      Err(NameInferenceError::ExtensionOnly(".ts".to_string()))
+    );
+    assert_eq!(
+      infer_name(&Url::parse("https://example.com/1password.ts").unwrap()),
+      Err(NameInferenceError::UnsanitizableStem(
+        "1password".to_string()
+      ))
+    );
+    assert_eq!(
+      infer_name(&Url::parse("https://example.com/my%20tool.ts").unwrap()),
+      Err(NameInferenceError::UnsanitizableStem("my tool".to_string()))
+    );
+  }
+
+  #[test]
+  fn install_infer_name_failure_guidance() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "https://example.com".to_string(),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("the URL has no path, only a host"));
+    assert!(err.contains("--name"));
+  }
+
+  #[test]
+  fn install_config_file_path_with_dots() {
+    let _guard = ENV_LOCK.lock().ok();
+    let bin_dir = PathBuf::from("/usr/local/bin");
+    assert_eq!(
+      get_config_file_path(&bin_dir.join("tool.v2")),
+      bin_dir.join("tool.v2.tsconfig.json")
+    );
+    assert_eq!(
+      get_config_file_path(&bin_dir.join("tool")),
+      bin_dir.join("tool.tsconfig.json")
+    );
+    assert_eq!(
+      InstallMetadata::filename(&bin_dir.join("tool.v2")),
+      bin_dir.join("tool.v2.metadata.json")
+    );
+    assert_eq!(
+      get_executable_path_for(ExecutableKind::Cmd, &bin_dir, "tool.v2"),
+      bin_dir.join("tool.v2.cmd")
+    );
+  }
+
+  #[test]
+  fn install_reserved_names() {
+    for name in &["deno", "Deno", "run", "install", "fmt", "test", "cache"] {
+      let err = validate_name(name).unwrap_err();
+      assert_eq!(
+        err.to_string(),
+        format!(
+          "Executable name {} is reserved, it would shadow deno or one of its subcommands",
+          name
+        )
+      );
+    }
+    assert!(validate_name("deno-fmt").is_ok());
+    assert!(validate_name("runner").is_ok());
+  }
+
+  #[test]
+  fn install_name_from_subcommand() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    for subcommand in &["serve", "build"] {
+      install(
+        Flags::default(),
+        InstallFlags {
+          module_url: "https://deno.land/x/cliffy/cli.ts".to_string(),
+          args: vec![subcommand.to_string()],
+          root: Some(temp_dir.path().to_path_buf()),
+          name_from_subcommand: true,
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      )
+      .expect("Install failed");
+    }
+    for name in &["cliffy-serve", "cliffy-build"] {
+      let mut file_path = bin_dir.join(name);
+      if cfg!(windows) {
+        file_path = file_path.with_extension("cmd");
+      }
+      assert!(file_path.exists());
+    }
+    assert_eq!(sanitize_subcommand("db:migrate"), "db-migrate");
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "https://deno.land/x/cliffy/cli.ts".to_string(),
+        args: vec!["--verbose".to_string()],
+        root: Some(temp_dir.path().to_path_buf()),
+        name_from_subcommand: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn retry_transient_errors() {
+    let mut calls = 0;
+    let result = retry_transient_with(TRANSIENT_RETRY_ATTEMPTS, || {
+      calls += 1;
+      if calls <= 2 {
+        Err(Error::new(ErrorKind::PermissionDenied, "file in use"))
+      } else {
+        Ok(calls)
+      }
+    });
+    assert_eq!(result.unwrap(), 3);
+
+    let mut calls = 0;
+    let result: Result<(), Error> =
+      retry_transient_with(TRANSIENT_RETRY_ATTEMPTS, || {
+        calls += 1;
+        Err(Error::new(ErrorKind::NotFound, "no such file"))
+      });
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(calls, 1);
+
+    let mut calls = 0;
+    let result: Result<(), Error> =
+      retry_transient_with(TRANSIENT_RETRY_ATTEMPTS, || {
+        calls += 1;
+        Err(Error::new(ErrorKind::PermissionDenied, "file in use"))
+      });
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::PermissionDenied);
+    assert_eq!(calls, TRANSIENT_RETRY_ATTEMPTS);
+  }
+
+  #[test]
+  fn install_name_with_path_separator() {
+    for name in &["a/b", "a\\b", "../a"] {
+      let err = validate_name(name).unwrap_err();
+      assert_eq!(err.code(), "name_with_path_separator");
+      assert_eq!(
+        err.to_string(),
+        "Executable names cannot contain path separators; did you mean --root?"
+      );
+    }
+    let err = validate_name("a!b").unwrap_err();
+    assert_eq!(err.code(), "invalid_name");
+    assert_eq!(err.to_string(), "Invalid executable name: a!b");
+  }
+
+  #[test]
+  fn install_empty_name() {
+    for name in &["", "   "] {
+      let err = validate_name(name).unwrap_err();
+      assert_eq!(err.code(), "empty_name");
+      assert_eq!(err.to_string(), "Executable name cannot be empty");
+    }
+
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some(" echo ".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let mut file_path = bin_dir.join("echo");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+    assert!(file_path.exists());
+  }
+
+  #[test]
+  fn install_scoped_name() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("@scope/echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let file_path = get_executable_path(&bin_dir.join("@scope"), "echo_test");
+    assert_eq!(result.path, file_path);
+    assert!(file_path.exists());
+    assert!(bin_dir.join("@scope/echo_test.metadata.json").exists());
+    if cfg!(windows) {
+      assert!(bin_dir.join("@scope").join("echo_test.cmd").exists());
+    }
+
+    uninstall(
+      "@scope/echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .expect("Uninstall failed");
+    assert!(!bin_dir.join("@scope").exists());
+  }
+
+  #[test]
+  fn install_invalid_scoped_name() {
+    for name in &["@1scope/tool", "@/tool", "@scope/", "@scope/a/b"] {
+      let err = validate_name(name).unwrap_err();
+      assert_eq!(
+        err.to_string(),
+        format!("Invalid executable name: {}", name)
+      );
+    }
+    assert_eq!(
+      validate_name("scope/a").unwrap_err().code(),
+      "name_with_path_separator"
+    );
+    assert!(validate_name("@scope/deno").is_ok());
+  }
+
+  #[test]
+  fn install_basic() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let temp_dir_str = temp_dir.path().to_string_lossy().to_string();
+    // NOTE: this test overrides environmental variables
+    // don't add other tests in this file that mess with "HOME" and "USEPROFILE"
+    // otherwise transient failures are possible because tests are run in parallel.
+    // It means that other test can override env vars when this test is running.
+    let original_home = env::var_os("HOME");
+    let original_user_profile = env::var_os("HOME");
+    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
+    let original_xdg_bin_home = env::var_os("XDG_BIN_HOME");
+    let original_xdg_data_home = env::var_os("XDG_DATA_HOME");
+    env::set_var("HOME", &temp_dir_str);
+    env::set_var("USERPROFILE", &temp_dir_str);
+    env::set_var("DENO_INSTALL_ROOT", "");
+    env::remove_var("XDG_BIN_HOME");
+    env::remove_var("XDG_DATA_HOME");
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = temp_dir.path().join(".deno/bin/echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    assert!(file_path.exists());
+
+    let content = fs::read_to_string(file_path).unwrap();
+    // It's annoying when shell scripts don't have NL at the end.
+    assert_eq!(content.chars().last().unwrap(), '\n');
+
+    assert!(content
+      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
+    if let Some(home) = original_home {
+      env::set_var("HOME", home);
+    }
+    if let Some(user_profile) = original_user_profile {
+      env::set_var("USERPROFILE", user_profile);
+    }
+    if let Some(install_root) = original_install_root {
+      env::set_var("DENO_INSTALL_ROOT", install_root);
+    }
+    if let Some(xdg_bin_home) = original_xdg_bin_home {
+      env::set_var("XDG_BIN_HOME", xdg_bin_home);
+    }
+    if let Some(xdg_data_home) = original_xdg_data_home {
+      env::set_var("XDG_DATA_HOME", xdg_data_home);
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn install_root_xdg_data_home() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let xdg_data_home = temp_dir.path().join("share");
+    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
+    let original_xdg_data_home = env::var_os("XDG_DATA_HOME");
+    env::remove_var("DENO_INSTALL_ROOT");
+    env::set_var("XDG_DATA_HOME", &xdg_data_home);
+
+    let xdg_root = get_installer_root();
+    env::set_var("DENO_INSTALL_ROOT", temp_dir.path());
+    let env_root = get_installer_root();
+
+    match original_install_root {
+      Some(value) => env::set_var("DENO_INSTALL_ROOT", value),
+      None => env::remove_var("DENO_INSTALL_ROOT"),
+    }
+    match original_xdg_data_home {
+      Some(value) => env::set_var("XDG_DATA_HOME", value),
+      None => env::remove_var("XDG_DATA_HOME"),
+    }
+    assert_eq!(xdg_root.unwrap(), xdg_data_home.join("deno"));
+    assert_eq!(env_root.unwrap(), temp_dir.path().canonicalize().unwrap());
+  }
+
+  #[test]
+  fn install_unstable() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+
+    install(
+      Flags {
+        unstable: true,
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    assert!(file_path.exists());
+
+    let content = fs::read_to_string(file_path).unwrap();
+    println!("this is the file path {:?}", content);
+    assert!(content.contains(
+      r#""run" "--unstable" "http://localhost:4545/cli/tests/echo_server.ts"#
+    ));
+  }
+
+  #[test]
+  fn install_seed_v8_flags() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags {
+        seed: Some(250),
+        v8_flags: Some(vec![
+          "--expose-gc".to_string(),
+          "--stack-trace-limit=20 --harmony".to_string(),
+          "--random-seed=250".to_string(),
+        ]),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains(
+      r#""run" "--seed" "250" "--v8-flags=--expose-gc,--stack-trace-limit=20 --harmony" "http://localhost:4545/cli/tests/echo_server.ts"#
+    ));
+  }
+
+  #[test]
+  fn install_inspect() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags {
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        inspect_brk: Some("[::1]:9230".parse().unwrap()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains(
+      r#""run" "--inspect=127.0.0.1:9229" "--inspect-brk=[::1]:9230" "http://localhost:4545/cli/tests/echo_server.ts"#
+    ));
+  }
+
+  #[test]
+  fn install_inferred_name() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("echo_server");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    assert!(file_path.exists());
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content
+      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
+  }
+
+  #[test]
+  fn install_inferred_name_from_parent() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/subdir/main.ts"
+          .to_string(),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("subdir");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    assert!(file_path.exists());
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content
+      .contains(r#""run" "http://localhost:4545/cli/tests/subdir/main.ts""#));
+  }
+
+  #[test]
+  fn install_custom_dir_option() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    assert!(file_path.exists());
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content
+      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
+  }
+
+  #[test]
+  fn install_custom_dir_env_var() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
+    env::set_var("DENO_INSTALL_ROOT", temp_dir.path().to_path_buf());
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    assert!(file_path.exists());
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content
+      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
+    if let Some(install_root) = original_install_root {
+      env::set_var("DENO_INSTALL_ROOT", install_root);
+    }
+  }
+
+  #[test]
+  fn install_with_flags() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+
+    install(
+      Flags {
+        allow_net: true,
+        allow_read: true,
+        no_check: NoCheck::All,
+        log_level: Some(Level::Error),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--foobar".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    assert!(file_path.exists());
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content.contains(r#""run" "--allow-read" "--allow-net" "--quiet" "--no-check" "http://localhost:4545/cli/tests/echo_server.ts" "--foobar""#));
+  }
+
+  #[test]
+  fn install_local_module() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+    let local_module = temp_dir.path().join("echo_server.ts");
+    fs::write(&local_module, "").unwrap();
+    let local_module_url =
+      Url::from_file_path(local_module.canonicalize().unwrap()).unwrap();
+    let local_module_str = local_module.to_string_lossy();
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: local_module_str.to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    assert!(file_path.exists());
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content.contains(&local_module_url.to_string()));
+  }
+
+  #[test]
+  fn verify_install_missing_module() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let local_module = temp_dir.path().join("echo_server.ts");
+    fs::write(&local_module, "").unwrap();
+    let local_module_url =
+      Url::from_file_path(local_module.canonicalize().unwrap()).unwrap();
+    let root = Some(temp_dir.path().to_path_buf());
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: local_module.to_string_lossy().to_string(),
+        name: Some("echo_test".to_string()),
+        root: root.clone(),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_remote".to_string()),
+        root: root.clone(),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let report = verify_install("echo_test", root.clone(), None).unwrap();
+    assert_eq!(report.status, TargetStatus::Ok);
+    assert_eq!(
+      verify_install("echo_remote", root.clone(), None)
+        .unwrap()
+        .status,
+      TargetStatus::Remote
+    );
+
+    fs::remove_file(&local_module).unwrap();
+    assert_eq!(
+      verify_install("echo_test", root.clone(), None).unwrap(),
+      VerifyReport {
+        name: "echo_test".to_string(),
+        module_url: local_module_url.to_string(),
+        status: TargetStatus::Missing,
+      }
+    );
+    let mut out = vec![];
+    doctor(root.clone(), None, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains(&format!(
+      "⚠️  echo_test runs {}, which no longer exists",
+      local_module_url
+    )));
+    assert!(verify_install("nope", root, None).is_err());
+  }
+
+  #[test]
+  fn install_ephemeral() {
+    let _guard = ENV_LOCK.lock().ok();
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        ephemeral: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let bin_dir = result.path.parent().unwrap().to_path_buf();
+    let root = bin_dir.parent().unwrap().to_path_buf();
+    assert!(result.installed);
+    assert!(result.path.is_file());
+    assert_eq!(result.path, get_executable_path(&bin_dir, "echo_test"));
+    let content = fs::read_to_string(&result.path).unwrap();
+    assert!(content.contains("http://localhost:4545/cli/tests/echo_server.ts"));
+    #[cfg(not(windows))]
+    assert_eq!(
+      fs::metadata(&result.path).unwrap().permissions().mode() & 0o777,
+      0o755
+    );
+    if let Ok(default_root) = get_installer_root() {
+      assert!(!result.path.starts_with(default_root));
+    }
+    assert!(root
+      .file_name()
+      .unwrap()
+      .to_string_lossy()
+      .starts_with("deno-install-"));
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains(&format!(
+      "ℹ️  Installed into {}, try it out with:",
+      bin_dir.to_string_lossy()
+    )));
+    fs::remove_dir_all(root).unwrap();
+  }
+
+  #[test]
+  fn install_ephemeral_failure_leaves_no_dir() {
+    let _guard = ENV_LOCK.lock().ok();
+    let get_ephemeral_dirs = || -> HashSet<PathBuf> {
+      fs::read_dir(env::temp_dir())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+          entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("deno-install-")
+        })
+        .map(|entry| entry.path())
+        .collect()
+    };
+    let ephemeral_dirs = get_ephemeral_dirs();
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("Echo Test".to_string()),
+        ephemeral: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    assert!(result.is_err());
+    assert_eq!(get_ephemeral_dirs(), ephemeral_dirs);
+  }
+
+  #[test]
+  fn install_package_name() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let module_dir = temp_dir.path().join("tool");
+    fs::create_dir(&module_dir).unwrap();
+    fs::write(module_dir.join("main.ts"), "").unwrap();
+    fs::write(
+      module_dir.join("deno.json"),
+      "{\n  // the package\n  \"name\": \"mytool\"\n}",
+    )
+    .unwrap();
+    let install_flags = InstallFlags {
+      module_url: module_dir.to_string_lossy().to_string(),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+
+    let result =
+      install(Flags::default(), install_flags.clone(), &mut io::sink())
+        .expect("Install failed");
+    assert_eq!(result.name, "mytool");
+    assert!(get_executable_path(&bin_dir, "mytool").exists());
+
+    // An explicit name still wins.
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("othertool".to_string()),
+        ..install_flags.clone()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    assert_eq!(result.name, "othertool");
+
+    // As does the directory name over an invalid one.
+    fs::write(module_dir.join("deno.json"), "{\"name\": \"my tool\"}").unwrap();
+    let result = install(Flags::default(), install_flags, &mut io::sink())
+      .expect("Install failed");
+    assert_eq!(result.name, "tool");
+  }
+
+  #[test]
+  fn install_overwrote() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+    let result =
+      install(Flags::default(), install_flags.clone(), &mut io::sink())
+        .expect("Install failed");
+    assert!(result.installed);
+    assert!(!result.overwrote);
+
+    let result = install(
+      Flags {
+        allow_net: true,
+        ..Flags::default()
+      },
+      InstallFlags {
+        force: true,
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    assert!(result.installed);
+    assert!(result.overwrote);
+  }
+
+  #[test]
+  fn install_concurrently() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let handles: Vec<thread::JoinHandle<Result<InstallResult, InstallError>>> =
+      vec!["echo_test", "cat_test"]
+        .into_iter()
+        .map(|name| {
+          let root = temp_dir.path().to_path_buf();
+          thread::spawn(move || {
+            install(
+              Flags::default(),
+              InstallFlags {
+                module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+                  .to_string(),
+                name: Some(name.to_string()),
+                root: Some(root),
+                ..InstallFlags::default()
+              },
+              &mut io::sink(),
+            )
+          })
+        })
+        .collect();
+    for handle in handles {
+      handle.join().unwrap().expect("Install failed");
+    }
+
+    for name in &["echo_test", "cat_test"] {
+      let mut file_path = bin_dir.join(name);
+      if cfg!(windows) {
+        file_path = file_path.with_extension("cmd");
+      }
+      assert!(file_path.exists());
+    }
+    assert!(bin_dir.join(".install.lock").exists());
+  }
+
+  #[test]
+  fn install_lock_left_out_after_uninstall() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let root = Some(temp_dir.path().to_path_buf());
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: root.clone(),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    uninstall(
+      "echo_test".to_string(),
+      root.clone(),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .expect("Uninstall failed");
+
+    let lock_path = temp_dir.path().join("bin").join(".install.lock");
+    assert!(lock_path.exists());
+    assert!(list_installed(root.clone(), None).unwrap().is_empty());
+    let mut out: Vec<u8> = vec![];
+    doctor(root.clone(), None, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(!out.contains(".install.lock"));
+    assert!(prune(root, None).unwrap().is_empty());
+    assert!(lock_path.exists());
+  }
+
+  #[test]
+  fn install_completions() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let root = Some(temp_dir.path().to_path_buf());
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: root.clone(),
+        completions: Some(("bash".to_string(), "completions".to_string())),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let completions_path = temp_dir
+      .path()
+      .canonicalize()
+      .unwrap()
+      .join("completions")
+      .join("echo_test.bash");
+    let content = fs::read_to_string(&completions_path).unwrap();
+    assert!(content.contains(&format!(
+      "source <({} completions bash)",
+      get_run_command(&result.path, "bash")
+    )));
+    assert!(!bin_dir.join("completions").exists());
+    let metadata = InstallMetadata::read(&bin_dir.join("echo_test")).unwrap();
+    assert_eq!(
+      metadata.completions,
+      Some(("bash".to_string(), "completions".to_string()))
+    );
+
+    uninstall(
+      "echo_test".to_string(),
+      root.clone(),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .unwrap();
+    assert!(!completions_path.exists());
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root,
+        completions: Some(("csh".to_string(), "completions".to_string())),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn install_dev() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let local_module = temp_dir.path().join("echo_server.ts");
+    fs::write(&local_module, "").unwrap();
+    let root = Some(temp_dir.path().to_path_buf());
+
+    for (name, dev) in &[("echo_dev", true), ("echo_test", false)] {
+      install(
+        Flags::default(),
+        InstallFlags {
+          module_url: local_module.to_string_lossy().to_string(),
+          name: Some(name.to_string()),
+          root: root.clone(),
+          dev: *dev,
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      )
+      .expect("Install failed");
+    }
+
+    assert!(
+      InstallMetadata::read(&bin_dir.join("echo_dev"))
+        .unwrap()
+        .dev
+    );
+    assert!(
+      !InstallMetadata::read(&bin_dir.join("echo_test"))
+        .unwrap()
+        .dev
+    );
+    assert_eq!(
+      list_installed(root.clone(), None).unwrap(),
+      vec![
+        InstalledScript {
+          name: "echo_dev".to_string(),
+          dev: true,
+        },
+        InstalledScript {
+          name: "echo_test".to_string(),
+          dev: false,
+        },
+      ]
+    );
+    let mut out: Vec<u8> = vec![];
+    list(root.clone(), None, false, &mut out).unwrap();
+    assert_eq!(
+      String::from_utf8(out).unwrap(),
+      "echo_dev (dev)\necho_test\n"
+    );
+    let (_, install_flags) =
+      get_reinstall_flags("echo_dev", root.clone(), None).unwrap();
+    assert!(install_flags.dev);
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_remote".to_string()),
+        root,
+        dev: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn install_file_url() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let local_module = temp_dir.path().join("echo_server.ts");
+    fs::write(&local_module, "").unwrap();
+    let local_module_url =
+      Url::from_file_path(local_module.canonicalize().unwrap()).unwrap();
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: local_module_url.to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let shim_info =
+      inspect_shim(&get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert_eq!(shim_info.module_url, local_module_url.to_string());
+
+    let missing_url = Url::from_file_path(
+      temp_dir.path().canonicalize().unwrap().join("nope.ts"),
+    )
+    .unwrap();
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: missing_url.to_string(),
+        name: Some("echo_test2".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, InstallError::ModuleNotFound(_)));
+  }
+
+  #[test]
+  fn install_local_module_not_found() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "./nope.ts".to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    let missing_path = env::current_dir().unwrap().join("./nope.ts");
+    assert_eq!(
+      err.to_string(),
+      format!("Module not found: {}", missing_path.to_string_lossy())
+    );
+    assert!(!get_executable_path(&bin_dir, "echo_test").exists());
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn install_local_module_symlink() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let local_module = temp_dir.path().join("echo_server.ts");
+    fs::write(&local_module, "").unwrap();
+    let link_path = temp_dir.path().join("link.ts");
+    std::os::unix::fs::symlink(&local_module, &link_path).unwrap();
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: link_path.to_string_lossy().to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    let local_module_url =
+      Url::from_file_path(local_module.canonicalize().unwrap()).unwrap();
+    assert!(content.contains(&local_module_url.to_string()));
+    assert!(!content.contains("link.ts"));
+  }
+
+  #[test]
+  fn install_force() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+    assert!(file_path.exists());
+
+    // No force. Install failed.
+    let no_force_result = install(
+      Flags::default(),
+      InstallFlags {
+        // using a different URL
+        module_url: "http://localhost:4545/cli/tests/cat.ts".to_string(),
+        args: vec![],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    assert!(no_force_result.is_err());
+    assert!(no_force_result
+      .unwrap_err()
+      .to_string()
+      .contains("Existing installation found"));
+    // Assert not modified
+    let file_content = fs::read_to_string(&file_path).unwrap();
+    assert!(file_content.contains("echo_server.ts"));
+
+    // Force. Install success.
+    let force_result = install(
+      Flags::default(),
+      InstallFlags {
+        // using a different URL
+        module_url: "http://localhost:4545/cli/tests/cat.ts".to_string(),
+        args: vec![],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        force: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    assert!(force_result.is_ok());
+    // Assert modified
+    let file_content_2 = fs::read_to_string(&file_path).unwrap();
+    assert!(file_content_2.contains("cat.ts"));
+  }
+
+  #[test]
+  fn install_with_config() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    let config = "{}";
+    let mut config_file = File::create(&config_file_path).unwrap();
+    let result = config_file.write_all(config.as_bytes());
+    assert!(result.is_ok());
+
+    let result = install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/cat.ts".to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        force: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    eprintln!("result {:?}", result);
+    let result = result.unwrap();
+    assert!(result.installed);
+
+    let config_file_name = "echo_test.tsconfig.json";
+
+    let file_path = bin_dir.join(config_file_name.to_string());
+    assert_eq!(result.config_path.as_ref(), Some(&file_path));
+    assert!(file_path.exists());
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content == "{}");
+  }
+
+  #[test]
+  fn install_logs_steps() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let temp_dir_name = temp_dir.path().to_string_lossy().to_string();
+    assert!(captured_log_messages(&temp_dir_name).is_empty());
+
+    install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap();
+
+    let mut file_path = bin_dir.join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+    let messages = captured_log_messages(&temp_dir_name);
+    assert!(messages.contains(&format!(
+      "DEBUG Writing shim {}",
+      file_path.to_string_lossy()
+    )));
+    assert!(messages.iter().any(|message| message.starts_with(&format!(
+      "DEBUG Copying config {}",
+      config_file_path.canonicalize().unwrap().to_string_lossy()
+    ))));
+  }
+
+  #[test]
+  fn install_force_up_to_date() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let flags = Flags {
+      config_path: Some(config_file_path.to_string_lossy().to_string()),
+      ..Flags::default()
+    };
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+    install(flags.clone(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let config_copy_path = bin_dir.join("echo_test.tsconfig.json");
+    let modified =
+      |path: &Path| fs::metadata(path).unwrap().modified().unwrap();
+    let shim_modified = modified(&file_path);
+    let config_modified = modified(&config_copy_path);
+    // Leaves a rewrite the time to show in the modification times.
+    thread::sleep(std::time::Duration::from_millis(100));
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(flags.clone(), install_flags.clone(), &mut out)
+      .expect("Install failed");
+    assert!(!result.installed);
+    let output = String::from_utf8(out).unwrap();
+    assert_eq!(output, "✅ echo_test is already up to date\n");
+    assert_eq!(modified(&file_path), shim_modified);
+    assert_eq!(modified(&config_copy_path), config_modified);
+
+    // A changed config is copied again.
+    fs::write(&config_file_path, "{ }").unwrap();
+    let result =
+      install(flags, install_flags, &mut io::sink()).expect("Install failed");
+    assert!(result.installed);
+    assert_eq!(fs::read_to_string(&config_copy_path).unwrap(), "{ }");
+  }
+
+  #[test]
+  fn install_name_case_collision() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("Tool".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("tool".to_string()),
+        ..install_flags.clone()
+      },
+      &mut io::sink(),
+    );
+    assert!(result
+      .unwrap_err()
+      .to_string()
+      .contains("Existing installation found"));
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("tool".to_string()),
+        force: true,
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let executable_names = |name: &str| -> Vec<String> {
+      let file_name = get_executable_path(&bin_dir, name)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+      fs::read_dir(&bin_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .filter(|entry_name| *entry_name == file_name)
+        .collect()
+    };
+    assert_eq!(executable_names("tool").len(), 1);
+    assert!(executable_names("Tool").is_empty());
+  }
+
+  #[test]
+  fn install_force_keep_existing_config() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let flags = Flags {
+      config_path: Some(config_file_path.to_string_lossy().to_string()),
+      ..Flags::default()
+    };
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+    install(flags.clone(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+
+    let config_copy_path = bin_dir.join("echo_test.tsconfig.json");
+    let tuned_config = r#"{ "compilerOptions": { "strict": false } }"#;
+    fs::write(&config_copy_path, tuned_config).unwrap();
+
+    let result = install(
+      flags,
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/cat.ts".to_string(),
+        force: true,
+        keep_existing_config: true,
+        ..install_flags
+      },
+      &mut io::sink(),
+    );
+    assert!(result.is_ok());
+
+    let mut file_path = bin_dir.join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content.contains("cat.ts"));
+    assert!(content.contains("--config"));
+    let config_content = fs::read_to_string(config_copy_path).unwrap();
+    assert_eq!(config_content, tuned_config);
+  }
+
+  #[test]
+  fn install_prints_run_command() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.canonicalize().unwrap().join("echo_test");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+
+    // The temp dir is not on PATH, so the full path is needed to run it.
+    let output = String::from_utf8(out).unwrap();
+    assert!(
+      output.contains(&format!("Run it with: {}", file_path.to_string_lossy()))
+    );
+  }
+
+  #[test]
+  fn install_prints_run_command_on_path() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+    let original_path = env::var_os("PATH");
+    env::set_var("PATH", bin_dir.canonicalize().unwrap());
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut out,
+    );
+    if let Some(path) = original_path {
+      env::set_var("PATH", path);
+    }
+    result.expect("Install failed");
+
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("Run it with: echo_test\n"));
+    assert!(!output.contains("to PATH"));
+  }
+
+  #[test]
+  fn is_in_path_resolves_entries() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+    let bin_dir = bin_dir.canonicalize().unwrap();
+    let original_path = env::var_os("PATH");
+
+    let mut entries = vec![
+      temp_dir.path().join("bin").join("."),
+      temp_dir.path().join("bin").join("..").join("bin"),
+    ];
+    #[cfg(unix)]
+    {
+      let link_path = temp_dir.path().join("link");
+      std::os::unix::fs::symlink(&bin_dir, &link_path).unwrap();
+      entries.push(link_path);
+    }
+    let mut results = vec![];
+    for entry in &entries {
+      env::set_var("PATH", entry);
+      results.push(is_in_path(&bin_dir));
+    }
+    env::set_var("PATH", temp_dir.path().join("missing"));
+    let missing_result = is_in_path(&bin_dir);
+    match original_path {
+      Some(path) => env::set_var("PATH", path),
+      None => env::remove_var("PATH"),
+    }
+
+    assert!(results.iter().all(|result| *result), "{:?}", results);
+    assert!(!missing_result);
+  }
+
+  #[test]
+  fn install_parse_module_hints() {
+    let source = "#!/usr/bin/env -S deno run
+// Copyright the authors.
+// deno-install: --allow-net --allow-read=/tmp
+//deno-install: --allow-env
+
+// deno-install: --allow-run
+import { serve } from \"./server.ts\";
+// deno-install: --allow-write
+";
+    assert_eq!(
+      parse_module_hints(source),
+      vec![
+        "--allow-net",
+        "--allow-read=/tmp",
+        "--allow-env",
+        "--allow-run"
+      ]
+    );
+    assert!(parse_module_hints("console.log(1);").is_empty());
+  }
+
+  #[test]
+  fn install_with_module_hints() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let module_path = temp_dir.path().join("tool.ts");
+    fs::write(
+      &module_path,
+      "// deno-install: --allow-net --allow-read --allow-hrtime --foo\n",
+    )
+    .unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags {
+        read_allowlist: vec![PathBuf::from("/tmp")],
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: module_path.to_string_lossy().to_string(),
+        root: Some(temp_dir.path().to_path_buf()),
+        use_module_hints: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let mut file_path = bin_dir.join("tool");
+    if cfg!(windows) {
+      file_path = file_path.with_extension("cmd");
+    }
+    let content = fs::read_to_string(file_path).unwrap();
+    // The allowlist passed by the user wins over the broad read hint.
+    assert!(content.contains(
+      r#""run" "--allow-read=/tmp" "--allow-net" "--allow-hrtime" "file:"#
+    ));
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("Ignoring unsupported module hint: --foo"));
+  }
+
+  #[test]
+  fn install_info_json() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+
+    install(
+      Flags {
+        allow_net: true,
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--foobar".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let mut out: Vec<u8> = vec![];
+    info(
+      "echo_test",
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      true,
+      &mut out,
+    )
+    .expect("info failed");
+
+    let metadata: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(metadata["name"], "echo_test");
+    assert_eq!(
+      metadata["module_url"],
+      "http://localhost:4545/cli/tests/echo_server.ts"
+    );
+    let args: Vec<&str> = metadata["args"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|a| a.as_str().unwrap())
+      .collect();
+    assert!(args.contains(&"--allow-net"));
+    assert!(args.contains(&"--foobar"));
+    assert!(metadata["config_path"]
+      .as_str()
+      .unwrap()
+      .ends_with("echo_test.tsconfig.json"));
+    assert_eq!(metadata["version"], crate::version::DENO);
+    assert!(metadata["installed_at"].as_u64().unwrap() > 0);
+    assert_eq!(metadata["checksum"].as_str().unwrap().len(), 64);
+
+    let not_installed = info(
+      "missing",
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      true,
+      &mut out,
+    );
+    assert_eq!(not_installed.unwrap_err().kind(), ErrorKind::NotFound);
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn install_xdg_bin_home() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let xdg_bin_home = temp_dir.path().join("xdg_bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
+    let original_xdg_bin_home = env::var_os("XDG_BIN_HOME");
+    env::set_var("DENO_INSTALL_ROOT", "");
+    env::set_var("XDG_BIN_HOME", &xdg_bin_home);
+
+    let result = install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    let mut out: Vec<u8> = vec![];
+    let info_result = info("echo_test", None, None, true, &mut out);
+    if let Some(install_root) = original_install_root {
+      env::set_var("DENO_INSTALL_ROOT", install_root);
+    }
+    match original_xdg_bin_home {
+      Some(dir) => env::set_var("XDG_BIN_HOME", dir),
+      None => env::remove_var("XDG_BIN_HOME"),
+    }
+    result.expect("Install failed");
+    info_result.expect("info failed");
+
+    let file_path = xdg_bin_home.join("echo_test");
+    assert!(file_path.exists());
+    // The shared bin only gets the executable, the rest is kept aside.
+    assert!(!xdg_bin_home.join("echo_test.tsconfig.json").exists());
+    assert!(!xdg_bin_home.join("echo_test.metadata.json").exists());
+    assert!(!xdg_bin_home.join(".install.lock").exists());
+    let sidecar_dir = xdg_bin_home.join(".deno");
+    let config_copy_path = sidecar_dir.join("echo_test.tsconfig.json");
+    assert!(config_copy_path.exists());
+    assert!(sidecar_dir.join("echo_test.metadata.json").exists());
+    assert!(sidecar_dir.join(".install.lock").exists());
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content.contains(&format!(
+      r#""--config" "{}""#,
+      config_copy_path.to_string_lossy()
+    )));
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("echo_server.ts"));
+  }
+
+  #[test]
+  fn install_warns_on_module_like_arg() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let local_module = temp_dir.path().join("other.ts");
+    fs::write(&local_module, "").unwrap();
+    let local_module_str = local_module.to_string_lossy().to_string();
+    let mut out: Vec<u8> = vec![];
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec![
+          "http://localhost:4545/cli/tests/subdir/mod1.ts".to_string(),
+          local_module_str.clone(),
+          "--port".to_string(),
+          "8080".to_string(),
+        ],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(
+      "\"http://localhost:4545/cli/tests/subdir/mod1.ts\" looks like a module"
+    ));
+    assert!(
+      output.contains(&format!("\"{}\" looks like a module", local_module_str))
+    );
+    assert!(!output.contains("\"8080\" looks like a module"));
+    assert!(!output.contains("\"--port\" looks like a module"));
+    assert!(
+      temp_dir.path().join("bin").join("echo_test").exists()
+        || temp_dir.path().join("bin").join("echo_test.cmd").exists()
+    );
+  }
+
+  #[test]
+  fn install_ensure_trailing_newline() {
+    assert_eq!(ensure_trailing_newline("".to_string()), "\n");
+    assert_eq!(ensure_trailing_newline("deno".to_string()), "deno\n");
+    assert_eq!(ensure_trailing_newline("deno\n".to_string()), "deno\n");
+    assert_eq!(ensure_trailing_newline("deno\r\n".to_string()), "deno\r\n");
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn install_trampoline() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let stub_path = temp_dir.path().join("stub.exe");
+    fs::write(&stub_path, "MZ stub").unwrap();
+    let file_path = get_executable_path_for(
+      ExecutableKind::Cmd,
+      temp_dir.path(),
+      "echo_test",
+    );
+    let args = vec!["run".to_string(), "echo_server.ts".to_string()];
+
+    let exe_path = generate_executable_file(
+      ExecutableKind::Cmd,
+      file_path.clone(),
+      args.clone(),
+      None,
+      &[],
+      None,
+      None,
+      Some(&stub_path),
+    )
+    .expect("generate_executable_file failed");
+
+    assert_eq!(exe_path, temp_dir.path().join("echo_test.exe"));
+    assert!(!file_path.exists());
+    assert_eq!(fs::read_to_string(&exe_path).unwrap(), "MZ stub");
+    let trampoline_args: TrampolineArgs = serde_json::from_str(
+      &fs::read_to_string(temp_dir.path().join("echo_test.exe.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+      trampoline_args,
+      TrampolineArgs {
+        deno: "deno.exe".to_string(),
+        args,
+        env: vec![],
+        cwd: None,
+      }
+    );
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn install_trampoline_existing() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let stub_path = temp_dir.path().join("stub.exe");
+    fs::write(&stub_path, "MZ stub").unwrap();
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      targets: vec!["cmd".to_string()],
+      trampoline: Some(stub_path),
+      ..InstallFlags::default()
+    };
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+    assert!(bin_dir.join("echo_test.exe").exists());
+
+    let err = install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .unwrap_err();
+    assert_eq!(err.code(), "existing_installation");
+
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        force: true,
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    assert!(result.overwrote);
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn install_trampoline_not_windows() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let stub_path = temp_dir.path().join("stub.exe");
+    fs::write(&stub_path, "MZ stub").unwrap();
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        trampoline: Some(stub_path),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(!temp_dir.path().join("bin").exists());
+  }
+
+  #[test]
+  fn install_executable_ends_with_newline() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let file_path = get_executable_path(temp_dir.path(), "echo_test");
+
+    generate_executable_file(
+      ExecutableKind::host(),
+      file_path.clone(),
+      vec!["run".to_string(), "echo_server.ts".to_string()],
+      None,
+      &[],
+      None,
+      None,
+      None,
+    )
+    .expect("generate_executable_file failed");
+
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content.ends_with('\n'));
+    if cfg!(windows) {
+      assert!(content.ends_with("%*\n"));
+    } else {
+      assert!(content.ends_with("\"$@\"\n"));
+    }
+  }
+
+  #[test]
+  fn install_expand_module_shorthand() {
+    assert_eq!(
+      expand_module_shorthand("gh:denoland/deno/std/http/file_server.ts")
+        .unwrap(),
+      "https://raw.githubusercontent.com/denoland/deno/main/std/http/file_server.ts"
+    );
+    assert_eq!(
+      expand_module_shorthand("gh:denoland/deno@v1.3.1/cli/tests/cat.ts")
+        .unwrap(),
+      "https://raw.githubusercontent.com/denoland/deno/v1.3.1/cli/tests/cat.ts"
+    );
+    assert_eq!(
+      expand_module_shorthand("https://example.com/gh:cli.ts").unwrap(),
+      "https://example.com/gh:cli.ts"
+    );
+    for malformed in &[
+      "gh:",
+      "gh:denoland",
+      "gh:denoland/deno",
+      "gh:denoland/deno/",
+      "gh:denoland/deno@/cli.ts",
+      "gh:/deno/cli.ts",
+      "gh:denoland//cli.ts",
+      "gh:denoland/deno/std/",
+    ] {
+      let err = expand_module_shorthand(malformed).unwrap_err();
+      assert!(err.to_string().contains("Invalid GitHub shorthand"));
+    }
+  }
+
+  #[test]
+  fn install_github_shorthand() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "gh:denoland/deno@v1.3.1/std/http/file_server.ts"
+          .to_string(),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let expanded_url = "https://raw.githubusercontent.com/denoland/deno/v1.3.1/std/http/file_server.ts";
+    let metadata = InstallMetadata::read(&bin_dir.join("file_server")).unwrap();
+    assert_eq!(metadata.name, "file_server");
+    assert_eq!(metadata.module_url, expanded_url);
+    let file_path = get_executable_path(&bin_dir, "file_server");
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(content.contains(expanded_url));
+  }
+
+  #[test]
+  fn install_doctor() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let orphan_path = bin_dir.join("gone.metadata.json");
+    fs::write(&orphan_path, "{}").unwrap();
+    // Not an installed script, must not be counted.
+    fs::write(bin_dir.join("deno"), "").unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    doctor(Some(temp_dir.path().to_path_buf()), None, &mut out)
+      .expect("doctor failed");
+
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(&format!(
+      "⚠️  {} is not in PATH",
+      bin_dir.canonicalize().unwrap().to_string_lossy()
+    )));
+    assert!(output.contains("1 script installed"));
+    assert!(output.contains(&format!(
+      "⚠️  Orphaned file {}",
+      bin_dir
+        .canonicalize()
+        .unwrap()
+        .join("gone.metadata.json")
+        .to_string_lossy()
+    )));
+    assert!(!output.contains("echo_test has no installation metadata"));
+    assert!(!output.contains("No problems found"));
+  }
+
+  #[test]
+  fn install_header() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let header = "Audited tooling, see https://example.com/policy (TICKET-42)";
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        header: Some(header.to_string()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let content = fs::read_to_string(&result.path).unwrap();
+    if cfg!(windows) {
+      assert!(content.contains(&format!("\n@rem {}\n", escape_cmd(header))));
+    } else {
+      assert!(content.starts_with(&format!(
+        "#!/usr/bin/env sh\n# generated by deno install\n# {}\n# module: ",
+        header
+      )));
+    }
+    // Still recognized as generated.
+    assert_eq!(
+      list_installed(Some(temp_dir.path().to_path_buf()), None)
+        .unwrap()
+        .len(),
+      1
+    );
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        force: true,
+        header: Some("policy\nrm -rf ~".to_string()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn install_prune() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_path = temp_dir.path().join("tsconfig.json");
+    fs::write(&config_path, "{}").unwrap();
+
+    install(
+      Flags {
+        config_path: Some(config_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let orphan_path = bin_dir.join("foo.tsconfig.json");
+    fs::write(&orphan_path, "{}").unwrap();
+
+    let removed = prune(Some(temp_dir.path().to_path_buf()), None).unwrap();
+    assert_eq!(
+      removed,
+      vec![bin_dir.canonicalize().unwrap().join("foo.tsconfig.json")]
+    );
+    assert!(!orphan_path.exists());
+    assert!(bin_dir.join("echo_test.tsconfig.json").exists());
+    assert!(bin_dir.join("echo_test.metadata.json").exists());
+    assert!(prune(Some(temp_dir.path().to_path_buf()), None)
+      .unwrap()
+      .is_empty());
+  }
+
+  #[test]
+  fn prune_keeps_referenced_copies() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let deno_dir = temp_dir.path().join("deno_dir");
+    let config_path = temp_dir.path().join("tsconfig.json");
+    fs::write(&config_path, "{}").unwrap();
+    let original_deno_dir = env::var_os("DENO_DIR");
+    env::set_var("DENO_DIR", &deno_dir);
+
+    let install_with = |name: &str, install_flags: InstallFlags| {
+      install(
+        Flags {
+          config_path: Some(config_path.to_string_lossy().to_string()),
+          ..Flags::default()
+        },
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some(name.to_string()),
+          root: Some(temp_dir.path().to_path_buf()),
+          ..install_flags
+        },
+        &mut io::sink(),
+      )
+    };
+    let results = vec![
+      install_with(
+        "echo_test",
+        InstallFlags {
+          config_dest_name: Some("shared.tsconfig.json".to_string()),
+          ..InstallFlags::default()
+        },
+      ),
+      install_with(
+        "echo_test2",
+        InstallFlags {
+          copies_in_deno_dir: true,
+          ..InstallFlags::default()
+        },
+      ),
+    ];
+    // A trampoline install, as written on Windows.
+    fs::write(bin_dir.join("tramp.exe"), "MZ").unwrap();
+    fs::write(
+      bin_dir.join("tramp.exe.json"),
+      serde_json::to_string(&TrampolineArgs {
+        deno: "deno".to_string(),
+        args: vec![],
+        env: vec![],
+        cwd: None,
+      })
+      .unwrap(),
+    )
+    .unwrap();
+    fs::write(bin_dir.join("tramp.tsconfig.json"), "{}").unwrap();
+    let bin_dir = bin_dir.canonicalize().unwrap();
+    let copies_dir = get_deno_dir_copies_dir(&bin_dir).unwrap();
+    let orphan_path = copies_dir.join("gone").join("gone.tsconfig.json");
+    fs::create_dir_all(orphan_path.parent().unwrap()).unwrap();
+    fs::write(&orphan_path, "{}").unwrap();
+
+    let removed = prune(Some(temp_dir.path().to_path_buf()), None);
+    match original_deno_dir {
+      Some(value) => env::set_var("DENO_DIR", value),
+      None => env::remove_var("DENO_DIR"),
+    }
+    for result in results {
+      result.unwrap();
+    }
+    assert_eq!(removed.unwrap(), vec![orphan_path.clone()]);
+    assert!(!orphan_path.parent().unwrap().exists());
+    assert!(bin_dir.join("shared.tsconfig.json").exists());
+    assert!(bin_dir.join("tramp.tsconfig.json").exists());
+    assert!(copies_dir
+      .join("echo_test2")
+      .join("echo_test2.tsconfig.json")
+      .exists());
+  }
+
+  #[test]
+  fn install_force_lists_overwritten_files() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let flags = Flags {
+      config_path: Some(config_file_path.to_string_lossy().to_string()),
+      ..Flags::default()
+    };
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+    let mut out: Vec<u8> = vec![];
+    install(flags.clone(), install_flags.clone(), &mut out)
+      .expect("Install failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(!output.contains("will be overwritten"));
+    // Or the installation would be up to date and left as is.
+    fs::write(&config_file_path, "{ }").unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      flags,
+      InstallFlags {
+        force: true,
+        ..install_flags
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let bin_dir = bin_dir.canonicalize().unwrap();
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let output = String::from_utf8(out).unwrap();
+    let expected = format!(
+      "The following files will be overwritten:\n  {}\n  {}\n  {}\n",
+      file_path.to_string_lossy(),
+      bin_dir.join("echo_test.tsconfig.json").to_string_lossy(),
+      bin_dir.join("echo_test.metadata.json").to_string_lossy(),
+    );
+    assert!(output.starts_with(&expected));
+  }
+
+  #[test]
+  fn install_force_quiet_overwrites_without_prompt() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+    install(Flags::default(), install_flags.clone(), &mut vec![])
+      .expect("Install failed");
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/subdir/main.ts"
+          .to_string(),
+        force: true,
+        quiet: true,
+        ..install_flags
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+    assert!(result.overwrote);
+    assert!(out.is_empty());
+  }
+
+  #[test]
+  fn confirm_overwrite_prompt() {
+    let mut out: Vec<u8> = vec![];
+    let mut input = std::io::Cursor::new("maybe\nn\n");
+    assert!(!confirm_overwrite(&mut out, &mut input, true).unwrap());
+    assert_eq!(
+      String::from_utf8(out).unwrap(),
+      "Continue? [y/n] Continue? [y/n] "
+    );
+
+    let mut out: Vec<u8> = vec![];
+    let mut input = std::io::Cursor::new("y\n");
+    assert!(confirm_overwrite(&mut out, &mut input, true).unwrap());
+
+    // With the output suppressed, nothing is asked or read.
+    let mut out: Vec<u8> = vec![];
+    let mut input = std::io::Cursor::new("");
+    assert!(confirm_overwrite(&mut out, &mut input, false).unwrap());
+    assert!(out.is_empty());
+  }
+
+  #[test]
+  fn install_targets() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags {
+        allow_net: true,
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["it's".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        targets: vec!["sh".to_string(), "cmd".to_string(), "ps1".to_string()],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let installed_at = format_timestamp(
+      InstallMetadata::read(&bin_dir.join("echo_test"))
+        .unwrap()
+        .installed_at,
+    );
+
+    let sh = fs::read_to_string(bin_dir.join("echo_test")).unwrap();
+    assert_eq!(
+      sh,
+      format!(
+        r#"#!/usr/bin/env sh
+# generated by deno install
+# module: http://localhost:4545/cli/tests/echo_server.ts
+# installed: {}
+deno "run" "--allow-net" "http://localhost:4545/cli/tests/echo_server.ts" "it's" "$@"
+"#,
+        installed_at
+      )
+    );
+    let cmd = fs::read_to_string(bin_dir.join("echo_test.cmd")).unwrap();
+    assert_eq!(
+      cmd,
+      format!(
+        r#"% generated by deno install %
+@rem module: http://localhost:4545/cli/tests/echo_server.ts
+@rem installed: {}
+@deno.exe "run" "--allow-net" "http://localhost:4545/cli/tests/echo_server.ts" "it's" %*
+@exit /b %errorlevel%
+"#,
+        installed_at
+      )
+    );
+    let ps1 = fs::read_to_string(bin_dir.join("echo_test.ps1")).unwrap();
+    let deno = if cfg!(windows) { "deno.exe" } else { "deno" };
+    assert_eq!(
+      ps1,
+      format!(
+        r#"#!/usr/bin/env pwsh
+# generated by deno install
+# module: http://localhost:4545/cli/tests/echo_server.ts
+# installed: {}
+{} 'run' '--allow-net' 'http://localhost:4545/cli/tests/echo_server.ts' 'it''s' @args
+exit $LASTEXITCODE
+"#,
+        installed_at, deno
+      )
+    );
+
+    let mut out: Vec<u8> = vec![];
+    doctor(Some(temp_dir.path().to_path_buf()), None, &mut out)
+      .expect("doctor failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("1 script installed"));
+  }
+
+  #[test]
+  fn install_aliases() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--foo".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        aliases: vec!["et".to_string(), "echo".to_string()],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    for alias in &["et", "echo"] {
+      let alias_content =
+        fs::read_to_string(get_executable_path(&bin_dir, alias)).unwrap();
+      assert_eq!(alias_content, content);
+    }
+    let metadata = InstallMetadata::read(&bin_dir.join("echo_test")).unwrap();
+    assert_eq!(metadata.aliases, vec!["et", "echo"]);
+
+    let mut out: Vec<u8> = vec![];
+    doctor(Some(temp_dir.path().to_path_buf()), None, &mut out)
+      .expect("doctor failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("1 script installed"));
+    assert!(!output.contains("has no installation metadata"));
+
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("other".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        aliases: vec!["et".to_string()],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    assert!(result
+      .unwrap_err()
+      .to_string()
+      .contains("Existing installation found"));
+  }
+
+  #[test]
+  fn install_names_invalid_writes_nothing() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    for (names, aliases) in &[
+      (vec!["add".to_string(), "remove files".to_string()], vec![]),
+      (vec![], vec!["et".to_string(), "9lives".to_string()]),
+    ] {
+      let result = install(
+        Flags::default(),
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: if names.is_empty() {
+            Some("echo_test".to_string())
+          } else {
+            None
+          },
+          names: names.clone(),
+          aliases: aliases.clone(),
+          root: Some(temp_dir.path().to_path_buf()),
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      );
+      assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("Invalid executable name"));
+    }
+    for name in &["add", "echo_test", "et"] {
+      assert!(!get_executable_path(&bin_dir, name).exists());
+    }
+  }
+
+  #[test]
+  fn install_force_removes_stale_copies() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let import_map_path = temp_dir.path().join("import_map.json");
+    fs::write(&import_map_path, r#"{ "imports": {} }"#).unwrap();
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+
+    install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        import_map_path: Some(import_map_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      install_flags.clone(),
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let config_copy_path = bin_dir.join("echo_test.tsconfig.json");
+    let import_map_copy_path = bin_dir.join("echo_test.import_map.json");
+    assert!(config_copy_path.exists());
+    assert!(import_map_copy_path.exists());
+
+    // Without a config this time, the import map is still used.
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags {
+        import_map_path: Some(import_map_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      install_flags,
+      &mut out,
+    )
+    .expect("Install failed");
+    assert!(!config_copy_path.exists());
+    assert!(import_map_copy_path.exists());
+    let output = String::from_utf8(out).unwrap();
+    assert!(output
+      .contains(&format!("Removed {}", config_copy_path.to_string_lossy())));
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(!content.contains("--config"));
+  }
+
+  #[test]
+  fn install_force_backup() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let backup_dir = temp_dir.path().join("backup");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let flags = Flags {
+      config_path: Some(config_file_path.to_string_lossy().to_string()),
+      ..Flags::default()
+    };
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+    install(flags.clone(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let first_content = fs::read_to_string(&file_path).unwrap();
+    let file_name = file_path.file_name().unwrap().to_owned();
+
+    // Backed up in place by default.
+    install(
+      flags.clone(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/cat.ts".to_string(),
+        ..install_flags.clone()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let mut bak_name = file_name.clone();
+    bak_name.push(".bak");
+    let bak_content = fs::read_to_string(bin_dir.join(bak_name)).unwrap();
+    assert_eq!(bak_content, first_content);
+    assert!(bin_dir.join("echo_test.tsconfig.json.bak").exists());
+    let second_content = fs::read_to_string(&file_path).unwrap();
+    assert!(second_content.contains("cat.ts"));
+
+    install(
+      flags,
+      InstallFlags {
+        backup_dir: Some(backup_dir.clone()),
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let backups: Vec<PathBuf> = fs::read_dir(&backup_dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().path())
+      .collect();
+    assert_eq!(backups.len(), 1);
+    let backup_content =
+      fs::read_to_string(backups[0].join(&file_name)).unwrap();
+    assert_eq!(backup_content, second_content);
+    assert!(backups[0].join("echo_test.tsconfig.json").exists());
+    assert!(fs::read_to_string(&file_path)
+      .unwrap()
+      .contains("echo_server.ts"));
+  }
+
+  #[test]
+  fn install_error_json() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+
+    let err = install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .unwrap_err();
+    let json: serde_json::Value =
+      serde_json::from_str(&error_to_json(&err)).unwrap();
+    assert_eq!(
+      json,
+      serde_json::json!({
+        "error": {
+          "code": "existing_installation",
+          "message": "Existing installation found. Aborting (Use -f to overwrite).",
+        }
+      })
+    );
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("echo test".to_string()),
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    let json: serde_json::Value =
+      serde_json::from_str(&error_to_json(&err)).unwrap();
+    assert_eq!(
+      json,
+      serde_json::json!({
+        "error": {
+          "code": "invalid_name",
+          "message": "Invalid executable name: echo test",
+        }
+      })
+    );
+
+    let err = InstallError::from(Error::new(ErrorKind::NotFound, "gone"));
+    let json: serde_json::Value =
+      serde_json::from_str(&error_to_json(&err)).unwrap();
+    assert_eq!(json["error"]["code"], "io");
+  }
+
+  #[test]
+  fn install_build_executable_args() {
+    let flags = Flags {
+      read_allowlist: vec![PathBuf::from("/tmp")],
+      allow_net: true,
+      no_check: NoCheck::All,
+      ..Flags::default()
+    };
+    assert_eq!(
+      build_executable_args(
+        &flags,
+        "http://localhost:4545/cli/tests/echo_server.ts",
+        &["--foobar".to_string()]
+      ),
+      vec![
+        "run",
+        "--allow-read=/tmp",
+        "--allow-net",
+        "--no-check",
+        "http://localhost:4545/cli/tests/echo_server.ts",
+        "--foobar",
+      ]
+    );
+
+    let flags = Flags {
+      config_path: Some("/copies/tsconfig.json".to_string()),
+      lock: Some("/copies/lock.json".to_string()),
+      log_level: Some(Level::Error),
+      ..Flags::default()
+    };
+    assert_eq!(
+      build_executable_args(&flags, "file:///main.ts", &[]),
+      vec![
+        "run",
+        "--quiet",
+        "--config",
+        "/copies/tsconfig.json",
+        "--lock",
+        "/copies/lock.json",
+        "file:///main.ts",
+      ]
+    );
+  }
+
+  #[test]
+  fn install_root_not_a_directory() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let root_file = temp_dir.path().join("root");
+    File::create(&root_file).unwrap();
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(root_file.clone()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+
+    let root_file = root_file.canonicalize().unwrap();
+    assert!(matches!(err, InstallError::RootNotADirectory(_)));
+    assert_eq!(
+      err.to_string(),
+      format!(
+        "Install root is not a directory: {}",
+        root_file.to_string_lossy()
+      )
+    );
+    assert!(root_file.is_file());
+
+    env::set_var(
+      "DENO_INSTALL_ROOT",
+      format!("{} ", temp_dir.path().to_string_lossy()),
+    );
+    let root = get_installer_root();
+    env::remove_var("DENO_INSTALL_ROOT");
+    assert_eq!(root.unwrap(), temp_dir.path().canonicalize().unwrap());
+  }
+
+  #[test]
+  fn install_invalid_json() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{ invalid").unwrap();
+    let import_map_path = temp_dir.path().join("import_map.json");
+    fs::write(&import_map_path, "{\"imports\": {},}").unwrap();
+
+    let err = install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, InstallError::ConfigCopy(_, _)));
+    assert_eq!(
+      err.to_string(),
+      format!(
+        "Failed to copy config file {}: invalid JSON: key must be a string at line 1 column 3",
+        config_file_path.to_string_lossy()
+      )
+    );
+
+    // Unlike a config, an import map is strict JSON.
+    let err = install(
+      Flags {
+        import_map_path: Some(import_map_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, InstallError::ImportMapCopy(_, _)));
+    assert!(err.to_string().contains("invalid JSON"));
+    assert!(!get_executable_path(&bin_dir, "echo_test").exists());
+    assert!(!get_config_file_path(&bin_dir.join("echo_test")).exists());
+    assert!(!get_import_map_file_path(&bin_dir.join("echo_test")).exists());
+  }
+
+  #[test]
+  fn install_config_with_comments() {
+    assert!(parse_config_json(
+      "// the config\n{\n  /* no */ \"compilerOptions\": {\"strict\": true,},\n  \"a\": \"//,}\",\n}"
+    )
+    .is_ok());
+    let err = parse_config_json("/* a\n b */ { invalid").unwrap_err();
+    assert_eq!((err.line(), err.column()), (2, 9));
+  }
+
+  #[test]
+  fn install_config_env_var() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let env_config_path = temp_dir.path().join("env_tsconfig.json");
+    fs::write(&env_config_path, "{\"compilerOptions\":{}}").unwrap();
+    let original_config = env::var_os("DENO_INSTALL_CONFIG");
+    env::set_var("DENO_INSTALL_CONFIG", &env_config_path);
+
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    match original_config {
+      Some(value) => env::set_var("DENO_INSTALL_CONFIG", value),
+      None => env::remove_var("DENO_INSTALL_CONFIG"),
+    }
+
+    let result = result.expect("Install failed");
+    let config_copy_path = get_config_file_path(&bin_dir.join("echo_test"));
+    assert_eq!(
+      fs::read_to_string(&config_copy_path).unwrap(),
+      "{\"compilerOptions\":{}}"
+    );
+    assert_eq!(
+      result.config_path,
+      Some(
+        bin_dir
+          .canonicalize()
+          .unwrap()
+          .join("echo_test.tsconfig.json")
+      )
+    );
+    let shim_info = inspect_shim(&result.path).unwrap();
+    assert_eq!(shim_info.flags[0], "--config");
+  }
+
+  #[test]
+  fn install_config_flag_overrides_env_var() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let env_config_path = temp_dir.path().join("env_tsconfig.json");
+    fs::write(&env_config_path, "{\"env\":true}").unwrap();
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{\"flag\":true}").unwrap();
+    let original_config = env::var_os("DENO_INSTALL_CONFIG");
+    env::set_var("DENO_INSTALL_CONFIG", &env_config_path);
+
+    let mut results = vec![];
+    for (name, flags) in vec![
+      (
+        "echo_test",
+        Flags {
+          config_path: Some(config_file_path.to_string_lossy().to_string()),
+          ..Flags::default()
+        },
+      ),
+      (
+        "echo_test2",
+        Flags {
+          no_config: true,
+          ..Flags::default()
+        },
+      ),
+    ] {
+      results.push(install(
+        flags,
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some(name.to_string()),
+          root: Some(temp_dir.path().to_path_buf()),
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      ));
+    }
+    match original_config {
+      Some(value) => env::set_var("DENO_INSTALL_CONFIG", value),
+      None => env::remove_var("DENO_INSTALL_CONFIG"),
+    }
+
+    for result in results {
+      result.expect("Install failed");
+    }
+    assert_eq!(
+      fs::read_to_string(get_config_file_path(&bin_dir.join("echo_test")))
+        .unwrap(),
+      "{\"flag\":true}"
+    );
+    assert!(!get_config_file_path(&bin_dir.join("echo_test2")).exists());
+  }
+
+  #[test]
+  fn install_config_dest_name() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let flags = Flags {
+      config_path: Some(config_file_path.to_string_lossy().to_string()),
+      ..Flags::default()
+    };
+
+    install(
+      flags.clone(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        config_dest_name: Some("shared.tsconfig.json".to_string()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let config_copy_path = bin_dir.join("shared.tsconfig.json");
+    assert!(config_copy_path.exists());
+    assert!(!get_config_file_path(&bin_dir.join("echo_test")).exists());
+    let shim_info =
+      inspect_shim(&get_executable_path(&bin_dir, "echo_test")).unwrap();
+    let config_index = shim_info
+      .flags
+      .iter()
+      .position(|flag| flag == "--config")
+      .unwrap();
+    assert_eq!(
+      PathBuf::from(&shim_info.flags[config_index + 1]),
+      bin_dir.canonicalize().unwrap().join("shared.tsconfig.json")
+    );
+    let (_, install_flags) = get_reinstall_flags(
+      "echo_test",
+      Some(temp_dir.path().to_path_buf()),
+      None,
+    )
+    .unwrap();
+    assert_eq!(
+      install_flags.config_dest_name.as_deref(),
+      Some("shared.tsconfig.json")
+    );
+    assert!(!install_flags.no_copy_config);
+
+    fs::write(bin_dir.join("notes.json"), "{}").unwrap();
+    for config_dest_name in &[
+      "../tsconfig.json",
+      "sub/tsconfig.json",
+      "",
+      ".json",
+      "echo_test",
+      "tsconfig",
+      "notes.json",
+    ] {
+      let err = install(
+        flags.clone(),
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some("echo_test".to_string()),
+          root: Some(temp_dir.path().to_path_buf()),
+          force: true,
+          config_dest_name: Some(config_dest_name.to_string()),
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      )
+      .unwrap_err();
+      assert!(matches!(err, InstallError::InvalidConfigName(_)));
+    }
+    assert_eq!(
+      fs::read_to_string(bin_dir.join("notes.json")).unwrap(),
+      "{}"
+    );
+  }
+
+  #[test]
+  fn uninstall_keeps_shared_config_copy() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    for name in &["echo_test", "echo_test2"] {
+      install(
+        Flags {
+          config_path: Some(config_file_path.to_string_lossy().to_string()),
+          ..Flags::default()
+        },
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some(name.to_string()),
+          root: Some(temp_dir.path().to_path_buf()),
+          config_dest_name: Some("shared.tsconfig.json".to_string()),
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      )
+      .expect("Install failed");
+    }
+
+    let config_copy_path = bin_dir.join("shared.tsconfig.json");
+    for name in &["echo_test", "echo_test2"] {
+      uninstall(
+        name.to_string(),
+        Some(temp_dir.path().to_path_buf()),
+        None,
+        false,
+        &mut io::sink(),
+      )
+      .unwrap();
+      assert_eq!(config_copy_path.exists(), *name == "echo_test");
+    }
+  }
+
+  #[test]
+  fn install_warns_about_permission_args() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec![
+          "--allow-net".to_string(),
+          "--allow-read=/tmp".to_string(),
+          "--allow-netflix".to_string(),
+          "--port".to_string(),
+        ],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("⚠️  --allow-net follows the module, so it will be passed to http://localhost:4545/cli/tests/echo_server.ts as an argument, not to deno. To grant the permission, pass it before the module."));
+    assert!(output.contains("⚠️  --allow-read=/tmp follows the module"));
+    assert!(!output.contains("--allow-netflix follows"));
+    assert!(!output.contains("--port follows"));
+    let shim_info = inspect_shim(&get_executable_path(
+      &temp_dir.path().join("bin"),
+      "echo_test",
+    ))
+    .unwrap();
+    assert!(shim_info.flags.is_empty());
+  }
+
+  #[test]
+  fn install_quiet() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags {
+        allow_net: true,
+        log_level: Some(Level::Error),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--allow-read".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        quiet: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    assert!(out.is_empty());
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    assert!(file_path.exists());
+  }
+
+  #[test]
+  fn install_deno_args() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let module_url = "http://localhost:4545/cli/tests/echo_server.ts";
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: module_url.to_string(),
+        deno_args: vec!["--reload".to_string()],
+        args: vec!["--foobar".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let shim_info =
+      inspect_shim(&get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert_eq!(shim_info.flags, vec!["--reload"]);
+    assert_eq!(shim_info.module_url, module_url);
+    assert_eq!(shim_info.args, vec!["--foobar"]);
+    let metadata = InstallMetadata::read(&bin_dir.join("echo_test")).unwrap();
+    assert_eq!(
+      metadata.args,
+      vec!["run", "--reload", module_url, "--foobar"]
+    );
+    let (_, install_flags) = get_reinstall_flags(
+      "echo_test",
+      Some(temp_dir.path().to_path_buf()),
+      None,
+    )
+    .unwrap();
+    assert_eq!(install_flags.deno_args, vec!["--reload"]);
+  }
+
+  #[test]
+  fn install_deno_args_allowlist() {
+    assert!(validate_deno_arg("--reload").is_ok());
+    assert!(validate_deno_arg("--reload=https://deno.land/std").is_ok());
+    assert!(validate_deno_arg("--seed=1").is_ok());
+    assert!(validate_deno_arg("--seed=").is_err());
+    assert!(validate_deno_arg("--reloaded").is_err());
+    assert!(validate_deno_arg("--allow-all").is_err());
+
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        deno_args: vec!["--allow-all".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, InstallError::InvalidDenoArg(_)));
+    assert!(!temp_dir.path().join("bin").exists());
+  }
+
+  #[test]
+  fn install_error_variants() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+    let err = install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .unwrap_err();
+    match &err {
+      InstallError::InvalidName(name) => assert_eq!(name, "echo test"),
+      err => panic!("unexpected error: {:?}", err),
+    }
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(err.to_string(), "Invalid executable name: echo test");
+
+    let install_flags = InstallFlags {
+      name: Some("echo_test".to_string()),
+      ..install_flags
+    };
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+    let err =
+      install(Flags::default(), install_flags, &mut io::sink()).unwrap_err();
+    assert!(matches!(err, InstallError::ExistingInstallation));
+    assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+    // Still found inside the io::Error of the other functions.
+    let err = Error::from(err);
+    assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    assert!(matches!(
+      InstallError::downcast(&err),
+      Some(InstallError::ExistingInstallation)
+    ));
+    assert!(matches!(
+      InstallError::from(err),
+      InstallError::ExistingInstallation
+    ));
+  }
+
+  #[test]
+  fn install_if_not_installed() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      if_not_installed: true,
+      ..InstallFlags::default()
+    };
+
+    let mut out: Vec<u8> = vec![];
+    install(Flags::default(), install_flags.clone(), &mut out)
+      .expect("Install failed");
+    assert!(!out.is_empty());
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let content = fs::read_to_string(&file_path).unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/cat.ts".to_string(),
+        ..install_flags
+      },
+      &mut out,
+    );
+    assert!(!result.unwrap().installed);
+    assert!(out.is_empty());
+    assert_eq!(fs::read_to_string(&file_path).unwrap(), content);
+  }
+
+  #[test]
+  fn install_max_name_length() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let install_flags = InstallFlags {
+      module_url:
+        "http://localhost:4545/cli/tests/subdir/very_long_module_name.ts"
+          .to_string(),
+      root: Some(temp_dir.path().to_path_buf()),
+      max_name_length: Some(9),
+      ..InstallFlags::default()
+    };
+
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+    assert!(get_executable_path(&bin_dir, "very_long").exists());
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("explicit_name".to_string()),
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Executable name explicit_name is longer than 9 characters"
+    );
+    assert!(!get_executable_path(&bin_dir, "explicit_").exists());
+  }
+
+  #[test]
+  fn uninstall_basic() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        aliases: vec!["et".to_string()],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let config_copy_path = bin_dir.join("echo_test.tsconfig.json");
+    assert!(file_path.exists());
+    assert!(config_copy_path.exists());
+
+    let mut out: Vec<u8> = vec![];
+    uninstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut out,
+    )
+    .expect("Uninstall failed");
+
+    assert!(!file_path.exists());
+    assert!(!config_copy_path.exists());
+    assert!(!get_executable_path(&bin_dir, "et").exists());
+    assert!(!bin_dir.join("echo_test.metadata.json").exists());
+    let output = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    let removed_files: Vec<String> = vec![
+      file_path,
+      get_executable_path(&bin_dir, "et"),
+      config_copy_path,
+      bin_dir.join("echo_test.metadata.json"),
+    ]
+    .iter()
+    .map(|path| format!("Removed {} (", path.to_string_lossy()))
+    .collect();
+    assert_eq!(lines.len(), removed_files.len() + 1);
+    for (line, removed_file) in lines.iter().zip(&removed_files) {
+      assert!(line.starts_with(removed_file), "{}", line);
+    }
+    assert!(lines[4].starts_with("✅ Successfully uninstalled echo_test"));
+    assert!(!lines[4].ends_with(", freeing 0 bytes"));
+  }
+
+  #[test]
+  fn uninstall_keeps_root_under_installs_dir() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    // Only the directories made for the copies in DENO_DIR are owned, not
+    // any under a directory of that name.
+    let root = temp_dir.path().join("installs").join("tools");
+    fs::create_dir_all(&root).unwrap();
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(root.clone()),
+        completions: Some(("bash".to_string(), "completions".to_string())),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let completions_path =
+      get_completions_path(&root.join("bin"), "echo_test", "bash").unwrap();
+    assert!(completions_path.exists());
+
+    uninstall(
+      "echo_test".to_string(),
+      Some(root),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .expect("Uninstall failed");
+    assert!(!completions_path.exists());
+    assert!(completions_path.parent().unwrap().is_dir());
+  }
+
+  #[test]
+  fn uninstall_keeps_foreign_executables() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        aliases: vec!["foo".to_string()],
+        targets: vec!["sh".to_string()],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    // Another tool's files, sharing the bin directory.
+    let foreign_paths = vec![
+      bin_dir.join("foo.cmd"),
+      bin_dir.join("foo.ps1"),
+      bin_dir.join("echo_test.cmd"),
+    ];
+    for path in &foreign_paths {
+      fs::write(path, "#!/bin/sh\necho foreign\n").unwrap();
+    }
+
+    uninstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .expect("Uninstall failed");
+
+    assert!(!bin_dir.join("echo_test").exists());
+    assert!(!bin_dir.join("foo").exists());
+    for path in &foreign_paths {
+      assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        "#!/bin/sh\necho foreign\n"
+      );
+    }
+
+    // Nor a program named like the script, unlike a trampoline.
+    let exe_path = bin_dir.join("foo.exe");
+    fs::write(&exe_path, "MZ program").unwrap();
+    fs::write(bin_dir.join("foo.exe.json"), "{\"version\": 1}").unwrap();
+    assert!(!is_trampoline(&exe_path));
+    let trampoline_path = bin_dir.join("echo_test.exe");
+    fs::write(&trampoline_path, "MZ stub").unwrap();
+    fs::write(
+      bin_dir.join("echo_test.exe.json"),
+      serde_json::to_string(&TrampolineArgs {
+        deno: "deno.exe".to_string(),
+        args: vec!["run".to_string(), "echo_server.ts".to_string()],
+        env: vec![],
+        cwd: None,
+      })
+      .unwrap(),
+    )
+    .unwrap();
+    uninstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .expect("Uninstall failed");
+    assert!(!trampoline_path.exists());
+    assert!(!bin_dir.join("echo_test.exe.json").exists());
+
+    // Nothing generated is left, a foreign file alone is not an installation.
+    fs::write(bin_dir.join("foo"), "#!/bin/sh\necho foreign\n").unwrap();
+    let err = uninstall(
+      "foo".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert!(bin_dir.join("foo").exists());
+  }
+
+  #[test]
+  fn uninstall_not_found() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    fs::create_dir(temp_dir.path().join("bin")).unwrap();
+
+    let err = uninstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert_eq!(err.to_string(), "No installation found for echo_test");
+
+    let err = uninstall(
+      "echo test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("Invalid executable name"));
+  }
+
+  #[test]
+  fn install_list_installed() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    for (name, config_path) in &[
+      ("echo_test", None),
+      (
+        "cat_test",
+        Some(config_file_path.to_string_lossy().to_string()),
+      ),
+    ] {
+      install(
+        Flags {
+          config_path: config_path.clone(),
+          ..Flags::default()
+        },
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some(name.to_string()),
+          root: Some(temp_dir.path().to_path_buf()),
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      )
+      .expect("Install failed");
+    }
+    assert!(bin_dir.join("cat_test.tsconfig.json").exists());
+    // Stray files aren't listed.
+    fs::write(bin_dir.join("README.md"), "").unwrap();
+
+    let names: Vec<String> =
+      list_installed(Some(temp_dir.path().to_path_buf()), None)
+        .unwrap()
+        .into_iter()
+        .map(|script| script.name)
+        .collect();
+    assert_eq!(names, vec!["cat_test", "echo_test"]);
+
+    let mut out: Vec<u8> = vec![];
+    list(Some(temp_dir.path().to_path_buf()), None, true, &mut out).unwrap();
+    let json: Vec<String> = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json, names);
+  }
+
+  #[test]
+  fn install_project_config() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    fs::create_dir(temp_dir.path().join("tools")).unwrap();
+    fs::write(
+      temp_dir.path().join(".deno-install.json"),
+      r#"{
+        "permissions": ["--allow-net", "--allow-read", "--foo"],
+        "root": "tools",
+        "max_name_length": 4
+      }"#,
+    )
+    .unwrap();
+
+    let mut flags = Flags {
+      read_allowlist: vec![PathBuf::from("/data")],
+      ..Flags::default()
+    };
+    let mut install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      ..InstallFlags::default()
+    };
+    let mut out: Vec<u8> = vec![];
+    apply_project_config(
+      temp_dir.path(),
+      &mut flags,
+      &mut install_flags,
+      &mut out,
+    )
+    .unwrap();
+    let output = String::from_utf8(out).unwrap();
+    assert_eq!(
+      output,
+      "⚠️  Ignoring unsupported permission in .deno-install.json: --foo\n"
+    );
+    assert_eq!(install_flags.root, Some(temp_dir.path().join("tools")));
+    assert_eq!(install_flags.max_name_length, Some(4));
+
+    install(flags, install_flags, &mut io::sink()).expect("Install failed");
+
+    let bin_dir = temp_dir.path().join("tools").join("bin");
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo")).unwrap();
+    assert!(content.contains(r#""--allow-net""#));
+    // The allowlist given on the command line wins.
+    assert!(content.contains(r#""--allow-read=/data""#));
+    assert!(!content.contains(r#""--allow-read" "#));
+  }
+
+  #[test]
+  fn install_project_config_invalid() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    fs::write(
+      temp_dir.path().join(".deno-install.json"),
+      r#"{"rot": "."}"#,
+    )
+    .unwrap();
+
+    let err = apply_project_config(
+      temp_dir.path(),
+      &mut Flags::default(),
+      &mut InstallFlags::default(),
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains(".deno-install.json"));
+    assert!(err.to_string().contains("unknown field `rot`"));
+  }
+
+  #[test]
+  fn install_escape_args() {
+    let args = vec![
+      "run".to_string(),
+      "https://example.com/mod.ts?q=\"a\"".to_string(),
+      "--foo=\"a b\"".to_string(),
+      "$HOME".to_string(),
+      "100%".to_string(),
+    ];
+
+    let cmd = ExecutableKind::Cmd.render(None, &[], None, &args);
+    assert_eq!(
+      cmd,
+      r#"% generated by deno install %
+@deno.exe "run" "https://example.com/mod.ts?q=""a""" "--foo=""a b""" "$HOME" "100%%" %*
+@exit /b %errorlevel%
+"#
+    );
+
+    let sh = ExecutableKind::Sh.render(None, &[], None, &args);
+    assert!(sh.contains(
+      r#"deno "run" "https://example.com/mod.ts?q=\"a\"" "--foo=\"a b\"" "\$HOME" "100%" "$@""#
+    ));
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn install_escape_args_sh_tokens() {
+    let args = vec![
+      "https://example.com/mod.ts?q=\"a\"".to_string(),
+      "--foo=\"a b\"".to_string(),
+      "$HOME `pwd` \\".to_string(),
+    ];
+    let sh = ExecutableKind::Sh.render(None, &[], None, &args);
+    let command = sh.lines().nth(2).unwrap();
+    // Print each argument the script passes to deno on its own line.
+    let script = format!("deno() {{ printf '%s\\n' \"$@\"; }}; {}", command);
+    let output = std::process::Command::new("sh")
+      .arg("-c")
+      .arg(script)
+      .output()
+      .unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.lines().collect::<Vec<_>>(), args);
+  }
+
+  #[test]
+  fn install_path_hint_default_shell() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let home_dir = temp_dir.path().join("home");
+    let original_home = env::var_os("HOME");
+    let original_userprofile = env::var_os("USERPROFILE");
+    let original_shell = env::var_os("SHELL");
+    env::set_var("HOME", &home_dir);
+    env::set_var("USERPROFILE", &home_dir);
+    env::set_var("SHELL", "/bin/zsh");
+
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+    let mut before: Vec<u8> = vec![];
+    let before_result =
+      install(Flags::default(), install_flags.clone(), &mut before);
+    let set_result = set_default_shell("fish", &mut io::sink());
+    let mut after: Vec<u8> = vec![];
+    let after_result = install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("echo_test2".to_string()),
+        ..install_flags
+      },
+      &mut after,
+    );
+    let unsupported_result = set_default_shell("nu", &mut io::sink());
+
+    for (var, value) in &[
+      ("HOME", original_home),
+      ("USERPROFILE", original_userprofile),
+      ("SHELL", original_shell),
+    ] {
+      match value {
+        Some(value) => env::set_var(var, value),
+        None => env::remove_var(var),
+      }
+    }
+    before_result.expect("Install failed");
+    set_result.expect("set_default_shell failed");
+    after_result.expect("Install failed");
+    assert!(unsupported_result.is_err());
+
+    let bin_dir = temp_dir.path().canonicalize().unwrap().join("bin");
+    let before = String::from_utf8(before).unwrap();
+    assert!(before.contains(&format!(
+      "export PATH=\"{}:$PATH\"",
+      bin_dir.to_string_lossy()
+    )));
+    let after = String::from_utf8(after).unwrap();
+    assert!(after.contains(&format!(
+      "set -gx PATH \"{}\" $PATH",
+      bin_dir.to_string_lossy()
+    )));
+    let settings = fs::read_to_string(home_dir.join(".deno").join("config"));
+    assert!(settings.unwrap().contains(r#""default_shell": "fish""#));
+  }
+
+  #[test]
+  fn install_path_hint_shell() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let original_home = env::var_os("HOME");
+    let original_userprofile = env::var_os("USERPROFILE");
+    let original_shell = env::var_os("SHELL");
+    env::set_var("HOME", temp_dir.path().join("home"));
+    env::set_var("USERPROFILE", temp_dir.path().join("home"));
+    env::set_var("SHELL", "/usr/local/bin/fish");
+
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+    let mut from_env: Vec<u8> = vec![];
+    let from_env_result =
+      install(Flags::default(), install_flags.clone(), &mut from_env);
+    let mut from_flag: Vec<u8> = vec![];
+    let from_flag_result = install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("echo_test2".to_string()),
+        shell: Some("bash".to_string()),
+        ..install_flags
+      },
+      &mut from_flag,
+    );
+
+    for (var, value) in &[
+      ("HOME", original_home),
+      ("USERPROFILE", original_userprofile),
+      ("SHELL", original_shell),
+    ] {
+      match value {
+        Some(value) => env::set_var(var, value),
+        None => env::remove_var(var),
+      }
+    }
+    from_env_result.expect("Install failed");
+    from_flag_result.expect("Install failed");
+
+    let bin_dir = temp_dir.path().canonicalize().unwrap().join("bin");
+    let from_env = String::from_utf8(from_env).unwrap();
+    assert!(from_env.contains(&format!(
+      "set -gx PATH \"{}\" $PATH",
+      bin_dir.to_string_lossy()
+    )));
+    let from_flag = String::from_utf8(from_flag).unwrap();
+    assert!(from_flag.contains(&format!(
+      "export PATH=\"{}:$PATH\"",
+      bin_dir.to_string_lossy()
+    )));
+  }
+
+  #[test]
+  fn install_data_url() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let data_url = "data:application/typescript,console.log(Deno.args);";
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: data_url.to_string(),
+        name: Some("print_args".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("data: URLs can't be installed"));
+    assert!(!get_executable_path(&bin_dir, "print_args").exists());
+  }
+
+  #[test]
+  fn install_no_copy_config() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        no_copy_config: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    assert!(!bin_dir.join("echo_test.tsconfig.json").exists());
+    let config_file_path = config_file_path.canonicalize().unwrap();
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains(&format!(
+      r#""--config" "{}""#,
+      config_file_path.to_string_lossy()
+    )));
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(&format!(
+      "⚠️  echo_test uses {} directly",
+      config_file_path.to_string_lossy()
+    )));
+  }
+
+  #[test]
+  fn install_config_absolute_and_relative() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    let config = r#"{ "compilerOptions": { "strict": true } }"#;
+    fs::write(&config_file_path, config).unwrap();
+
+    // The same file, relative to the current directory.
+    let cwd = env::current_dir().unwrap();
+    let mut relative_config_file_path = PathBuf::new();
+    for _ in cwd.components().skip(1) {
+      relative_config_file_path.push("..");
+    }
+    let fs_root = config_file_path.ancestors().last().unwrap();
+    relative_config_file_path
+      .push(config_file_path.strip_prefix(fs_root).unwrap());
+    assert!(relative_config_file_path.is_relative());
+
+    for (name, config_path) in &[
+      ("absolute", config_file_path.clone()),
+      ("relative", relative_config_file_path),
+    ] {
+      install(
+        Flags {
+          config_path: Some(config_path.to_string_lossy().to_string()),
+          ..Flags::default()
+        },
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some(name.to_string()),
+          root: Some(temp_dir.path().to_path_buf()),
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      )
+      .expect("Install failed");
+      let config_copy_path = bin_dir.join(format!("{}.tsconfig.json", name));
+      assert_eq!(fs::read_to_string(config_copy_path).unwrap(), config);
+    }
+  }
+
+  #[test]
+  fn install_prints_root_in_path_hint() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let root = temp_dir.path().canonicalize().unwrap();
+    let original_path = env::var_os("PATH");
+    env::set_var("PATH", &root);
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(root.clone()),
+        ..InstallFlags::default()
+      },
+      &mut out,
+    );
+    let mut doctor_out: Vec<u8> = vec![];
+    let doctor_result = doctor(Some(root.clone()), None, &mut doctor_out);
+    if let Some(path) = original_path {
+      env::set_var("PATH", path);
+    }
+    result.expect("Install failed");
+    doctor_result.expect("doctor failed");
+
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(&format!(
+      "ℹ️  You added the root {} to PATH, add the bin subdirectory {} instead",
+      root.to_string_lossy(),
+      root.join("bin").to_string_lossy()
+    )));
+    assert!(!output.contains("ℹ️  Add"));
+    let doctor_output = String::from_utf8(doctor_out).unwrap();
+    assert!(doctor_output.contains(&format!(
+      "You added the root {} to PATH",
+      root.to_string_lossy()
+    )));
+  }
+
+  #[test]
+  fn install_verify_name_available() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let target_root = temp_dir.path().join("target");
+    let other_root = temp_dir.path().join("other");
+    fs::create_dir(&target_root).unwrap();
+    fs::create_dir_all(other_root.join("bin")).unwrap();
+    let other_file_path =
+      get_executable_path(&other_root.join("bin"), "echo_test");
+    fs::write(&other_file_path, "").unwrap();
+    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
+    env::set_var("DENO_INSTALL_ROOT", &other_root);
+
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(target_root),
+      ..InstallFlags::default()
+    };
+    // Free in the target, taken in another root.
+    let mut out: Vec<u8> = vec![];
+    let first_result =
+      install(Flags::default(), install_flags.clone(), &mut out);
+    // Taken in the target.
+    let second_result =
+      install(Flags::default(), install_flags.clone(), &mut io::sink());
+    let forced_result = install(
+      Flags::default(),
+      InstallFlags {
+        force: true,
+        ..install_flags
+      },
+      &mut io::sink(),
+    );
+    match original_install_root {
+      Some(install_root) => env::set_var("DENO_INSTALL_ROOT", install_root),
+      None => env::remove_var("DENO_INSTALL_ROOT"),
+    }
+
+    first_result.expect("Install failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(&format!(
+      "⚠️  {} already exists, the one found first in PATH will be run",
+      other_file_path.canonicalize().unwrap().to_string_lossy()
+    )));
+    assert!(second_result
+      .unwrap_err()
+      .to_string()
+      .contains("Existing installation found"));
+    forced_result.expect("Install failed");
+  }
+
+  #[test]
+  fn install_missing_config_leaves_no_executable() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("missing.tsconfig.json");
+
+    let result = install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("Failed to copy config file"));
+    assert!(!is_installed(&bin_dir, "echo_test"));
+    assert!(!get_config_file_path(&bin_dir.join("echo_test")).exists());
+    assert!(!InstallMetadata::filename(&bin_dir.join("echo_test")).exists());
+  }
+
+  #[test]
+  fn install_with_no_config() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags {
+        no_config: true,
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let content = fs::read_to_string(file_path).unwrap();
+    assert!(!content.contains("--no-config"));
+    assert!(!content.contains("--config"));
+    assert!(!content.contains("tsconfig.json"));
+    let (flags, _) = get_reinstall_flags(
+      "echo_test",
+      Some(temp_dir.path().to_path_buf()),
+      None,
+    )
+    .unwrap();
+    assert!(flags.no_config);
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn install_bin_dir_symlink() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let target_dir = temp_dir.path().join("target");
+    std::os::unix::fs::symlink(&target_dir, &bin_dir).unwrap();
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+
+    let err = install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .unwrap_err();
+    assert_eq!(err.to_string(), "Installation path is a broken symlink");
+    assert!(!target_dir.exists());
+
+    fs::create_dir(&target_dir).unwrap();
+    install(Flags::default(), install_flags, &mut io::sink())
+      .expect("Install failed");
+    assert!(target_dir.join("echo_test").exists());
+  }
+
+  #[test]
+  fn install_deno_path() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let deno_path = if cfg!(windows) {
+      PathBuf::from("C:\\Program Files\\deno\\deno.exe")
+    } else {
+      PathBuf::from("/opt/deno/bin/deno")
+    };
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        targets: vec!["sh".to_string(), "cmd".to_string(), "ps1".to_string()],
+        deno_path: Some(deno_path.clone()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let deno_path = deno_path.to_string_lossy();
+    let sh = fs::read_to_string(bin_dir.join("echo_test")).unwrap();
+    assert!(sh.contains(&format!("\n\"{}\" \"run\"", deno_path)));
+    let cmd = fs::read_to_string(bin_dir.join("echo_test.cmd")).unwrap();
+    assert!(cmd.contains(&format!("\n@\"{}\" \"run\"", deno_path)));
+    assert!(!cmd.contains("@deno.exe"));
+    let ps1 = fs::read_to_string(bin_dir.join("echo_test.ps1")).unwrap();
+    assert!(ps1.contains(&format!("\n& '{}' 'run'", deno_path)));
+  }
+
+  #[test]
+  fn install_update() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      update: true,
+      ..InstallFlags::default()
+    };
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+
+    install(
+      Flags {
+        allow_net: true,
+        ..Flags::default()
+      },
+      install_flags.clone(),
+      &mut io::sink(),
+    )
+    .expect("Update failed");
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains("--allow-net"));
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/cat.ts".to_string(),
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Existing installation of another module found: http://localhost:4545/cli/tests/echo_server.ts. Aborting (Use -f to overwrite)."
+    );
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains("echo_server.ts"));
+  }
+
+  #[test]
+  fn install_force_different_module() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+
+    let mut out: Vec<u8> = vec![];
+    install(Flags::default(), install_flags.clone(), &mut out)
+      .expect("Install failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(!output.contains("Replacing different module"));
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/cat.ts".to_string(),
+        ..install_flags.clone()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(
+      "⚠️  Replacing different module: echo_test was installed from http://localhost:4545/cli/tests/echo_server.ts\n"
+    ));
+
+    // Without metadata, the URL is read from the executable.
+    fs::remove_file(bin_dir.join("echo_test.metadata.json")).unwrap();
+    let mut out: Vec<u8> = vec![];
+    install(Flags::default(), install_flags, &mut out).expect("Install failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(
+      "⚠️  Replacing different module: echo_test was installed from http://localhost:4545/cli/tests/cat.ts\n"
+    ));
+  }
+
+  #[test]
+  fn inspect_installed_shim() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags {
+        allow_net: true,
+        allow_read: true,
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--port".to_string(), "8080".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let shim_info =
+      inspect_shim(&get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert_eq!(
+      shim_info,
+      ShimInfo {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        flags: vec!["--allow-read".to_string(), "--allow-net".to_string()],
+        args: vec!["--port".to_string(), "8080".to_string()],
+      }
+    );
+  }
+
+  #[test]
+  fn inspect_shim_targets() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let deno_path = temp_dir.path().join("deno dir").join("deno");
+
+    install(
+      Flags {
+        seed: Some(1),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["it's \"100%\" $HOME".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        targets: vec!["sh".to_string(), "cmd".to_string(), "ps1".to_string()],
+        deno_path: Some(deno_path),
+        env: vec![("NO_COLOR".to_string(), "1".to_string())],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let expected = ShimInfo {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      flags: vec!["--seed".to_string(), "1".to_string()],
+      args: vec!["it's \"100%\" $HOME".to_string()],
+    };
+    for file_name in &["echo_test", "echo_test.cmd", "echo_test.ps1"] {
+      assert_eq!(inspect_shim(&bin_dir.join(file_name)).unwrap(), expected);
+    }
+  }
+
+  #[test]
+  fn inspect_shim_not_generated() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let file_path = temp_dir.path().join("echo_test");
+    fs::write(&file_path, "#!/bin/sh\necho \"run\"\n").unwrap();
+    let err = inspect_shim(&file_path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+  }
+
+  #[test]
+  fn install_parse_quoted_args() {
+    assert_eq!(
+      parse_quoted_args(
+        "#!/bin/sh\ndeno \"run\" \"a \\\"b\\\" \\$c\" \"\" \"$@\"\n"
+      ),
+      vec!["run", "a \"b\" $c", "", "$@"]
+    );
+    assert_eq!(
+      parse_quoted_args("@deno.exe \"run\" \"a \"\"b\"\"\" %*\n"),
+      vec!["run", "a \"b\""]
+    );
+  }
+
+  #[test]
+  fn install_verify() {
+    let _guard = ENV_LOCK.lock().ok();
+    let _http_server_guard = test_util::http_server();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/does_not_exist.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        verify: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().starts_with(
+      "Module http://localhost:4545/cli/tests/does_not_exist.ts can't be fetched"
+    ));
+    assert!(err.to_string().contains("404"));
+    assert!(!get_executable_path(&bin_dir, "echo_test").exists());
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        verify: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    assert!(get_executable_path(&bin_dir, "echo_test").exists());
+  }
+
+  #[test]
+  fn install_pin() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let root = Some(temp_dir.path().to_path_buf());
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: root.clone(),
+      ..InstallFlags::default()
+    };
+
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+    let manifest = load_manifest("echo_test", root.clone(), None).unwrap();
+    assert_eq!(manifest.module_checksum, None);
+
+    // Not downloaded again to be hashed.
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        pin: true,
+        force: true,
+        ..install_flags.clone()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert_eq!(
+      err.to_string(),
+      "Module http://localhost:4545/cli/tests/echo_server.ts has to be \
+       fetched and cached before it can be pinned"
+    );
+    let manifest = load_manifest("echo_test", root.clone(), None).unwrap();
+    assert_eq!(manifest.module_checksum, None);
+
+    let checksum = crate::checksum::gen(&[b"console.log('cached');"]);
+    install(
+      Flags::default(),
+      InstallFlags {
+        pin: true,
+        module_checksum: Some(checksum.clone()),
+        force: true,
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let manifest = load_manifest("echo_test", root.clone(), None).unwrap();
+    assert_eq!(manifest.module_checksum, Some(checksum));
+
+    // Without fetching the module, the recorded checksum is kept.
+    reinstall("echo_test".to_string(), root.clone(), None, &mut io::sink())
+      .expect("Reinstall failed");
+    assert_eq!(
+      load_manifest("echo_test", root, None)
+        .unwrap()
+        .module_checksum,
+      manifest.module_checksum
+    );
+  }
+
+  #[test]
+  fn install_json() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        json: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let result: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(result["name"], "echo_test");
+    assert_eq!(result["path"], file_path.to_string_lossy().as_ref());
+    assert_eq!(
+      result["module_url"],
+      "http://localhost:4545/cli/tests/echo_server.ts"
+    );
+    assert_eq!(result["path_update_needed"], true);
+    assert_eq!(result["overwrote"], false);
+  }
+
+  #[test]
+  fn install_dry_run_json() {
+    // Warnings about the environment would end up in the JSON output.
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        dry_run: true,
+        json: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let preview: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(preview["name"], "echo_test");
+    assert_eq!(preview["path"], file_path.to_string_lossy().as_ref());
+    assert_eq!(preview["config_path"], serde_json::Value::Null);
+    assert_eq!(
+      preview["args"],
+      serde_json::json!([
+        "run",
+        "http://localhost:4545/cli/tests/echo_server.ts"
+      ])
+    );
+    let args = vec![
+      "run".to_string(),
+      "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+    ];
+    assert_eq!(
+      preview["content"],
+      ensure_trailing_newline(ExecutableKind::host().render(
+        None,
+        &[],
+        None,
+        &args
+      ))
+    );
+    assert!(!bin_dir.exists());
+  }
+
+  #[test]
+  fn install_root_env_var_home_expansion() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    fs::create_dir(temp_dir.path().join("something")).unwrap();
+    let home_env_var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    let original_home = env::var_os(home_env_var);
+    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
+    env::set_var(home_env_var, temp_dir.path());
+    env::set_var("DENO_INSTALL_ROOT", "~/something");
+
+    let installer_root = get_installer_root();
+
+    match original_home {
+      Some(home) => env::set_var(home_env_var, home),
+      None => env::remove_var(home_env_var),
+    }
+    match original_install_root {
+      Some(install_root) => env::set_var("DENO_INSTALL_ROOT", install_root),
+      None => env::remove_var("DENO_INSTALL_ROOT"),
+    }
+    assert_eq!(
+      installer_root.unwrap(),
+      temp_dir.path().join("something").canonicalize().unwrap()
+    );
+  }
+
+  #[test]
+  fn install_dry_run() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags {
+        allow_net: true,
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        dry_run: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    );
+
+    assert!(result.is_ok());
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    assert!(!file_path.exists());
+    assert!(!bin_dir.exists());
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(&format!("{}:", file_path.to_string_lossy())));
+    assert!(output.contains(&format!(
+      "args: run --allow-net --config {} http://localhost:4545/cli/tests/echo_server.ts",
+      get_config_file_path(&bin_dir.join("echo_test")).to_string_lossy()
+    )));
+
+    // The validation still happens.
+    let result = install(
+      Flags {
+        config_path: Some("missing_tsconfig.json".to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        dry_run: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn install_dry_run_force() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let flags = Flags {
+      config_path: Some(config_file_path.to_string_lossy().to_string()),
+      ..Flags::default()
+    };
+
+    install(
+      flags.clone(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let config_copy_path = get_config_file_path(&bin_dir.join("echo_test"));
+    let content = fs::read_to_string(&file_path).unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      flags,
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/subdir/main.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        force: true,
+        dry_run: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(&format!(
+      "Would overwrite:\n  {}\n  {}\n",
+      file_path.to_string_lossy(),
+      config_copy_path.to_string_lossy()
+    )));
+    assert!(output.contains(
+      "Would replace http://localhost:4545/cli/tests/echo_server.ts with http://localhost:4545/cli/tests/subdir/main.ts"
+    ));
+    assert_eq!(fs::read_to_string(&file_path).unwrap(), content);
+    assert_eq!(
+      InstallMetadata::read(&bin_dir.join("echo_test"))
+        .unwrap()
+        .module_url,
+      "http://localhost:4545/cli/tests/echo_server.ts"
+    );
+    assert!(!append_extension(&file_path, "bak").exists());
+  }
+
+  #[test]
+  fn install_names() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--verbose".to_string()],
+        names: vec!["add".to_string(), "remove".to_string(), "ls".to_string()],
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let metadata = InstallMetadata::read(&bin_dir.join("add")).unwrap();
+
+    for name in &["add", "remove", "ls"] {
+      let content =
+        fs::read_to_string(get_executable_path(&bin_dir, name)).unwrap();
+      let args = vec![
+        "run".to_string(),
+        "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+        name.to_string(),
+        "--verbose".to_string(),
+      ];
+      let template = ensure_trailing_newline(ExecutableKind::host().render(
+        None,
+        &[],
+        None,
+        &args,
+      ));
+      assert_eq!(
+        content,
+        ExecutableKind::host().add_header(
+          &template,
+          "http://localhost:4545/cli/tests/echo_server.ts",
+          metadata.installed_at,
+          None
+        )
+      );
+    }
+    assert_eq!(metadata.aliases, vec!["remove", "ls"]);
+    assert!(metadata.pass_name);
+
+    uninstall(
+      "add".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .expect("Uninstall failed");
+    for name in &["add", "remove", "ls"] {
+      assert!(!is_installed(&bin_dir, name));
+    }
+  }
+
+  #[test]
+  fn install_permission_args_order() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      print_args: true,
+      ..InstallFlags::default()
+    };
+    let flags = Flags {
+      allow_env: true,
+      allow_read: true,
+      read_allowlist: vec![PathBuf::from("/tmp")],
+      write_allowlist: vec![
+        PathBuf::from("/b"),
+        PathBuf::from("/a"),
+        PathBuf::from("/b"),
+      ],
+      net_allowlist: vec![
+        "b.com".to_string(),
+        "a.com".to_string(),
+        "a.com".to_string(),
+      ],
+      allow_hrtime: true,
+      ..Flags::default()
+    };
+
+    let mut out: Vec<u8> = vec![];
+    install(flags.clone(), install_flags.clone(), &mut out)
+      .expect("Install failed");
+    let args: Vec<String> = serde_json::from_slice(&out).unwrap();
+    assert_eq!(
+      args,
+      vec![
+        "run",
+        "--allow-read",
+        "--allow-write=/a,/b",
+        "--allow-net=a.com,b.com",
+        "--allow-env",
+        "--allow-hrtime",
+        "http://localhost:4545/cli/tests/echo_server.ts",
+      ]
+    );
+
+    // The same permissions, given in another order.
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags {
+        write_allowlist: vec![PathBuf::from("/a"), PathBuf::from("/b")],
+        net_allowlist: vec!["a.com".to_string(), "b.com".to_string()],
+        read_allowlist: vec![],
+        ..flags
+      },
+      install_flags,
+      &mut out,
+    )
+    .expect("Install failed");
+    assert_eq!(serde_json::from_slice::<Vec<String>>(&out).unwrap(), args);
+  }
+
+  #[test]
+  fn install_allow_all() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+    let flags = Flags {
+      allow_read: true,
+      allow_write: true,
+      allow_net: true,
+      net_allowlist: vec!["deno.land".to_string()],
+      allow_env: true,
+      allow_run: true,
+      allow_plugin: true,
+      allow_hrtime: true,
+      ..Flags::default()
+    };
+
+    install(flags.clone(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains(r#""run" "--allow-all" "http://"#));
+    for granular in &["--allow-read", "--allow-net", "--allow-hrtime"] {
+      assert!(!content.contains(granular));
+    }
+
+    // One missing permission keeps them listed.
+    install(
+      Flags {
+        allow_plugin: false,
+        ..flags
+      },
+      install_flags,
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(!content.contains("--allow-all"));
+    assert!(content.contains(r#""--allow-env" "--allow-run" "--allow-hrtime""#));
+  }
+
+  #[test]
+  fn install_print_args() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let flags = Flags {
+      allow_net: true,
+      allow_read: true,
+      read_allowlist: vec![PathBuf::from("/tmp")],
+      ca_file: Some("cert.pem".to_string()),
+      log_level: Some(Level::Error),
+      no_check: NoCheck::All,
+      unstable: true,
+      ..Flags::default()
+    };
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      args: vec!["--foobar".to_string()],
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      flags.clone(),
+      InstallFlags {
+        print_args: true,
+        ..install_flags.clone()
+      },
+      &mut out,
+    )
+    .expect("Install failed");
+    assert!(!bin_dir.exists());
+    let args: Vec<String> = serde_json::from_slice(&out).unwrap();
+
+    install(flags, install_flags, &mut io::sink()).expect("Install failed");
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    let metadata = InstallMetadata::read(&bin_dir.join("echo_test")).unwrap();
+    let template = ensure_trailing_newline(ExecutableKind::host().render(
+      None,
+      &[],
+      None,
+      &args,
+    ));
+    assert_eq!(
+      content,
+      ExecutableKind::host().add_header(
+        &template,
+        "http://localhost:4545/cli/tests/echo_server.ts",
+        metadata.installed_at,
+        None
+      )
+    );
+    assert_eq!(args[0], "run");
+    assert!(args.contains(&"--unstable".to_string()));
+  }
+
+  #[test]
+  #[cfg(windows)]
+  fn install_windows_ps1() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    assert!(bin_dir.join("echo_test.cmd").exists());
+    let content = fs::read_to_string(bin_dir.join("echo_test.ps1")).unwrap();
+    assert!(content.contains("deno.exe"));
+    assert!(content.contains("@args"));
+    assert!(content.contains("$LASTEXITCODE"));
+
+    uninstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .expect("Uninstall failed");
+    assert!(!bin_dir.join("echo_test.cmd").exists());
+    assert!(!bin_dir.join("echo_test.ps1").exists());
+  }
+
+  #[test]
+  #[cfg(windows)]
+  fn install_root_env_var_home_expansion_windows() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    fs::create_dir(temp_dir.path().join("something")).unwrap();
+    let original_home = env::var_os("USERPROFILE");
+    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
+    env::set_var("USERPROFILE", temp_dir.path());
+
+    let mut installer_roots = vec![];
+    for install_root in
+      &["~\\something", "~/something", "%USERPROFILE%\\something"]
+    {
+      env::set_var("DENO_INSTALL_ROOT", install_root);
+      installer_roots.push(get_installer_root());
+    }
+
+    match original_home {
+      Some(home) => env::set_var("USERPROFILE", home),
+      None => env::remove_var("USERPROFILE"),
+    }
+    match original_install_root {
+      Some(install_root) => env::set_var("DENO_INSTALL_ROOT", install_root),
+      None => env::remove_var("DENO_INSTALL_ROOT"),
+    }
+    let expected = temp_dir.path().join("something").canonicalize().unwrap();
+    for installer_root in installer_roots {
+      assert_eq!(installer_root.unwrap(), expected);
+    }
+  }
+
+  #[test]
+  fn install_cmd_exit_code() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        targets: vec!["cmd".to_string()],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let cmd = fs::read_to_string(bin_dir.join("echo_test.cmd")).unwrap();
+    assert!(cmd.ends_with("%*\n@exit /b %errorlevel%\n"));
+  }
+
+  #[test]
+  fn install_many_results() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let modules = vec![
+      "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      "https://example.com".to_string(),
+      "http://localhost:4545/cli/tests/subdir/main.ts".to_string(),
+      "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+    ];
+
+    let results = install_many(
+      Flags::default(),
+      InstallFlags {
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      modules
+        .iter()
+        .map(|module| (module.clone(), None))
+        .collect(),
+      &mut io::sink(),
+    );
+
+    assert_eq!(results.len(), 4);
+    for (i, (module_url, _)) in results.iter().enumerate() {
+      assert_eq!(module_url, &modules[i]);
+    }
+    assert_eq!(
+      results[0].1.as_ref().unwrap(),
+      &InstallResult {
+        name: "echo_server".to_string(),
+        path: get_executable_path(&bin_dir, "echo_server"),
+        installed: true,
+        config_path: None,
+        path_update_needed: true,
+        overwrote: false,
+      }
+    );
+    assert!(results[1]
+      .1
+      .as_ref()
+      .unwrap_err()
+      .to_string()
+      .contains("could not be inferred"));
+    assert_eq!(results[2].1.as_ref().unwrap().name, "subdir");
+    assert!(results[3]
+      .1
+      .as_ref()
+      .unwrap_err()
+      .to_string()
+      .contains("Existing installation found"));
+
+    let mut out: Vec<u8> = vec![];
+    write_install_summary(&results, true, &mut out).unwrap();
+    let summary: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(summary[0]["name"], "echo_server");
+    assert_eq!(summary[1]["error"]["code"], "name_inference");
+    assert_eq!(summary[3]["error"]["code"], "existing_installation");
+  }
+
+  #[test]
+  fn install_local() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let original_dir = env::current_dir().unwrap();
+    env::set_current_dir(temp_dir.path()).unwrap();
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        local: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
     );
+    env::set_current_dir(original_dir).unwrap();
+
+    let bin_dir = temp_dir.path().canonicalize().unwrap().join(".deno/bin");
     assert_eq!(
-      infer_name_from_url(
-        &Url::parse("https://example.com/abc/mod.ts").unwrap()
-      ),
-      Some("abc".to_string())
+      result.expect("Install failed").path,
+      get_executable_path(&bin_dir, "echo_test")
     );
+    assert!(get_executable_path(&bin_dir, "echo_test").exists());
+    let output = String::from_utf8(out).unwrap();
+    assert!(
+      output.contains(&format!("Add {} to PATH", bin_dir.to_string_lossy()))
+    );
+  }
+
+  #[test]
+  fn install_quiet_runtime() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    install(
+      Flags {
+        log_level: Some(Level::Debug),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        quiet_runtime: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains(r#""run" "--quiet""#));
+    assert!(!content.contains("--log-level"));
+  }
+
+  #[test]
+  fn install_with_import_map() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let import_map_path = temp_dir.path().join("import_map.json");
+    fs::write(&import_map_path, r#"{ "imports": {} }"#).unwrap();
+
+    install(
+      Flags {
+        import_map_path: Some(import_map_path.to_string_lossy().to_string()),
+        unstable: true,
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let import_map_copy_path = bin_dir.join("echo_test.import_map.json");
     assert_eq!(
-      infer_name_from_url(
-        &Url::parse("https://example.com/abc/index.ts").unwrap()
-      ),
-      Some("abc".to_string())
+      fs::read_to_string(&import_map_copy_path).unwrap(),
+      r#"{ "imports": {} }"#
     );
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains(&format!(
+      r#""--importmap" "{}""#,
+      import_map_copy_path.to_string_lossy()
+    )));
+    let metadata = InstallMetadata::read(&bin_dir.join("echo_test")).unwrap();
     assert_eq!(
-      infer_name_from_url(
-        &Url::parse("https://example.com/abc/cli.ts").unwrap()
-      ),
-      Some("abc".to_string())
+      metadata.import_map_path,
+      Some(import_map_copy_path.to_string_lossy().to_string())
+    );
+  }
+
+  #[test]
+  fn install_check_name() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let root = Some(temp_dir.path().to_path_buf());
+
+    let mut out: Vec<u8> = vec![];
+    check_name("echo_test", root.clone(), None, &mut out)
+      .expect("check failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("✅ echo_test can be installed"));
+
+    let err =
+      check_name("run", root.clone(), None, &mut io::sink()).unwrap_err();
+    assert!(err.to_string().contains("Executable name run is reserved"));
+    let err =
+      check_name("1password", root.clone(), None, &mut io::sink()).unwrap_err();
+    assert!(err
+      .to_string()
+      .contains("Invalid executable name: 1password"));
+
+    fs::write(get_executable_path(&bin_dir, "echo_test"), "").unwrap();
+    let err = check_name("echo_test", root, None, &mut io::sink()).unwrap_err();
+    assert!(err.to_string().contains("Existing installation found"));
+  }
+
+  #[test]
+  fn install_with_lock_and_cached_only() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let lock_path = temp_dir.path().join("lock.json");
+    fs::write(&lock_path, "{}").unwrap();
+
+    install(
+      Flags {
+        lock: Some(lock_path.to_string_lossy().to_string()),
+        cached_only: true,
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let lock_copy_path = bin_dir.join("echo_test.lock.json");
+    assert_eq!(fs::read_to_string(&lock_copy_path).unwrap(), "{}");
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains(&format!(
+      r#""--lock" "{}""#,
+      lock_copy_path.to_string_lossy()
+    )));
+    assert!(content.contains(r#""--cached-only""#));
+    let metadata = InstallMetadata::read(&bin_dir.join("echo_test")).unwrap();
+    assert_eq!(
+      metadata.lock_path,
+      Some(lock_copy_path.to_string_lossy().to_string())
+    );
+  }
+
+  #[test]
+  fn install_shadowed_in_path() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let other_dir = temp_dir.path().join("other");
+    fs::create_dir(&other_dir).unwrap();
+    let other_file_path = if cfg!(windows) {
+      other_dir.join("echo_test.exe")
+    } else {
+      other_dir.join("echo_test")
+    };
+    fs::write(&other_file_path, "").unwrap();
+    let original_path = env::var_os("PATH");
+    env::set_var("PATH", &other_dir);
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut out,
     );
+    match original_path {
+      Some(path) => env::set_var("PATH", path),
+      None => env::remove_var("PATH"),
+    }
+
+    result.expect("Install failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(&format!(
+      "⚠️  {} comes first in PATH, running echo_test will run it instead",
+      other_file_path.to_string_lossy()
+    )));
+  }
+
+  #[test]
+  fn install_config_template() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(
+      &config_file_path,
+      r#"{ "compilerOptions": { "outDir": "{{INSTALL_DIR}}/{{NAME}}_out" } }"#,
+    )
+    .unwrap();
+    let flags = Flags {
+      config_path: Some(config_file_path.to_string_lossy().to_string()),
+      ..Flags::default()
+    };
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      config_template: true,
+      ..InstallFlags::default()
+    };
+
+    install(flags.clone(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+
+    let config_copy_path = get_config_file_path(&bin_dir.join("echo_test"));
+    let config: serde_json::Value =
+      serde_json::from_str(&fs::read_to_string(&config_copy_path).unwrap())
+        .unwrap();
+    let install_dir = temp_dir.path().canonicalize().unwrap().join("bin");
     assert_eq!(
-      infer_name_from_url(&Url::parse("https://example.com/main.ts").unwrap()),
-      Some("main".to_string())
+      config["compilerOptions"]["outDir"],
+      format!("{}/echo_test_out", install_dir.to_string_lossy())
     );
+
+    // The substituted config must still be JSON.
+    fs::write(&config_file_path, r#"{ "outDir": {{INSTALL_DIR}} }"#).unwrap();
+    let err = install(
+      flags,
+      InstallFlags {
+        force: true,
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(err
+      .to_string()
+      .contains("not valid JSON after substitution"));
+  }
+
+  #[test]
+  fn uninstall_json() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let size = fs::metadata(&file_path).unwrap().len()
+      + fs::metadata(bin_dir.join("echo_test.metadata.json"))
+        .unwrap()
+        .len();
+
+    let mut out: Vec<u8> = vec![];
+    uninstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      true,
+      &mut out,
+    )
+    .expect("Uninstall failed");
+
+    let result: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(result["name"], "echo_test");
     assert_eq!(
-      infer_name_from_url(&Url::parse("https://example.com").unwrap()),
-      None
+      result["removed"][0]["path"],
+      file_path.to_string_lossy().as_ref()
+    );
+    assert_eq!(result["removed"].as_array().unwrap().len(), 2);
+    assert_eq!(result["freed_bytes"], size);
+  }
+
+  #[test]
+  fn load_manifest_round_trip() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    install(
+      Flags {
+        allow_net: true,
+        allow_read: true,
+        unstable: true,
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--foobar".to_string(), "baz".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let manifest =
+      load_manifest("echo_test", Some(temp_dir.path().to_path_buf()), None)
+        .unwrap();
+    assert_eq!(manifest.name, "echo_test");
+    assert_eq!(
+      manifest.module_url,
+      "http://localhost:4545/cli/tests/echo_server.ts"
+    );
+    assert_eq!(
+      manifest.flag_args,
+      vec!["--allow-read", "--allow-net", "--unstable"]
+    );
+    assert_eq!(manifest.script_args, vec!["--foobar", "baz"]);
+    assert_eq!(
+      manifest.args,
+      vec![
+        "run",
+        "--allow-read",
+        "--allow-net",
+        "--unstable",
+        "http://localhost:4545/cli/tests/echo_server.ts",
+        "--foobar",
+        "baz"
+      ]
+    );
+    assert_eq!(
+      manifest,
+      InstallMetadata::read(&bin_dir.join("echo_test")).unwrap()
+    );
+
+    uninstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      false,
+      &mut io::sink(),
+    )
+    .unwrap();
+    let err =
+      load_manifest("echo_test", Some(temp_dir.path().to_path_buf()), None)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert_eq!(err.to_string(), "No install manifest found for echo_test");
+  }
+
+  #[test]
+  fn reinstall_recreates_executable() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    let lock_file_path = temp_dir.path().join("lock.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    fs::write(&lock_file_path, "{}").unwrap();
+    install(
+      Flags {
+        allow_net: true,
+        no_check: NoCheck::All,
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        lock: Some(lock_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--foobar".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        aliases: vec!["et".to_string()],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let content = fs::read_to_string(&file_path).unwrap();
+    fs::remove_file(&file_path).unwrap();
+
+    reinstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      &mut io::sink(),
+    )
+    .expect("Reinstall failed");
+
+    assert_eq!(fs::read_to_string(&file_path).unwrap(), content);
+    assert!(get_executable_path(&bin_dir, "et").exists());
+    assert!(content.contains("http://localhost:4545/cli/tests/echo_server.ts"));
+    assert!(content.contains("--allow-net"));
+    assert!(content.contains("--no-check"));
+    assert_eq!(
+      fs::read_to_string(bin_dir.join("echo_test.lock.json")).unwrap(),
+      "{}"
     );
+    assert!(bin_dir.join("echo_test.tsconfig.json").exists());
+  }
+
+  #[test]
+  fn regenerate_executables_from_manifest() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+
+    install(
+      Flags {
+        allow_read: true,
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        args: vec!["--foobar".to_string()],
+        deno_args: vec!["--reload".to_string()],
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        aliases: vec!["echo_alias".to_string()],
+        env: vec![("FOO".to_string(), "bar".to_string())],
+        targets: vec!["sh".to_string(), "ps1".to_string()],
+        deno_path: Some(PathBuf::from("/opt/deno/bin/deno")),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let file_path = bin_dir.join("echo_test");
+    let alias_path = bin_dir.join("echo_alias");
+    let ps1_path = bin_dir.join("echo_test.ps1");
+    let original = fs::read_to_string(&file_path).unwrap();
+    let original_alias = fs::read_to_string(&alias_path).unwrap();
+    let original_ps1 = fs::read_to_string(&ps1_path).unwrap();
+    let metadata = InstallMetadata::read(&bin_dir.join("echo_test")).unwrap();
+    fs::remove_file(&file_path).unwrap();
+    fs::remove_file(&ps1_path).unwrap();
+    fs::write(&alias_path, "corrupted").unwrap();
+
+    let mut out = vec![];
+    regenerate_executables(
+      "echo_test",
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      &mut out,
+    )
+    .unwrap();
+
+    assert_eq!(fs::read_to_string(&file_path).unwrap(), original);
+    assert_eq!(fs::read_to_string(&alias_path).unwrap(), original_alias);
+    assert_eq!(fs::read_to_string(&ps1_path).unwrap(), original_ps1);
+    assert!(original.contains("/opt/deno/bin/deno"));
     assert_eq!(
-      infer_name_from_url(&Url::parse("file:///abc/server.ts").unwrap()),
-      Some("server".to_string())
+      InstallMetadata::read(&bin_dir.join("echo_test")).unwrap(),
+      metadata
     );
+    let (_, install_flags) = get_reinstall_flags(
+      "echo_test",
+      Some(temp_dir.path().to_path_buf()),
+      None,
+    )
+    .unwrap();
     assert_eq!(
-      infer_name_from_url(&Url::parse("file:///abc/main.ts").unwrap()),
-      Some("abc".to_string())
+      install_flags.deno_path,
+      Some(PathBuf::from("/opt/deno/bin/deno"))
     );
-    assert_eq!(
-      infer_name_from_url(&Url::parse("file:///main.ts").unwrap()),
-      Some("main".to_string())
+    assert_eq!(install_flags.targets, vec!["sh", "ps1"]);
+    let out = String::from_utf8(out).unwrap();
+    assert!(
+      out.contains(&format!("✅ Regenerated {}", file_path.to_string_lossy()))
     );
-    assert_eq!(infer_name_from_url(&Url::parse("file:///").unwrap()), None);
   }
 
   #[test]
-  fn install_basic() {
+  fn reinstall_without_manifest() {
     let _guard = ENV_LOCK.lock().ok();
     let temp_dir = TempDir::new().expect("tempdir fail");
-    let temp_dir_str = temp_dir.path().to_string_lossy().to_string();
-    // NOTE: this test overrides environmental variables
-    // don't add other tests in this file that mess with "HOME" and "USEPROFILE"
-    // otherwise transient failures are possible because tests are run in parallel.
-    // It means that other test can override env vars when this test is running.
-    let original_home = env::var_os("HOME");
-    let original_user_profile = env::var_os("HOME");
-    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
-    env::set_var("HOME", &temp_dir_str);
-    env::set_var("USERPROFILE", &temp_dir_str);
-    env::set_var("DENO_INSTALL_ROOT", "");
+    fs::create_dir_all(temp_dir.path().join("bin")).unwrap();
+    let err = reinstall(
+      "echo_test".to_string(),
+      Some(temp_dir.path().to_path_buf()),
+      None,
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert!(err.to_string().contains("reinstall it manually"));
+  }
+
+  #[test]
+  fn install_module_file() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let module_file_path = temp_dir.path().join("module_url.txt");
+    fs::write(
+      &module_file_path,
+      "\n  http://localhost:4545/cli/tests/echo_server.ts  \nignored\n",
+    )
+    .unwrap();
 
     install(
       Flags::default(),
-      "http://localhost:4545/cli/tests/echo_server.ts",
-      vec![],
-      Some("echo_test".to_string()),
+      InstallFlags {
+        module_file: Some(module_file_path),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains("http://localhost:4545/cli/tests/echo_server.ts"));
+    assert!(!content.contains("ignored"));
+  }
+
+  #[test]
+  fn install_module_stdin() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let mut install_flags = InstallFlags {
+      module_url: "-".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+    let mut input =
+      io::Cursor::new(" http://localhost:4545/cli/tests/echo_server.ts\n");
+    resolve_module_stdin(&mut install_flags, &mut input).unwrap();
+    install(Flags::default(), install_flags, &mut io::sink())
+      .expect("Install failed");
+
+    let content =
+      fs::read_to_string(get_executable_path(&bin_dir, "echo_test")).unwrap();
+    assert!(content.contains("http://localhost:4545/cli/tests/echo_server.ts"));
+
+    let mut install_flags = InstallFlags {
+      module_url: "-".to_string(),
+      ..InstallFlags::default()
+    };
+    let err =
+      resolve_module_stdin(&mut install_flags, &mut io::Cursor::new(""))
+        .unwrap_err();
+    assert!(err.to_string().contains("--name"));
+    install_flags.name = Some("echo_test".to_string());
+    let err =
+      resolve_module_stdin(&mut install_flags, &mut io::Cursor::new("\n"))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "No module was given on stdin");
+  }
+
+  #[test]
+  fn install_module_file_invalid_url() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let module_file_path = temp_dir.path().join("module_url.txt");
+    fs::write(&module_file_path, "not a url\n").unwrap();
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_file: Some(module_file_path.clone()),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(err
+      .to_string()
+      .contains("doesn't contain a valid module URL"));
+
+    fs::write(&module_file_path, "\n \n").unwrap();
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_file: Some(module_file_path),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("doesn't contain a module URL"));
+  }
+
+  #[cfg(not(windows))]
+  #[test]
+  fn install_mode() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        mode: Some(0o700),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+
+    let file_path = bin_dir.join("echo_test");
+    let mode = fs::metadata(&file_path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+  }
+
+  #[test]
+  fn install_mode_without_owner_execute() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        mode: Some(0o644),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "File mode 644 doesn't let the owner execute the script"
+    );
+    assert!(!temp_dir.path().join("bin").exists());
+  }
+
+  #[test]
+  fn install_copies_in_deno_dir() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let deno_dir = temp_dir.path().join("deno_dir");
+    let config_path = temp_dir.path().join("tsconfig.json");
+    fs::write(&config_path, "{}").unwrap();
+    let original_deno_dir = env::var_os("DENO_DIR");
+    env::set_var("DENO_DIR", &deno_dir);
+
+    let install_in = |root: &Path, copies_in_deno_dir: bool| {
+      install(
+        Flags {
+          config_path: Some(config_path.to_string_lossy().to_string()),
+          ..Flags::default()
+        },
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some("echo_test".to_string()),
+          root: Some(root.to_path_buf()),
+          copies_in_deno_dir,
+          ..InstallFlags::default()
+        },
+        &mut std::io::sink(),
+      )
+    };
+    let root_a = temp_dir.path().join("a");
+    let root_b = temp_dir.path().join("b");
+    let root_c = temp_dir.path().join("c");
+    for root in &[&root_a, &root_b, &root_c] {
+      fs::create_dir(root).unwrap();
+    }
+    let results = vec![
+      install_in(&root_a, true),
+      install_in(&root_b, true),
+      install_in(&root_c, false),
+    ];
+    let copy_path = |root: &Path| {
+      let dirs = get_install_dirs(Some(root.to_path_buf()), None).unwrap();
+      get_config_file_path(
+        &get_copies_path(&dirs.sidecar, "echo_test", true).unwrap(),
+      )
+    };
+    let copy_a = copy_path(&root_a);
+    let copy_b = copy_path(&root_b);
+    let content_a =
+      fs::read_to_string(get_executable_path(&root_a.join("bin"), "echo_test"));
+    let config_c = root_c.join("bin").join("echo_test.tsconfig.json");
+    let config_c_exists = config_c.exists();
+    // The copies are found from the metadata when DENO_DIR has moved since.
+    env::set_var("DENO_DIR", temp_dir.path().join("other_deno_dir"));
+    let uninstall_result = uninstall(
+      "echo_test".to_string(),
+      Some(root_a),
       None,
       false,
+      &mut std::io::sink(),
+    );
+    match original_deno_dir {
+      Some(value) => env::set_var("DENO_DIR", value),
+      None => env::remove_var("DENO_DIR"),
+    }
+    for result in results {
+      result.unwrap();
+    }
+    assert!(copy_a.starts_with(deno_dir.join("installs")));
+    assert_ne!(copy_a, copy_b);
+    assert_eq!(fs::read_to_string(&copy_b).unwrap(), "{}");
+    assert!(config_c_exists);
+    assert!(content_a
+      .unwrap()
+      .contains(&copy_a.to_string_lossy().to_string()));
+    uninstall_result.unwrap();
+    assert!(!copy_a.exists());
+    assert!(!copy_a.parent().unwrap().parent().unwrap().exists());
+    assert!(copy_b.exists());
+  }
+
+  #[test]
+  fn install_copies_in_deno_dir_without_deno_dir() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let original_deno_dir = env::var_os("DENO_DIR");
+    env::remove_var("DENO_DIR");
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        copies_in_deno_dir: true,
+        ..InstallFlags::default()
+      },
+      &mut std::io::sink(),
+    );
+    if let Some(value) = original_deno_dir {
+      env::set_var("DENO_DIR", value);
+    }
+    assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn install_result_reports_path_update() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let original_path = env::var_os("PATH");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+
+    env::set_var("PATH", "");
+    let outside_path =
+      install(Flags::default(), install_flags.clone(), &mut io::sink());
+    env::set_var("PATH", &bin_dir);
+    let mut out: Vec<u8> = vec![];
+    let in_path = install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("echo_test2".to_string()),
+        ..install_flags
+      },
+      &mut out,
+    );
+    match original_path {
+      Some(value) => env::set_var("PATH", value),
+      None => env::remove_var("PATH"),
+    }
+
+    let outside_path = outside_path.unwrap();
+    assert!(outside_path.path_update_needed);
+    assert_eq!(outside_path.config_path, None);
+    let in_path = in_path.unwrap();
+    assert!(!in_path.path_update_needed);
+    let mut expected: Vec<u8> = vec![];
+    write_install_result(&in_path, true, None, &mut expected).unwrap();
+    assert!(String::from_utf8(out)
+      .unwrap()
+      .ends_with(&String::from_utf8(expected).unwrap()));
+  }
+
+  #[test]
+  fn install_name_path_traversal() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("../evil".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
     )
-    .expect("Install failed");
+    .unwrap_err();
+    assert_eq!(err.code(), "name_with_path_separator");
+    assert!(!get_executable_path(temp_dir.path(), "evil").exists());
 
-    let mut file_path = temp_dir.path().join(".deno/bin/echo_test");
+    assert!(is_inside_dir(&bin_dir, &bin_dir.join("echo_test")));
+    assert!(is_inside_dir(&bin_dir, &bin_dir.join("@scope/echo_test")));
+    assert!(!is_inside_dir(&bin_dir, &bin_dir.join("../evil")));
+    assert!(!is_inside_dir(&bin_dir, &bin_dir.join("/evil")));
+    assert!(!is_inside_dir(&bin_dir, &bin_dir));
+  }
+
+  #[test]
+  fn install_quiet_path_hint() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let original_path = env::var_os("PATH");
+    env::set_var("PATH", "");
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        quiet_path_hint: true,
+        ..InstallFlags::default()
+      },
+      &mut out,
+    );
+    match original_path {
+      Some(value) => env::set_var("PATH", value),
+      None => env::remove_var("PATH"),
+    }
+
+    assert!(result.unwrap().path_update_needed);
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("✅ Successfully installed echo_test"));
+    assert!(output.contains(&format!(
+      "Run it with: {}",
+      get_executable_path(&bin_dir, "echo_test").to_string_lossy()
+    )));
+    assert!(!output.contains("to PATH"));
+    assert!(!output.contains("export PATH"));
+  }
+
+  #[test]
+  fn install_root_with_spaces() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let root = temp_dir.path().join("Program Files").join("deno");
+    fs::create_dir_all(&root).unwrap();
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    let original_path = env::var_os("PATH");
+    env::set_var("PATH", "");
+
+    let result = install(
+      Flags {
+        config_path: Some(config_file_path.to_string_lossy().to_string()),
+        ..Flags::default()
+      },
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(root.clone()),
+        quiet_path_hint: true,
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    );
+    match original_path {
+      Some(value) => env::set_var("PATH", value),
+      None => env::remove_var("PATH"),
+    }
+
+    let result = result.expect("Install failed");
+    let bin_dir = root.canonicalize().unwrap().join("bin");
+    let config_copy_path = bin_dir.join("echo_test.tsconfig.json");
+    let shim_info = inspect_shim(&result.path).unwrap();
+    assert_eq!(
+      shim_info.flags,
+      vec!["--config", config_copy_path.to_str().unwrap()]
+    );
+    let content = fs::read_to_string(&result.path).unwrap();
     if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
+      assert!(content.contains(&format!(
+        "\"--config\" \"{}\"",
+        config_copy_path.to_string_lossy()
+      )));
+    } else {
+      assert!(content.contains(&format!(
+        "\"--config\" \"{}\"",
+        escape_sh(&config_copy_path.to_string_lossy())
+      )));
     }
 
-    assert!(file_path.exists());
+    for (shell, path_command, run_command) in &[
+      (
+        "sh",
+        format!("export PATH=\"{}:$PATH\"", bin_dir.to_string_lossy()),
+        format!("\"{}\"", result.path.to_string_lossy()),
+      ),
+      (
+        "cmd",
+        format!("set \"PATH=%PATH%;{}\"", bin_dir.to_string_lossy()),
+        format!("\"{}\"", result.path.to_string_lossy()),
+      ),
+      (
+        "powershell",
+        format!("$env:PATH += ';{}'", bin_dir.to_string_lossy()),
+        format!("& '{}'", result.path.to_string_lossy()),
+      ),
+    ] {
+      let mut out: Vec<u8> = vec![];
+      write_install_result(&result, true, Some(shell), &mut out).unwrap();
+      let output = String::from_utf8(out).unwrap();
+      assert!(output.contains(&format!("    {}\n", path_command)));
+      assert!(output.contains(&format!("Run it with: {}\n", run_command)));
+    }
+  }
 
-    let content = fs::read_to_string(file_path).unwrap();
-    // It's annoying when shell scripts don't have NL at the end.
-    assert_eq!(content.chars().last().unwrap(), '\n');
+  #[test]
+  fn install_path_command_quoting() {
+    let dir = Path::new("/home/it's me/$HOME/deno \"bin\"");
+    assert_eq!(
+      get_path_command(dir, "sh"),
+      r#"export PATH="/home/it's me/\$HOME/deno \"bin\":$PATH""#
+    );
+    assert_eq!(
+      get_path_command(dir, "fish"),
+      r#"set -gx PATH "/home/it's me/\$HOME/deno \"bin\"" $PATH"#
+    );
+    assert_eq!(
+      get_path_command(dir, "pwsh"),
+      r#"$env:PATH += ';/home/it''s me/$HOME/deno "bin"'"#
+    );
+  }
 
-    assert!(content
-      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
-    if let Some(home) = original_home {
-      env::set_var("HOME", home);
+  #[cfg(not(windows))]
+  #[test]
+  fn install_modify_path() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let home_dir = temp_dir.path().join("home");
+    fs::create_dir_all(&home_dir).unwrap();
+    let profile = home_dir.join(".bashrc");
+    fs::write(&profile, "alias ll='ls -l'").unwrap();
+    let original_home = env::var_os("HOME");
+    let original_shell = env::var_os("SHELL");
+    let original_path = env::var_os("PATH");
+    env::set_var("HOME", &home_dir);
+    env::set_var("SHELL", "/bin/bash");
+    env::set_var("PATH", "");
+
+    let mut outputs = vec![];
+    let mut results = vec![];
+    for name in &["echo_test", "echo_test2"] {
+      let mut out: Vec<u8> = vec![];
+      results.push(install(
+        Flags::default(),
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some(name.to_string()),
+          root: Some(temp_dir.path().to_path_buf()),
+          modify_path: true,
+          ..InstallFlags::default()
+        },
+        &mut out,
+      ));
+      outputs.push(String::from_utf8(out).unwrap());
     }
-    if let Some(user_profile) = original_user_profile {
-      env::set_var("USERPROFILE", user_profile);
+    for (var, value) in &[
+      ("HOME", original_home),
+      ("SHELL", original_shell),
+      ("PATH", original_path),
+    ] {
+      match value {
+        Some(value) => env::set_var(var, value),
+        None => env::remove_var(var),
+      }
     }
-    if let Some(install_root) = original_install_root {
-      env::set_var("DENO_INSTALL_ROOT", install_root);
+
+    for result in results {
+      result.expect("Install failed");
     }
+    let bin_dir = temp_dir.path().canonicalize().unwrap().join("bin");
+    let command =
+      format!("export PATH=\"{}:$PATH\"", bin_dir.to_string_lossy());
+    assert_eq!(
+      fs::read_to_string(&profile).unwrap(),
+      format!("alias ll='ls -l'\n{}\n", command)
+    );
+    assert!(outputs[0].contains(&format!(
+      "✅ Added {} to PATH in {}",
+      bin_dir.to_string_lossy(),
+      profile.to_string_lossy()
+    )));
+    assert!(!outputs[0].contains("    export PATH"));
+    assert!(outputs[1].contains(&format!(
+      "ℹ️  {} already adds {} to PATH",
+      profile.to_string_lossy(),
+      bin_dir.to_string_lossy()
+    )));
   }
 
+  #[cfg(not(windows))]
   #[test]
-  fn install_unstable() {
+  fn install_config_copy_mode() {
+    let _guard = ENV_LOCK.lock().ok();
     let temp_dir = TempDir::new().expect("tempdir fail");
     let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
+    let config_file_path = temp_dir.path().join("test_tsconfig.json");
+    fs::write(&config_file_path, "{}").unwrap();
+    fs::set_permissions(&config_file_path, fs::Permissions::from_mode(0o777))
+      .unwrap();
+    let config_copy_path = bin_dir.join("echo_test.tsconfig.json");
 
-    install(
-      Flags {
-        unstable: true,
-        ..Flags::default()
+    for (mode, expected_mode) in &[(None, 0o644), (Some(0o700), 0o600)] {
+      install(
+        Flags {
+          config_path: Some(config_file_path.to_string_lossy().to_string()),
+          ..Flags::default()
+        },
+        InstallFlags {
+          module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+            .to_string(),
+          name: Some("echo_test".to_string()),
+          root: Some(temp_dir.path().to_path_buf()),
+          mode: *mode,
+          force: true,
+          ..InstallFlags::default()
+        },
+        &mut io::sink(),
+      )
+      .expect("Install failed");
+      let permissions = fs::metadata(&config_copy_path).unwrap().permissions();
+      assert_eq!(permissions.mode() & 0o777, *expected_mode);
+    }
+  }
+
+  #[test]
+  fn install_bin_subdir() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+
+    let mut out: Vec<u8> = vec![];
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        bin_subdir: Some("shims".to_string()),
+        ..install_flags.clone()
       },
-      "http://localhost:4545/cli/tests/echo_server.ts",
-      vec![],
-      Some("echo_test".to_string()),
-      Some(temp_dir.path().to_path_buf()),
-      false,
+      &mut out,
     )
     .expect("Install failed");
+    let shims_dir = temp_dir.path().canonicalize().unwrap().join("shims");
+    assert_eq!(result.path, get_executable_path(&shims_dir, "echo_test"));
+    assert!(result.path.exists());
+    assert!(!temp_dir.path().join("bin").exists());
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(&shims_dir.to_string_lossy().to_string()));
 
-    let mut file_path = bin_dir.join("echo_test");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
+    let result = install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("other".to_string()),
+        bin_subdir: Some(".".to_string()),
+        ..install_flags.clone()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    assert_eq!(
+      result.path,
+      get_executable_path(&temp_dir.path().canonicalize().unwrap(), "other")
+    );
+
+    for bin_subdir in &["../bin", "shims/bin", "shims\\bin", "", ".."] {
+      let err = install(
+        Flags::default(),
+        InstallFlags {
+          bin_subdir: Some(bin_subdir.to_string()),
+          ..install_flags.clone()
+        },
+        &mut io::sink(),
+      )
+      .unwrap_err();
+      assert!(err.to_string().contains("Invalid bin subdirectory"));
     }
+  }
 
-    assert!(file_path.exists());
+  #[test]
+  fn manage_bin_subdir_install() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let root = Some(temp_dir.path().to_path_buf());
+    install(
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: root.clone(),
+        bin_subdir: Some("shims".to_string()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
 
-    let content = fs::read_to_string(file_path).unwrap();
-    println!("this is the file path {:?}", content);
-    assert!(content.contains(
-      r#""run" "--unstable" "http://localhost:4545/cli/tests/echo_server.ts"#
-    ));
+    assert!(list_installed(root.clone(), None).unwrap().is_empty());
+    let names: Vec<String> = list_installed(root.clone(), Some("shims"))
+      .unwrap()
+      .into_iter()
+      .map(|script| script.name)
+      .collect();
+    assert_eq!(names, vec!["echo_test".to_string()]);
+    let (_, install_flags) =
+      get_reinstall_flags("echo_test", root.clone(), Some("shims")).unwrap();
+    assert_eq!(install_flags.bin_subdir.as_deref(), Some("shims"));
+    verify_install("echo_test", root.clone(), Some("shims")).unwrap();
+    uninstall(
+      "echo_test".to_string(),
+      root.clone(),
+      Some("shims"),
+      false,
+      &mut io::sink(),
+    )
+    .unwrap();
+    assert!(
+      !get_executable_path(&temp_dir.path().join("shims"), "echo_test")
+        .exists()
+    );
+    let err = uninstall(
+      "echo_test".to_string(),
+      root,
+      Some(".."),
+      false,
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("Invalid bin subdirectory"));
+  }
+
+  #[cfg(windows)]
+  #[test]
+  fn install_windows_device_name() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
+
+    for name in &["con", "NUL", "Com1", "lpt9"] {
+      let err = install(
+        Flags::default(),
+        InstallFlags {
+          name: Some(name.to_string()),
+          ..install_flags.clone()
+        },
+        &mut io::sink(),
+      )
+      .unwrap_err();
+      assert_eq!(
+        err.to_string(),
+        format!(
+          "Executable name {} is a device name reserved by Windows",
+          name
+        )
+      );
+    }
+    install(
+      Flags::default(),
+      InstallFlags {
+        name: Some("console".to_string()),
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .expect("Install failed");
+    assert!(get_executable_path(&bin_dir, "console").exists());
   }
 
   #[test]
-  fn install_inferred_name() {
+  fn install_name_differs_from_inferred() {
     let temp_dir = TempDir::new().expect("tempdir fail");
-    let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
 
+    let mut out: Vec<u8> = vec![];
     install(
       Flags::default(),
-      "http://localhost:4545/cli/tests/echo_server.ts",
-      vec![],
-      None,
-      Some(temp_dir.path().to_path_buf()),
-      false,
+      InstallFlags {
+        name: Some("different".to_string()),
+        ..install_flags.clone()
+      },
+      &mut out,
     )
     .expect("Install failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains(
+      "ℹ️  Installing as different, the name inferred from the module is echo_server"
+    ));
 
-    let mut file_path = bin_dir.join("echo_server");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
+    for name in &["echo_server", "@tools/echo_server"] {
+      let mut out: Vec<u8> = vec![];
+      install(
+        Flags::default(),
+        InstallFlags {
+          name: Some(name.to_string()),
+          ..install_flags.clone()
+        },
+        &mut out,
+      )
+      .expect("Install failed");
+      assert!(!String::from_utf8(out).unwrap().contains("Installing as"));
     }
-
-    assert!(file_path.exists());
-    let content = fs::read_to_string(file_path).unwrap();
-    assert!(content
-      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
   }
 
   #[test]
-  fn install_inferred_name_from_parent() {
+  fn shim_path_for_name() {
     let temp_dir = TempDir::new().expect("tempdir fail");
-    let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
+    let root = Some(temp_dir.path().to_path_buf());
+    let bin_dir = temp_dir.path().canonicalize().unwrap().join("bin");
 
-    install(
+    let path = shim_path(root.clone(), None, "echo_test").unwrap();
+    if cfg!(windows) {
+      assert_eq!(path, bin_dir.join("echo_test.cmd"));
+    } else {
+      assert_eq!(path, bin_dir.join("echo_test"));
+    }
+    assert!(!path.exists());
+
+    let result = install(
       Flags::default(),
-      "http://localhost:4545/cli/tests/subdir/main.ts",
-      vec![],
-      None,
-      Some(temp_dir.path().to_path_buf()),
-      false,
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: root.clone(),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
     )
     .expect("Install failed");
+    assert_eq!(result.path, path);
 
-    let mut file_path = bin_dir.join("subdir");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
-    }
-
-    assert!(file_path.exists());
-    let content = fs::read_to_string(file_path).unwrap();
-    assert!(content
-      .contains(r#""run" "http://localhost:4545/cli/tests/subdir/main.ts""#));
+    let err = shim_path(root, None, "echo test").unwrap_err();
+    assert!(err
+      .to_string()
+      .contains("Invalid executable name: echo test"));
   }
 
   #[test]
-  fn install_custom_dir_option() {
+  fn install_header_comments() {
+    let _guard = ENV_LOCK.lock().ok();
     let temp_dir = TempDir::new().expect("tempdir fail");
     let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
 
     install(
       Flags::default(),
-      "http://localhost:4545/cli/tests/echo_server.ts",
-      vec![],
-      Some("echo_test".to_string()),
-      Some(temp_dir.path().to_path_buf()),
-      false,
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo%20server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        targets: vec!["sh".to_string(), "cmd".to_string()],
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
     )
     .expect("Install failed");
 
-    let mut file_path = bin_dir.join("echo_test");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
+    let installed_at = InstallMetadata::read(&bin_dir.join("echo_test"))
+      .unwrap()
+      .installed_at;
+    let timestamp_re =
+      Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z$").unwrap();
+    for (file_name, comment, module_url) in &[
+      (
+        "echo_test",
+        "# ",
+        "http://localhost:4545/cli/tests/echo%20server.ts",
+      ),
+      (
+        "echo_test.cmd",
+        "@rem ",
+        "http://localhost:4545/cli/tests/echo%%20server.ts",
+      ),
+    ] {
+      let content = fs::read_to_string(bin_dir.join(file_name)).unwrap();
+      let lines: Vec<&str> = content.lines().collect();
+      assert!(
+        lines.contains(&format!("{}module: {}", comment, module_url).as_str())
+      );
+      let timestamp = lines
+        .iter()
+        .find_map(|line| line.strip_prefix(&format!("{}installed: ", comment)))
+        .unwrap();
+      assert!(timestamp_re.is_match(timestamp));
+      assert_eq!(timestamp, format_timestamp(installed_at));
     }
+  }
 
-    assert!(file_path.exists());
-    let content = fs::read_to_string(file_path).unwrap();
-    assert!(content
-      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
+  #[test]
+  fn install_format_timestamp() {
+    assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+    assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+    assert_eq!(format_timestamp(1_598_922_061), "2020-09-01T01:01:01Z");
+    assert_eq!(format_timestamp(1_609_459_199), "2020-12-31T23:59:59Z");
   }
 
   #[test]
-  fn install_custom_dir_env_var() {
+  fn render_sh_shebang() {
+    let args = vec!["run".to_string(), "echo_server.ts".to_string()];
+    let sh = ExecutableKind::Sh.render(None, &[], None, &args);
+    assert_eq!(sh.lines().next(), Some("#!/usr/bin/env sh"));
+  }
+
+  #[test]
+  fn generated_executable_kind_legacy_shebang() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let file_path = temp_dir.path().join("echo_test");
+    fs::write(
+      &file_path,
+      "#!/bin/sh\n# generated by deno install\ndeno \"run\" \"echo_server.ts\" \"$@\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+      get_generated_executable_kind(&file_path),
+      Some(ExecutableKind::Sh)
+    );
+  }
+
+  #[test]
+  fn render_run_dir() {
+    let args = vec!["run".to_string(), "echo_server.ts".to_string()];
+    let run_dir = Path::new("/srv/my \"app\" 100%");
+
+    let sh = ExecutableKind::Sh.render(None, &[], Some(run_dir), &args);
+    assert_eq!(
+      sh,
+      r#"#!/usr/bin/env sh
+# generated by deno install
+cd "/srv/my \"app\" 100%" || exit
+deno "run" "echo_server.ts" "$@"
+"#
+    );
+    let cmd = ExecutableKind::Cmd.render(None, &[], Some(run_dir), &args);
+    assert_eq!(
+      cmd,
+      "% generated by deno install %\n@setlocal\n@cd /d \"/srv/my \"\"app\"\" 100%%\" || exit /b 1\n@deno.exe \"run\" \"echo_server.ts\" %*\n@exit /b %errorlevel%\n"
+    );
+    let ps1 = ExecutableKind::Ps1.render(None, &[], Some(run_dir), &args);
+    assert!(ps1.contains(
+      "\nPush-Location -LiteralPath '/srv/my \"app\" 100%' -ErrorAction Stop\n"
+    ));
+    assert!(ps1.ends_with(
+      " @args\n$exitCode = $LASTEXITCODE\nPop-Location\nexit $exitCode\n"
+    ));
+  }
+
+  #[test]
+  fn install_run_dir() {
     let _guard = ENV_LOCK.lock().ok();
     let temp_dir = TempDir::new().expect("tempdir fail");
     let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
-    let original_install_root = env::var_os("DENO_INSTALL_ROOT");
-    env::set_var("DENO_INSTALL_ROOT", temp_dir.path().to_path_buf());
+    let run_dir = temp_dir.path().join("my app");
 
     install(
       Flags::default(),
-      "http://localhost:4545/cli/tests/echo_server.ts",
-      vec![],
-      Some("echo_test".to_string()),
-      None,
-      false,
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        targets: vec!["sh".to_string(), "cmd".to_string()],
+        run_dir: Some(run_dir.clone()),
+        ..InstallFlags::default()
+      },
+      &mut io::sink(),
     )
     .expect("Install failed");
 
-    let mut file_path = bin_dir.join("echo_test");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
-    }
+    let sh = fs::read_to_string(bin_dir.join("echo_test")).unwrap();
+    assert!(sh.lines().any(
+      |line| line == format!("cd \"{}\" || exit", run_dir.to_string_lossy())
+    ));
+    let cmd = fs::read_to_string(bin_dir.join("echo_test.cmd")).unwrap();
+    assert!(cmd.lines().any(|line| line
+      == format!("@cd /d \"{}\" || exit /b 1", run_dir.to_string_lossy())));
+    let metadata = InstallMetadata::read(&bin_dir.join("echo_test")).unwrap();
+    assert_eq!(metadata.run_dir, Some(run_dir.clone()));
+    let (_, install_flags) = get_reinstall_flags(
+      "echo_test",
+      Some(temp_dir.path().to_path_buf()),
+      None,
+    )
+    .unwrap();
+    assert_eq!(install_flags.run_dir, Some(run_dir));
+    let shim_info = inspect_shim(&bin_dir.join("echo_test")).unwrap();
+    assert_eq!(
+      shim_info.module_url,
+      "http://localhost:4545/cli/tests/echo_server.ts"
+    );
+  }
 
-    assert!(file_path.exists());
-    let content = fs::read_to_string(file_path).unwrap();
-    assert!(content
-      .contains(r#""run" "http://localhost:4545/cli/tests/echo_server.ts""#));
-    if let Some(install_root) = original_install_root {
-      env::set_var("DENO_INSTALL_ROOT", install_root);
-    }
+  #[test]
+  fn render_env() {
+    let env = vec![
+      ("NO_COLOR".to_string(), "1".to_string()),
+      (
+        "GREETING".to_string(),
+        "say \"hi\" $USER 100% it's".to_string(),
+      ),
+      ("PIPE".to_string(), "\"a|b\" c|d".to_string()),
+    ];
+    let args = vec!["run".to_string(), "echo_server.ts".to_string()];
+
+    assert_eq!(
+      ExecutableKind::Sh.render(None, &env, None, &args),
+      r#"#!/usr/bin/env sh
+# generated by deno install
+export NO_COLOR="1"
+export GREETING="say \"hi\" \$USER 100% it's"
+export PIPE="\"a|b\" c|d"
+deno "run" "echo_server.ts" "$@"
+"#
+    );
+    assert_eq!(
+      ExecutableKind::Cmd.render(None, &env, None, &args),
+      "% generated by deno install %\n@setlocal\n@set \"NO_COLOR=1\"\n@set \"GREETING=say \"hi\" $USER 100%% it's\"\n@set \"PIPE=\"a^|b\" c|d\"\n@deno.exe \"run\" \"echo_server.ts\" %*\n@exit /b %errorlevel%\n"
+    );
+    let ps1 = ExecutableKind::Ps1.render(None, &env, None, &args);
+    assert!(ps1.contains(
+      "\n$env:NO_COLOR = '1'\n$env:GREETING = 'say \"hi\" $USER 100% it''s'\n$env:PIPE = '\"a|b\" c|d'\n"
+    ));
+    assert!(ps1.find("$env:GREETING").unwrap() < ps1.find(" 'run'").unwrap());
+    assert!(!ExecutableKind::Cmd
+      .render(None, &[], None, &args)
+      .contains("setlocal"));
   }
 
   #[test]
-  fn install_with_flags() {
+  fn install_env() {
+    let _guard = ENV_LOCK.lock().ok();
     let temp_dir = TempDir::new().expect("tempdir fail");
     let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      env: vec![("NO_COLOR".to_string(), "1".to_string())],
+      ..InstallFlags::default()
+    };
 
-    install(
-      Flags {
-        allow_net: true,
-        allow_read: true,
-        no_check: true,
-        log_level: Some(Level::Error),
-        ..Flags::default()
-      },
-      "http://localhost:4545/cli/tests/echo_server.ts",
-      vec!["--foobar".to_string()],
-      Some("echo_test".to_string()),
+    install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .expect("Install failed");
+    let file_path = get_executable_path(&bin_dir, "echo_test");
+    let content = fs::read_to_string(&file_path).unwrap();
+    let env_line = if cfg!(windows) {
+      "@set \"NO_COLOR=1\"\n"
+    } else {
+      "export NO_COLOR=\"1\"\n"
+    };
+    let env_index = content.find(env_line).unwrap();
+    assert!(env_index < content.find("\"run\"").unwrap());
+
+    reinstall(
+      "echo_test".to_string(),
       Some(temp_dir.path().to_path_buf()),
-      false,
+      None,
+      &mut io::sink(),
     )
-    .expect("Install failed");
-
-    let mut file_path = bin_dir.join("echo_test");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
-    }
+    .expect("Reinstall failed");
+    assert_eq!(fs::read_to_string(&file_path).unwrap(), content);
 
-    assert!(file_path.exists());
-    let content = fs::read_to_string(file_path).unwrap();
-    assert!(content.contains(r#""run" "--allow-read" "--allow-net" "--quiet" "--no-check" "http://localhost:4545/cli/tests/echo_server.ts" "--foobar""#));
+    let err = install(
+      Flags::default(),
+      InstallFlags {
+        env: vec![("NO-COLOR".to_string(), "1".to_string())],
+        force: true,
+        ..install_flags
+      },
+      &mut io::sink(),
+    )
+    .unwrap_err();
+    assert!(err
+      .to_string()
+      .contains("Invalid environment variable name: NO-COLOR"));
   }
 
   #[test]
-  fn install_local_module() {
+  fn install_invalid_remote_url() {
     let temp_dir = TempDir::new().expect("tempdir fail");
-    let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
-    let local_module = env::current_dir().unwrap().join("echo_server.ts");
-    let local_module_url = Url::from_file_path(&local_module).unwrap();
-    let local_module_str = local_module.to_string_lossy();
+    let install_flags = InstallFlags {
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
 
-    install(
+    for verify in &[false, true] {
+      let err = install(
+        Flags::default(),
+        InstallFlags {
+          module_url: "http://[::bad".to_string(),
+          verify: *verify,
+          ..install_flags.clone()
+        },
+        &mut io::sink(),
+      )
+      .unwrap_err();
+      assert_eq!(err.kind(), ErrorKind::InvalidInput);
+      assert!(err
+        .to_string()
+        .starts_with("Invalid module URL http://[::bad: "));
+    }
+
+    let module_url =
+      Url::parse("http://localhost:4545/cli/tests/echo_server.ts").unwrap();
+    let result = install_url(
       Flags::default(),
-      &local_module_str,
-      vec![],
-      Some("echo_test".to_string()),
-      Some(temp_dir.path().to_path_buf()),
-      false,
+      install_flags,
+      &module_url,
+      &mut io::sink(),
     )
     .expect("Install failed");
+    let content = fs::read_to_string(result.path).unwrap();
+    assert!(content.contains(module_url.as_str()));
+  }
 
-    let mut file_path = bin_dir.join("echo_test");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
-    }
+  #[test]
+  fn install_local_dir() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let bin_dir = temp_dir.path().join("bin");
+    let tool_dir = temp_dir.path().join("mytool");
+    fs::create_dir(&tool_dir).unwrap();
+    let install_flags = InstallFlags {
+      module_url: format!("{}/", tool_dir.to_string_lossy()),
+      root: Some(temp_dir.path().to_path_buf()),
+      ..InstallFlags::default()
+    };
 
-    assert!(file_path.exists());
-    let content = fs::read_to_string(file_path).unwrap();
-    assert!(content.contains(&local_module_url.to_string()));
+    let err = install(Flags::default(), install_flags.clone(), &mut io::sink())
+      .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert!(err
+      .to_string()
+      .contains("looked for mod.ts, main.ts, index.ts, cli.ts"));
+    assert!(!get_executable_path(&bin_dir, "mytool").exists());
+
+    fs::write(tool_dir.join("main.ts"), "").unwrap();
+    fs::write(tool_dir.join("cli.ts"), "").unwrap();
+    let result = install(Flags::default(), install_flags, &mut io::sink())
+      .expect("Install failed");
+    assert_eq!(result.name, "mytool");
+    let main_url =
+      Url::from_file_path(tool_dir.join("main.ts").canonicalize().unwrap())
+        .unwrap();
+    let content = fs::read_to_string(result.path).unwrap();
+    assert!(content.contains(&format!("\"{}\"", main_url)));
   }
 
   #[test]
-  fn install_force() {
+  fn resolve_module_url_of_dir() {
     let temp_dir = TempDir::new().expect("tempdir fail");
-    let bin_dir = temp_dir.path().join("bin");
-    std::fs::create_dir(&bin_dir).unwrap();
+    let tool_dir = temp_dir.path().join("mytool");
+    fs::create_dir(&tool_dir).unwrap();
+    fs::write(tool_dir.join("mod.ts"), "").unwrap();
+    fs::write(tool_dir.join("deno.json"), r#"{"name": "tool"}"#).unwrap();
+    let mod_url =
+      Url::from_file_path(tool_dir.join("mod.ts").canonicalize().unwrap())
+        .unwrap();
 
-    install(
-      Flags::default(),
+    let (url, package_name) =
+      resolve_module_url("./mytool/", temp_dir.path()).unwrap();
+    assert_eq!(url, mod_url);
+    assert_eq!(package_name.as_deref(), Some("tool"));
+    let (url, _) = resolve_module_url(
+      Url::from_file_path(&tool_dir).unwrap().as_str(),
+      temp_dir.path(),
+    )
+    .unwrap();
+    assert_eq!(url, mod_url);
+    let (url, package_name) = resolve_module_url(
       "http://localhost:4545/cli/tests/echo_server.ts",
-      vec![],
-      Some("echo_test".to_string()),
-      Some(temp_dir.path().to_path_buf()),
-      false,
+      temp_dir.path(),
+    )
+    .unwrap();
+    assert_eq!(
+      url.as_str(),
+      "http://localhost:4545/cli/tests/echo_server.ts"
+    );
+    assert_eq!(package_name, None);
+  }
+
+  #[test]
+  fn install_granted_permissions() {
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      force: true,
+      ..InstallFlags::default()
+    };
+    let flags = Flags {
+      allow_read: true,
+      allow_net: true,
+      ..Flags::default()
+    };
+
+    let mut out: Vec<u8> = vec![];
+    install(flags.clone(), install_flags.clone(), &mut out)
+      .expect("Install failed");
+    let output = String::from_utf8(out).unwrap();
+    assert!(output.contains("\nGranted: --allow-read, --allow-net\n"));
+
+    let mut out: Vec<u8> = vec![];
+    install(
+      Flags {
+        log_level: Some(Level::Error),
+        ..flags
+      },
+      install_flags.clone(),
+      &mut out,
     )
     .expect("Install failed");
+    assert!(!String::from_utf8(out).unwrap().contains("Granted"));
 
-    let mut file_path = bin_dir.join("echo_test");
-    if cfg!(windows) {
-      file_path = file_path.with_extension("cmd");
-    }
-    assert!(file_path.exists());
+    let mut out: Vec<u8> = vec![];
+    install(Flags::default(), install_flags, &mut out).expect("Install failed");
+    assert!(!String::from_utf8(out).unwrap().contains("Granted"));
+  }
 
-    // No force. Install failed.
-    let no_force_result = install(
-      Flags::default(),
-      "http://localhost:4545/cli/tests/cat.ts", // using a different URL
-      vec![],
-      Some("echo_test".to_string()),
-      Some(temp_dir.path().to_path_buf()),
-      false,
-    );
-    assert!(no_force_result.is_err());
-    assert!(no_force_result
-      .unwrap_err()
-      .to_string()
-      .contains("Existing installation found"));
-    // Assert not modified
-    let file_content = fs::read_to_string(&file_path).unwrap();
-    assert!(file_content.contains("echo_server.ts"));
+  #[test]
+  fn install_no_check_modes() {
+    let _guard = ENV_LOCK.lock().ok();
+    let temp_dir = TempDir::new().expect("tempdir fail");
+    let install_flags = InstallFlags {
+      module_url: "http://localhost:4545/cli/tests/echo_server.ts".to_string(),
+      name: Some("echo_test".to_string()),
+      root: Some(temp_dir.path().to_path_buf()),
+      print_args: true,
+      ..InstallFlags::default()
+    };
 
-    // Force. Install success.
-    let force_result = install(
-      Flags::default(),
-      "http://localhost:4545/cli/tests/cat.ts", // using a different URL
-      vec![],
-      Some("echo_test".to_string()),
-      Some(temp_dir.path().to_path_buf()),
-      true,
-    );
-    assert!(force_result.is_ok());
-    // Assert modified
-    let file_content_2 = fs::read_to_string(&file_path).unwrap();
-    assert!(file_content_2.contains("cat.ts"));
+    for (no_check, expected_args) in &[
+      (NoCheck::All, vec!["run", "--no-check"]),
+      (NoCheck::Remote, vec!["run", "--no-check=remote"]),
+      (NoCheck::None, vec!["run"]),
+    ] {
+      let mut out: Vec<u8> = vec![];
+      install(
+        Flags {
+          no_check: *no_check,
+          ..Flags::default()
+        },
+        install_flags.clone(),
+        &mut out,
+      )
+      .expect("Install failed");
+      let args: Vec<String> = serde_json::from_slice(&out).unwrap();
+      assert_eq!(&args[..args.len() - 1], &expected_args[..]);
+    }
   }
 
+  #[cfg(not(windows))]
   #[test]
-  fn install_with_config() {
+  fn install_dir_not_writable() {
     let temp_dir = TempDir::new().expect("tempdir fail");
     let bin_dir = temp_dir.path().join("bin");
-    let config_file_path = temp_dir.path().join("test_tsconfig.json");
-    let config = "{}";
-    let mut config_file = File::create(&config_file_path).unwrap();
-    let result = config_file.write_all(config.as_bytes());
-    assert!(result.is_ok());
+    fs::create_dir(&bin_dir).unwrap();
+    fs::set_permissions(&bin_dir, fs::Permissions::from_mode(0o500)).unwrap();
+    // Permissions don't apply to root.
+    let is_writable = File::create(bin_dir.join("probe")).is_ok();
 
     let result = install(
-      Flags {
-        config_path: Some(config_file_path.to_string_lossy().to_string()),
-        ..Flags::default()
+      Flags::default(),
+      InstallFlags {
+        module_url: "http://localhost:4545/cli/tests/echo_server.ts"
+          .to_string(),
+        name: Some("echo_test".to_string()),
+        root: Some(temp_dir.path().to_path_buf()),
+        ..InstallFlags::default()
       },
-      "http://localhost:4545/cli/tests/cat.ts",
-      vec![],
-      Some("echo_test".to_string()),
-      Some(temp_dir.path().to_path_buf()),
-      true,
+      &mut io::sink(),
     );
-    eprintln!("result {:?}", result);
-    assert!(result.is_ok());
-
-    let config_file_name = "echo_test.tsconfig.json";
+    fs::set_permissions(&bin_dir, fs::Permissions::from_mode(0o755)).unwrap();
+    if is_writable {
+      return;
+    }
 
-    let file_path = bin_dir.join(config_file_name.to_string());
-    assert!(file_path.exists());
-    let content = fs::read_to_string(file_path).unwrap();
-    assert!(content == "{}");
+    let err = result.unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      format!(
+        "Installation directory is not writable: {}",
+        bin_dir.canonicalize().unwrap().to_string_lossy()
+      )
+    );
+    assert_eq!(
+      fs::read_dir(&bin_dir).unwrap().count(),
+      0,
+      "nothing is written"
+    );
   }
 }
@@ -87,6 +87,7 @@ use deno_doc as doc;
 use deno_doc::parser::DocFileLoader;
 use flags::DenoSubcommand;
 use flags::Flags;
+use flags::InstallFlags;
 use futures::future::FutureExt;
 use futures::Future;
 use log::Level;
@@ -328,23 +329,254 @@ async fn info_command(
 
 async fn install_command(
   flags: Flags,
-  module_url: String,
-  args: Vec<String>,
-  name: Option<String>,
-  root: Option<PathBuf>,
-  force: bool,
+  install_flags: InstallFlags,
 ) -> Result<(), ErrBox> {
+  // With --json, whichever step fails, the error is reported as JSON.
+  let json = install_flags.json;
+  let result = run_install_command(flags, install_flags).await;
+  if let Err(err) = &result {
+    if json {
+      println!("{}", install_error_to_json(err));
+      std::process::exit(1);
+    }
+  }
+  result
+}
+
+/// Renders `err` like `installer::error_to_json`, the errors of the steps
+/// around `installer::install` included.
+fn install_error_to_json(err: &ErrBox) -> String {
+  if let Some(err) = err.downcast_ref::<installer::InstallError>() {
+    return installer::error_to_json(err);
+  }
+  let io_err = err.downcast_ref::<std::io::Error>();
+  // Converted to an io::Error by the functions of the installer that return
+  // one.
+  if let Some(err) = io_err
+    .and_then(|err| err.get_ref())
+    .and_then(|err| err.downcast_ref::<installer::InstallError>())
+  {
+    return installer::error_to_json(err);
+  }
+  let kind = io_err.map_or(std::io::ErrorKind::Other, |err| err.kind());
+  installer::error_to_json(&installer::InstallError::Io(std::io::Error::new(
+    kind,
+    err.to_string(),
+  )))
+}
+
+async fn run_install_command(
+  flags: Flags,
+  install_flags: InstallFlags,
+) -> Result<(), ErrBox> {
+  if let Some(name) = install_flags.info {
+    return installer::info(
+      &name,
+      install_flags.root,
+      install_flags.bin_subdir.as_deref(),
+      install_flags.json,
+      &mut std::io::stdout(),
+    )
+    .map_err(ErrBox::from);
+  }
+  if let Some(name) = install_flags.check_name {
+    return installer::check_name(
+      &name,
+      install_flags.root,
+      install_flags.bin_subdir.as_deref(),
+      &mut std::io::stdout(),
+    )
+    .map_err(ErrBox::from);
+  }
+  if let Some(shell) = install_flags.set_default_shell {
+    return installer::set_default_shell(&shell, &mut std::io::stdout())
+      .map_err(ErrBox::from);
+  }
+  if install_flags.list {
+    return installer::list(
+      install_flags.root,
+      install_flags.bin_subdir.as_deref(),
+      install_flags.json,
+      &mut std::io::stdout(),
+    )
+    .map_err(ErrBox::from);
+  }
+  if install_flags.doctor {
+    return installer::doctor(
+      install_flags.root,
+      install_flags.bin_subdir.as_deref(),
+      &mut std::io::stdout(),
+    )
+    .map_err(ErrBox::from);
+  }
+  if install_flags.prune {
+    let removed_files = installer::prune(
+      install_flags.root,
+      install_flags.bin_subdir.as_deref(),
+    )?;
+    for file_path in &removed_files {
+      println!("Removed {}", file_path.to_string_lossy());
+    }
+    println!("✅ Removed {} orphaned files", removed_files.len());
+    return Ok(());
+  }
+
+  if install_flags.many {
+    return install_many_command(flags, install_flags).await;
+  }
+  if let (Some(name), true) =
+    (&install_flags.reinstall, install_flags.metadata_only)
+  {
+    return installer::regenerate_executables(
+      name,
+      install_flags.root,
+      install_flags.bin_subdir.as_deref(),
+      &mut std::io::stdout(),
+    )
+    .map_err(ErrBox::from);
+  }
+  let (flags, mut install_flags) = match install_flags.reinstall.clone() {
+    Some(name) => installer::get_reinstall_flags(
+      &name,
+      install_flags.root,
+      install_flags.bin_subdir.as_deref(),
+    )?,
+    None => (flags, install_flags),
+  };
+  installer::resolve_module_file(&mut install_flags)?;
+  installer::resolve_module_stdin(
+    &mut install_flags,
+    &mut std::io::stdin().lock(),
+  )?;
+
   // Firstly fetch and compile module, this step ensures that module exists.
   let mut fetch_flags = flags.clone();
   fetch_flags.reload = true;
+  // --cached-only and the inspector are for the installed script, not for
+  // fetching it now.
+  fetch_flags.cached_only = false;
+  fetch_flags.inspect = None;
+  fetch_flags.inspect_brk = None;
   let global_state = GlobalState::new(fetch_flags)?;
-  let main_module = ModuleSpecifier::resolve_url_or_path(&module_url)?;
+  // The module the executables run, e.g. the entry file of a directory.
+  let module_url =
+    installer::expand_module_shorthand(&install_flags.module_url)?;
+  let (module_url, _) =
+    installer::resolve_module_url(&module_url, &std::env::current_dir()?)?;
+  let main_module = ModuleSpecifier::resolve_url(module_url.as_str())?;
   let mut worker = MainWorker::create(&global_state, main_module.clone())?;
   worker.preload_module(&main_module).await?;
-  installer::install(flags, &module_url, args, name, root, force)
+  if install_flags.pin {
+    install_flags.module_checksum =
+      get_cached_checksum(&global_state, &main_module);
+  }
+  installer::install(flags, install_flags, &mut std::io::stdout())
+    .map(|_| ())
     .map_err(ErrBox::from)
 }
 
+/// The checksum of `module` as the file fetcher cached it, which is the source
+/// the installed script runs, computed as in lock files.
+fn get_cached_checksum(
+  global_state: &GlobalState,
+  module: &ModuleSpecifier,
+) -> Option<String> {
+  let source_file = global_state
+    .file_fetcher
+    .fetch_cached_source_file(module, Permissions::allow_all())?;
+  let source_code = source_file.source_code.to_str().ok()?;
+  Some(checksum::gen(&[source_code.as_bytes()]))
+}
+
+async fn install_many_command(
+  flags: Flags,
+  mut install_flags: InstallFlags,
+) -> Result<(), ErrBox> {
+  let mut modules = vec![std::mem::take(&mut install_flags.module_url)];
+  modules.append(&mut install_flags.args);
+
+  // Modules that can't be fetched are reported along with the failed
+  // installations.
+  let mut fetch_flags = flags.clone();
+  fetch_flags.reload = true;
+  // --cached-only and the inspector are for the installed script, not for
+  // fetching it now.
+  fetch_flags.cached_only = false;
+  fetch_flags.inspect = None;
+  fetch_flags.inspect_brk = None;
+  let global_state = GlobalState::new(fetch_flags)?;
+  let mut fetch_errors = vec![];
+  let mut fetched_modules = vec![];
+  for module_url in &modules {
+    let preloaded: Result<ModuleSpecifier, ErrBox> = async {
+      let module_url = installer::expand_module_shorthand(&module_url)?;
+      let (module_url, _) =
+        installer::resolve_module_url(&module_url, &std::env::current_dir()?)?;
+      let main_module = ModuleSpecifier::resolve_url(module_url.as_str())?;
+      let mut worker = MainWorker::create(&global_state, main_module.clone())?;
+      worker.preload_module(&main_module).await?;
+      Ok(main_module)
+    }
+    .await;
+    match preloaded {
+      Ok(main_module) => {
+        let module_checksum = if install_flags.pin {
+          get_cached_checksum(&global_state, &main_module)
+        } else {
+          None
+        };
+        fetch_errors.push(None);
+        fetched_modules.push((module_url.clone(), module_checksum));
+      }
+      Err(err) => fetch_errors.push(Some(installer::InstallError::Io(
+        std::io::Error::new(std::io::ErrorKind::Other, err.to_string()),
+      ))),
+    }
+  }
+
+  let json = install_flags.json;
+  let mut out: Box<dyn std::io::Write> = if json {
+    Box::new(std::io::sink())
+  } else {
+    Box::new(std::io::stdout())
+  };
+  let mut installed =
+    installer::install_many(flags, install_flags, fetched_modules, &mut out)
+      .into_iter();
+  // In the order the modules were given, fetched or not.
+  let results: Vec<_> = modules
+    .into_iter()
+    .zip(fetch_errors)
+    .map(|(module_url, fetch_error)| match fetch_error {
+      Some(err) => (module_url, Err(err)),
+      None => installed
+        .next()
+        .expect("Each fetched module should have been installed"),
+    })
+    .collect();
+  installer::write_install_summary(&results, json, &mut std::io::stdout())?;
+  if results.iter().any(|(_, result)| result.is_err()) {
+    std::process::exit(1);
+  }
+  Ok(())
+}
+
+async fn uninstall_command(
+  name: String,
+  root: Option<PathBuf>,
+  bin_subdir: Option<String>,
+  json: bool,
+) -> Result<(), ErrBox> {
+  installer::uninstall(
+    name,
+    root,
+    bin_subdir.as_deref(),
+    json,
+    &mut std::io::stdout(),
+  )
+  .map_err(ErrBox::from)
+}
+
 async fn lint_command(
   flags: Flags,
   files: Vec<String>,
@@ -765,14 +997,8 @@ pub fn main() {
     DenoSubcommand::Info { file, json } => {
       info_command(flags, file, json).boxed_local()
     }
-    DenoSubcommand::Install {
-      module_url,
-      args,
-      name,
-      root,
-      force,
-    } => {
-      install_command(flags, module_url, args, name, root, force).boxed_local()
+    DenoSubcommand::Install(install_flags) => {
+      install_command(flags, install_flags).boxed_local()
     }
     DenoSubcommand::Lint {
       files,
@@ -805,6 +1031,12 @@ pub fn main() {
       }
       return;
     }
+    DenoSubcommand::Uninstall {
+      name,
+      root,
+      bin_subdir,
+      json,
+    } => uninstall_command(name, root, bin_subdir, json).boxed_local(),
     DenoSubcommand::Upgrade {
       force,
       dry_run,
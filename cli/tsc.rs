@@ -6,6 +6,7 @@ use crate::disk_cache::DiskCache;
 use crate::file_fetcher::SourceFile;
 use crate::file_fetcher::SourceFileFetcher;
 use crate::flags::Flags;
+use crate::flags::NoCheck;
 use crate::fmt_errors::JSError;
 use crate::global_state::GlobalState;
 use crate::module_graph::ModuleGraph;
@@ -401,6 +402,13 @@ struct BundleResponse {
   stats: Option<Vec<Stat>>,
 }
 
+/// Whether `item` is about a remote module, as opposed to a local one.
+fn is_remote_diagnostic(item: &DiagnosticItem) -> bool {
+  item.script_resource_name.as_ref().map_or(false, |name| {
+    name.starts_with("http://") || name.starts_with("https://")
+  })
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct CompileResponse {
@@ -614,8 +622,17 @@ impl TsCompiler {
     let json_str =
       execute_in_same_thread(global_state, permissions, req_msg).await?;
 
-    let compile_response: CompileResponse = serde_json::from_str(&json_str)?;
+    let mut compile_response: CompileResponse =
+      serde_json::from_str(&json_str)?;
 
+    // With --no-check=remote, remote modules are compiled along with the
+    // local ones, but only the errors of the local ones are reported.
+    if self.flags.no_check == NoCheck::Remote {
+      compile_response
+        .diagnostics
+        .items
+        .retain(|item| !is_remote_diagnostic(item));
+    }
     if !compile_response.diagnostics.items.is_empty() {
       return Err(ErrBox::error(compile_response.diagnostics.to_string()));
     }